@@ -0,0 +1,187 @@
+//! Cost of making a call, without the daemon: a transport that answers every request with the
+//! same canned reply stands in for the socket.
+//!
+//! Run with `cargo bench --bench requests`. Before the timings, the number of allocations per
+//! call is printed, which unlike the timings does not depend on the machine.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use criterion::{criterion_group, Criterion};
+use serde_json::{json, Value};
+
+use clightningrpc::client::{Client, IdGenerator};
+use clightningrpc::transport::RpcTransport;
+use clightningrpc::{requests, LightningRPC};
+
+/// Counts allocations, to report them per call
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Average number of allocations made by {f}, after warming up
+fn allocations_per_call<F: FnMut()>(mut f: F) -> f64 {
+    const CALLS: usize = 1000;
+    f();
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..CALLS {
+        f();
+    }
+    (ALLOCATIONS.load(Ordering::Relaxed) - before) as f64 / CALLS as f64
+}
+
+/// Answers each write with {reply}, which must have id 1
+struct CannedTransport {
+    reply: Vec<u8>,
+    pending: Vec<u8>,
+    connected: bool,
+}
+
+impl CannedTransport {
+    fn new(result: Value) -> CannedTransport {
+        let reply = json!({"jsonrpc": "2.0", "id": 1, "result": result});
+        CannedTransport {
+            reply: serde_json::to_vec(&reply).unwrap(),
+            pending: Vec::new(),
+            connected: false,
+        }
+    }
+}
+
+impl Read for CannedTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+}
+
+impl Write for CannedTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(&self.reply);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl RpcTransport for CannedTransport {
+    fn connect(&mut self) -> io::Result<()> {
+        self.connected = true;
+        Ok(())
+    }
+
+    fn disconnect(&mut self) {
+        self.connected = false;
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    fn set_timeout(&mut self, _timeout: Option<Duration>) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Gives every request id 1, to match the canned reply
+struct SameId;
+
+impl IdGenerator for SameId {
+    fn next_id(&mut self, _nonce: u64) -> Value {
+        Value::from(1)
+    }
+}
+
+fn route() -> Value {
+    json!({"route": [{
+        "id": "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59",
+        "channel": "103x1x0",
+        "direction": 1,
+        "amount_msat": 100000,
+        "delay": 9,
+        "style": "tlv"
+    }]})
+}
+
+/// A `getroute` call through `LightningRPC`
+fn getroute_call() -> impl FnMut() {
+    let mut rpc = LightningRPC::with_transport(CannedTransport::new(route()));
+    rpc.set_id_generator(SameId);
+    let exclude = vec!["104x1x0/0".to_owned()];
+    move || {
+        rpc.getroute(
+            "035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d",
+            100_000,
+            10.0,
+            None,
+            None,
+            Some(0.0),
+            &exclude,
+            None,
+        )
+        .unwrap();
+    }
+}
+
+/// Sending the same request through `client::Client` over and over
+fn send_request_call() -> impl FnMut() {
+    let mut client = Client::with_transport(CannedTransport::new(json!({})));
+    client.set_id_generator(SameId);
+    let params = serde_json::to_value(requests::Ping {
+        id: "035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d",
+        len: 128,
+        pongbytes: 128,
+    })
+    .unwrap();
+    let request = client.build_request("ping".to_owned(), params);
+    move || {
+        client.send_request(&request).unwrap();
+    }
+}
+
+fn getroute(c: &mut Criterion) {
+    let mut call = getroute_call();
+    c.bench_function("getroute", |b| b.iter(&mut call));
+}
+
+fn send_request(c: &mut Criterion) {
+    let mut call = send_request_call();
+    c.bench_function("send_request", |b| b.iter(&mut call));
+}
+
+criterion_group!(benches, getroute, send_request);
+
+fn main() {
+    println!(
+        "allocations per call: getroute {}, send_request {}",
+        allocations_per_call(getroute_call()),
+        allocations_per_call(send_request_call())
+    );
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+}
@@ -2,6 +2,7 @@ extern crate clightningrpc;
 
 use std::env;
 
+use clightningrpc::requests::FeerateStyle;
 use clightningrpc::LightningRPC;
 
 fn main() {
@@ -13,7 +14,11 @@ fn main() {
 
     println!("getinfo result: {:?}", client.getinfo().unwrap());
 
-    for style in &["perkb", "perkw"] {
-        println!("feerates {}: {:?}", style, client.feerates(style.to_string()).unwrap());
+    for style in &[FeerateStyle::PerKb, FeerateStyle::PerKw] {
+        println!(
+            "feerates {:?}: {:?}",
+            style,
+            client.feerates(*style).unwrap()
+        );
     }
 }
@@ -1,18 +1,16 @@
 extern crate clightningrpc;
-extern crate strason;
+extern crate serde_json;
 
 use std::env;
 
-use strason::Json;
-
 use clightningrpc::{client, requests, responses};
 
 fn main() {
     let mut sock = env::home_dir().unwrap();
     sock.push(".lightning/lightning-rpc");
     println!("Using socket {}", sock.display());
-    let client = client::Client::new(&sock);
-    let params = Json::from_serialize(requests::GetInfo {}).unwrap();
+    let mut client = client::Client::new(&sock);
+    let params = serde_json::to_value(requests::GetInfo {}).unwrap();
     let request = client.build_request("getinfo".to_string(), params);
     match client
         .send_request(&request)
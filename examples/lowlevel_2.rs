@@ -1,21 +1,17 @@
 extern crate clightningrpc;
-extern crate strason;
+extern crate serde_json;
 
 use std::env;
 
-use strason::Json;
-
 use clightningrpc::{client, requests, responses};
 
 fn main() {
     let mut sock = env::home_dir().unwrap();
     sock.push(".lightning/lightning-rpc");
     println!("Using socket {}", sock.display());
-    let client = client::Client::new(&sock);
-    for style in &["perkb", "perkw"] {
-        let params = Json::from_serialize(requests::FeeRates {
-            style: style.to_string(),
-        }).unwrap();
+    let mut client = client::Client::new(&sock);
+    for style in &[requests::FeerateStyle::PerKb, requests::FeerateStyle::PerKw] {
+        let params = serde_json::to_value(requests::FeeRates { style: *style }).unwrap();
         let request = client.build_request("feerates".to_string(), params);
         match client
             .send_request(&request)
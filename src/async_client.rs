@@ -0,0 +1,235 @@
+// Rust JSON-RPC Library
+// Written by
+//     Wladimir J. van der Laan <laanwj@gmail.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+//! Asynchronous client support
+//!
+//! Like `client`, but based on tokio. Only available with the `async` feature.
+//!
+
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+
+use crate::client::{
+    io_error, read_error, CounterIdGenerator, IdGenerator, DEFAULT_MAX_RESPONSE_BYTES,
+};
+use crate::error::Error;
+use crate::framing::{self, Incoming, JsonFramer};
+use crate::{Request, Response};
+
+/// A handle to a remote JSONRPC server, for use from async code
+pub struct AsyncClient {
+    sockpath: PathBuf,
+    timeout: Option<Duration>,
+    max_response_bytes: usize,
+    nonce: Arc<Mutex<u64>>,
+    id_generator: Mutex<Box<dyn IdGenerator>>,
+}
+
+/// Run a socket operation, failing with `Error::Timeout` if it takes longer than `timeout`.
+/// Other IO errors are converted with {convert}.
+async fn with_timeout<T, F>(
+    timeout: Option<Duration>,
+    fut: F,
+    convert: fn(io::Error) -> Error,
+) -> Result<T, Error>
+where
+    F: Future<Output = io::Result<T>>,
+{
+    match timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, fut).await {
+            Ok(res) => res.map_err(convert),
+            Err(_) => Err(Error::Timeout),
+        },
+        None => fut.await.map_err(convert),
+    }
+}
+
+impl AsyncClient {
+    /// Creates a new client
+    pub fn new<P: AsRef<Path>>(sockpath: P) -> AsyncClient {
+        AsyncClient {
+            sockpath: sockpath.as_ref().to_path_buf(),
+            timeout: None,
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            nonce: Arc::new(Mutex::new(0)),
+            id_generator: Mutex::new(Box::new(CounterIdGenerator)),
+        }
+    }
+
+    /// Replace the strategy used to generate request ids, the default is
+    /// `CounterIdGenerator`.
+    pub fn set_id_generator<G: IdGenerator + 'static>(&mut self, id_generator: G) {
+        self.id_generator = Mutex::new(Box::new(id_generator));
+    }
+
+    /// Set the timeout for socket operations, `None` (the default) means block forever.
+    ///
+    /// As with `client::Client::set_timeout`, this applies to every individual read and write.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Limit the size of a single response or notification to {limit} bytes, as with
+    /// `client::Client::set_max_response_bytes`.
+    pub fn set_max_response_bytes(&mut self, limit: usize) {
+        self.max_response_bytes = limit;
+    }
+
+    /// Sends a request to a client
+    pub async fn send_request(&self, request: &Request) -> Result<Response, Error> {
+        let mut request_raw = Vec::new();
+        framing::encode_request_into(&mut request_raw, &request.into())?;
+        framing::log_request(&request.method, &request.id);
+        log_trace!("-> {}", String::from_utf8_lossy(&request_raw));
+
+        let mut stream =
+            with_timeout(self.timeout, UnixStream::connect(&self.sockpath), io_error).await?;
+        with_timeout(self.timeout, stream.write_all(&request_raw), io_error).await?;
+
+        let mut framer = JsonFramer::new();
+        let mut buf = [0u8; 4096];
+        // Notifications are of no use on a connection that only lives for one request
+        let reply = loop {
+            let too_large = match framer.next_frame() {
+                Some(frame) if frame.len() <= self.max_response_bytes => {
+                    match framing::demux(&frame)? {
+                        Incoming::Reply(reply) => break reply,
+                        Incoming::Notification(_) => continue,
+                    }
+                }
+                Some(_) => true,
+                None => framer.buffered() > self.max_response_bytes,
+            };
+            if too_large {
+                return Err(Error::ResponseTooLarge {
+                    limit: self.max_response_bytes,
+                });
+            }
+            let n = with_timeout(self.timeout, stream.read(&mut buf), read_error).await?;
+            if n == 0 {
+                return Err(Error::Io(io::ErrorKind::UnexpectedEof.into()));
+            }
+            framer.push(&buf[..n]);
+        };
+        log_trace!("<- {}", reply);
+        let response = framing::reply_to_response(reply, &request.id)?;
+        framing::log_response(&response);
+        Ok(response)
+    }
+
+    /// Builds a request
+    pub fn build_request(&self, name: String, params: Value) -> Request {
+        let mut nonce = self.nonce.lock().unwrap();
+        *nonce += 1;
+        let id = self.id_generator.lock().unwrap().next_id(*nonce);
+        framing::new_request(name, params, id)
+    }
+
+    /// Accessor for the last-used nonce
+    pub fn last_nonce(&self) -> u64 {
+        *self.nonce.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixListener;
+    use std::thread;
+
+    #[tokio::test]
+    async fn round_trip() {
+        let path = std::env::temp_dir().join(format!("clightningrpc-async-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).unwrap();
+            // Deliver the response in two pieces to exercise the framing
+            stream
+                .write_all(b"{\"jsonrpc\": \"2.0\", \"id\": 1, ")
+                .unwrap();
+            stream
+                .write_all(b"\"result\": {\"ok\": true}}\n\n")
+                .unwrap();
+        });
+
+        let client = AsyncClient::new(&path);
+        let request = client.build_request("test".to_owned(), json!({}));
+        let response = client.send_request(&request).await.unwrap();
+        assert_eq!(response.result, Some(json!({"ok": true})));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn timeout_on_silent_socket() {
+        let path =
+            std::env::temp_dir().join(format!("clightningrpc-async-silent-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = Vec::new();
+            let _ = stream.read_to_end(&mut buf);
+        });
+
+        let mut client = AsyncClient::new(&path);
+        client.set_timeout(Some(Duration::from_millis(100)));
+        let request = client.build_request("getinfo".to_owned(), json!({}));
+        match client.send_request(&request).await {
+            Err(Error::Timeout) => {}
+            other => panic!("expected timeout, got {:?}", other),
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn response_too_large() {
+        let path = std::env::temp_dir().join(format!(
+            "clightningrpc-async-too-large-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).unwrap();
+            // An array that never ends, until the client hangs up
+            stream.write_all(b"[").unwrap();
+            let chunk = [b'1', b','].repeat(512);
+            while stream.write_all(&chunk).is_ok() {}
+        });
+
+        let mut client = AsyncClient::new(&path);
+        client.set_max_response_bytes(16 * 1024);
+        let request = client.build_request("listchannels".to_owned(), json!({}));
+        match client.send_request(&request).await {
+            Err(Error::ResponseTooLarge { limit }) => assert_eq!(limit, 16 * 1024),
+            other => panic!("expected response too large, got {:?}", other),
+        }
+        server.join().unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+}
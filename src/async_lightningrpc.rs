@@ -0,0 +1,1747 @@
+// Rust JSON-RPC Library
+// Written by
+//     Wladimir J. van der Laan <laanwj@gmail.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+//! High-level asynchronous interface to c-lightning RPC
+//!
+//! This mirrors `lightningrpc::LightningRPC`, but every call returns a future. Only available
+//! with the `async` feature.
+use std::path::Path;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::async_client;
+use crate::client;
+use crate::common;
+use crate::error::Error;
+use crate::framing;
+use crate::lightningrpc::{
+    already_connected, connected_peer, fee_for_weight, matching_channels, pay_request, stopped,
+    Capabilities, CloseResults, FeeTarget, FetchInvoiceOptions, KeySendOptions, OfferOptions,
+    ParamStyle, PayOptions, RenePayOptions, SendOnionOptions,
+};
+use crate::requests;
+use crate::responses;
+
+/// Structure providing a high-level asynchronous interface to the c-lightning daemon RPC
+pub struct AsyncLightningRPC {
+    client: async_client::AsyncClient,
+    param_style: ParamStyle,
+}
+
+impl AsyncLightningRPC {
+    /// Create a new connection from a UNIX socket path.
+    ///
+    /// See `LightningRPC::new`.
+    pub fn new<P: AsRef<Path>>(sockpath: P) -> AsyncLightningRPC {
+        AsyncLightningRPC {
+            client: async_client::AsyncClient::new(sockpath),
+            param_style: ParamStyle::Named,
+        }
+    }
+
+    /// Set the timeout for socket operations, `None` (the default) means block forever.
+    /// See `async_client::AsyncClient::set_timeout` for details.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.client.set_timeout(timeout);
+    }
+
+    /// Limit the size of a single response, see `client::Client::set_max_response_bytes`.
+    pub fn set_max_response_bytes(&mut self, limit: usize) {
+        self.client.set_max_response_bytes(limit);
+    }
+
+    /// Choose how parameters are encoded, see `LightningRPC::set_param_style`.
+    pub fn set_param_style(&mut self, param_style: ParamStyle) {
+        self.param_style = param_style;
+    }
+
+    /// Replace the strategy used to generate request ids, see `client::IdGenerator`.
+    pub fn set_id_generator<G: client::IdGenerator + 'static>(&mut self, id_generator: G) {
+        self.client.set_id_generator(id_generator);
+    }
+
+    /// Generic call function for RPC calls, see `LightningRPC::call`.
+    pub async fn call<P: Serialize, R: DeserializeOwned>(
+        &mut self,
+        method: &str,
+        params: P,
+    ) -> Result<R, Error> {
+        self.call_with_extra(method, params, Map::new(), false)
+            .await
+    }
+
+    /// Like `call`, but add {extra} to the parameters as is, see `LightningRPC::call_with_extra`.
+    pub async fn call_with_extra<P: Serialize, R: DeserializeOwned>(
+        &mut self,
+        method: &str,
+        params: P,
+        extra: Map<String, Value>,
+        overwrite: bool,
+    ) -> Result<R, Error> {
+        let params = match self.param_style {
+            ParamStyle::Named => serde_json::to_value(params)?,
+            ParamStyle::Positional => framing::positional_params(&params)?,
+        };
+        let params = framing::merge_params(params, extra, overwrite)?;
+        let request = self.client.build_request(method.to_string(), params);
+        self.client.send_request(&request).await?.into_result::<R>()
+    }
+
+    /// Show information about this node.
+    pub async fn getinfo(&mut self) -> Result<responses::GetInfo, Error> {
+        self.call("getinfo", requests::GetInfo {}).await
+    }
+
+    /// Return feerate estimates, either satoshi-per-kw ({style} perkw) or satoshi-per-kb ({style}
+    /// perkb).
+    pub async fn feerates(
+        &mut self,
+        style: requests::FeerateStyle,
+    ) -> Result<responses::FeeRates, Error> {
+        self.call("feerates", requests::FeeRates { style }).await
+    }
+
+    /// Estimate the fee in msat of a transaction of {weight} (in weight units, four times the
+    /// virtual size) for {target}, from the current `feerates` of lightningd.
+    ///
+    /// Fails with `Error::FeerateUnavailable` if lightningd has no estimate for {target}, as is
+    /// the case while bitcoind is starting up.
+    pub async fn estimate_fee_for_weight(
+        &mut self,
+        weight: u64,
+        target: FeeTarget,
+    ) -> Result<u64, Error> {
+        let rates = self.feerates(requests::FeerateStyle::PerKw).await?;
+        fee_for_weight(&rates, weight, target)
+    }
+
+    /// Show node {id} (or all, if no {id}), in our local network view.
+    ///
+    /// See `LightningRPC::listnodes` about the size of the response.
+    pub async fn listnodes(&mut self, id: Option<&str>) -> Result<responses::ListNodes, Error> {
+        self.call("listnodes", requests::ListNodes { id }).await
+    }
+
+    /// Show channel {short_channel_id} (or all known channels, if no {short_channel_id}),
+    /// optionally only those from node {source} or to node {destination}.
+    ///
+    /// See `LightningRPC::listchannels` about the size of the response.
+    pub async fn listchannels(
+        &mut self,
+        short_channel_id: Option<&str>,
+        source: Option<&str>,
+        destination: Option<&str>,
+    ) -> Result<responses::ListChannels, Error> {
+        self.call(
+            "listchannels",
+            requests::ListChannels {
+                short_channel_id,
+                source,
+                destination,
+            },
+        )
+        .await
+    }
+
+    /// List available commands, or give verbose help on one command.
+    pub async fn help(&mut self, command: Option<String>) -> Result<responses::Help, Error> {
+        self.call("help", requests::Help { command }).await
+    }
+
+    /// Commands the daemon provides, from `help`
+    ///
+    /// Unlike `LightningRPC::capabilities` this is not cached, every call fetches `help` again and
+    /// so sees commands of plugins that were started or stopped since.
+    pub async fn capabilities(&mut self) -> Result<Capabilities, Error> {
+        Ok(Capabilities::from_help(&self.help(None).await?))
+    }
+
+    /// Show logs, with optional log {level} (info|unusual|debug|io).
+    pub async fn getlog(&mut self, level: Option<&str>) -> Result<responses::GetLog, Error> {
+        self.call("getlog", requests::GetLog { level }).await
+    }
+
+    /// List all configuration options, or with {config}, just that one.
+    ///
+    /// Because of the dynamic nature of the returned object, unlike the other methods, this
+    /// returns a map (from String to Value) instead of a structure, see `responses::ListConfigs`
+    /// for typed accessors of common options.
+    pub async fn listconfigs(
+        &mut self,
+        config: Option<&str>,
+    ) -> Result<responses::ListConfigs, Error> {
+        self.call("listconfigs", requests::ListConfigs { config })
+            .await
+    }
+
+    /// Show current peers, or only peer {id}. If {level} is set (e.g. "debug"), include their
+    /// logs.
+    pub async fn listpeers(
+        &mut self,
+        id: Option<&str>,
+        level: Option<&str>,
+    ) -> Result<responses::ListPeers, Error> {
+        self.call("listpeers", requests::ListPeers { id, level })
+            .await
+    }
+
+    /// Show the channels with all peers, or only with peer {id}.
+    ///
+    /// lightningd v23.02 and newer list channels here instead of in `listpeers`; older versions
+    /// do not have the command and fail with an `Error::Rpc` for which
+    /// `RpcError::is_method_not_found` is true.
+    pub async fn listpeerchannels(
+        &mut self,
+        id: Option<&str>,
+    ) -> Result<responses::ListPeerChannels, Error> {
+        self.call("listpeerchannels", requests::ListPeerChannels { id })
+            .await
+    }
+
+    /// Show channels that were closed with {id} (or with any peer, if no {id}).
+    ///
+    /// The command is only available on newer versions of lightningd, older ones fail with an
+    /// `Error::Rpc` for which `RpcError::is_method_not_found` is true.
+    pub async fn listclosedchannels(
+        &mut self,
+        id: Option<&str>,
+    ) -> Result<responses::ListClosedChannels, Error> {
+        self.call("listclosedchannels", requests::ListClosedChannels { id })
+            .await
+    }
+
+    /// Show invoice {label} (or all, if no {label}), or the invoice for {invstring},
+    /// {payment_hash} or {offer_id}, optionally paginated, see `LightningRPC::listinvoices`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn listinvoices(
+        &mut self,
+        label: Option<&str>,
+        invstring: Option<&str>,
+        payment_hash: Option<&str>,
+        offer_id: Option<&str>,
+        index: Option<&str>,
+        start: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<responses::ListInvoices, Error> {
+        self.call(
+            "listinvoices",
+            requests::ListInvoices {
+                label,
+                invstring,
+                payment_hash,
+                offer_id,
+                index,
+                start,
+                limit,
+            },
+        )
+        .await
+    }
+
+    /// Create an invoice for {msatoshi} with {label} and {description} with
+    /// optional {expiry} seconds (default 1 week). Negative values fail with
+    /// `Error::InvalidInput`.
+    pub async fn invoice(
+        &mut self,
+        msatoshi: i64,
+        label: String,
+        description: String,
+        expiry: Option<i64>,
+    ) -> Result<responses::Invoice, Error> {
+        let amount_msat = common::non_negative("msatoshi", msatoshi)?;
+        let expiry = expiry
+            .map(|e| common::non_negative("expiry", e))
+            .transpose()?;
+        self.call(
+            "invoice",
+            requests::Invoice {
+                amount_msat,
+                label,
+                description,
+                expiry,
+                fallbacks: None,
+                preimage: None,
+                exposeprivatechannels: None,
+                cltv: None,
+                deschashonly: None,
+            },
+        )
+        .await
+    }
+
+    /// Create and sign an invoice from {bolt11} (as returned by `invoice` with `createonly`, or
+    /// built externally) under {label}, using the known {preimage} as the payment secret.
+    pub async fn createinvoice(
+        &mut self,
+        label: &str,
+        bolt11: &str,
+        preimage: &str,
+    ) -> Result<responses::CreateInvoice, Error> {
+        common::check_hex("preimage", preimage, 64)?;
+        self.call(
+            "createinvoice",
+            requests::CreateInvoice {
+                invstring: bolt11,
+                label,
+                preimage,
+            },
+        )
+        .await
+    }
+
+    /// Sign {invstring}, an unsigned or modified bolt11 invoice, with the node's key.
+    ///
+    /// Unlike `createinvoice` this only returns the signed invoice: it is not stored by the
+    /// node, which will not recognize payments to it unless they are handled elsewhere, e.g. by
+    /// a plugin that knows the preimage.
+    pub async fn signinvoice(&mut self, invstring: &str) -> Result<responses::SignInvoice, Error> {
+        self.call("signinvoice", requests::SignInvoice { invstring })
+            .await
+    }
+
+    /// Delete unpaid invoice {label} with {status}, returning the deleted invoice.
+    ///
+    /// lightningd refuses with an `Error::Rpc` if the invoice's current status is not {status}
+    /// ("paid", "expired" or "unpaid"), so that a concurrently paid invoice is not deleted.
+    pub async fn delinvoice(
+        &mut self,
+        label: &str,
+        status: &str,
+    ) -> Result<responses::DelInvoice, Error> {
+        self.call("delinvoice", requests::DelInvoice { label, status })
+            .await
+    }
+
+    /// Delete all expired invoices that expired as of given {maxexpirytime} (a UNIX epoch time),
+    /// or all expired invoices if not specified.
+    pub async fn delexpiredinvoice(
+        &mut self,
+        maxexpirytime: Option<u64>,
+    ) -> Result<responses::DelExpiredInvoice, Error> {
+        self.call(
+            "delexpiredinvoice",
+            requests::DelExpiredInvoice { maxexpirytime },
+        )
+        .await
+    }
+
+    /// Set up autoclean of expired invoices. Perform cleanup every {cycle_seconds} (default 3600),
+    /// or disable autoclean if 0. Clean up expired invoices that have expired for {expired_by}
+    /// seconds (default 86400).
+    pub async fn autocleaninvoice(
+        &mut self,
+        cycle_seconds: Option<u64>,
+        expired_by: Option<u64>,
+    ) -> Result<responses::AutoCleanInvoice, Error> {
+        self.call(
+            "autocleaninvoice",
+            requests::AutoCleanInvoice {
+                cycle_seconds,
+                expired_by,
+            },
+        )
+        .await
+    }
+
+    /// Wait for the next invoice to be paid, after {lastpay_index}.
+    /// (if supplied)
+    ///
+    /// This blocks until an invoice is paid, which may never happen. Either set a generous
+    /// timeout with `set_timeout` (the call fails with `Error::Timeout` when it expires) or
+    /// issue it from a dedicated `LightningRPC` instance.
+    pub async fn waitanyinvoice(
+        &mut self,
+        lastpay_index: Option<u64>,
+    ) -> Result<responses::WaitAnyInvoice, Error> {
+        self.call("waitanyinvoice", requests::WaitAnyInvoice { lastpay_index })
+            .await
+    }
+
+    /// Wait for an incoming payment matching the invoice with {label}.
+    ///
+    /// The same caveat about blocking as for `waitanyinvoice` applies.
+    pub async fn waitinvoice(&mut self, label: &str) -> Result<responses::WaitInvoice, Error> {
+        self.call("waitinvoice", requests::WaitInvoice { label })
+            .await
+    }
+
+    /// Wait until the blockchain has reached {blockheight}, or at most {timeout} seconds
+    /// (default 60).
+    ///
+    /// The same caveats as for the synchronous `waitblockheight` apply.
+    pub async fn waitblockheight(
+        &mut self,
+        blockheight: u32,
+        timeout: Option<u32>,
+    ) -> Result<responses::WaitBlockHeight, Error> {
+        self.call(
+            "waitblockheight",
+            requests::WaitBlockHeight {
+                blockheight,
+                timeout,
+            },
+        )
+        .await
+    }
+
+    /// Wait until the {indexname} index of {subsystem} reaches {nextvalue}, returning the
+    /// current indexes.
+    ///
+    /// Returns immediately if the index is already there. Waiting for one past the last value
+    /// seen, and then fetching what changed, follows a subsystem without polling. Needs
+    /// lightningd v23.08 or later.
+    pub async fn wait(
+        &mut self,
+        subsystem: common::WaitSubsystem,
+        indexname: common::WaitIndex,
+        nextvalue: u64,
+    ) -> Result<responses::Wait, Error> {
+        self.call(
+            "wait",
+            requests::Wait {
+                subsystem,
+                indexname,
+                nextvalue,
+            },
+        )
+        .await
+    }
+
+    /// Send a lightning payment.
+    ///
+    /// Unlike the blocking `LightningRPC::pay`, which takes only {bolt11} and leaves the options
+    /// to `LightningRPC::pay_builder`, this takes all options at once in {options}. Each
+    /// `PayBuilder` setter except `extra_params` has a `PayOptions` field of the same name; for
+    /// extra parameters, pass a `requests::Pay` to `call_with_extra`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bolt11` - A string that holds the payment information in bolt11 format.
+    /// * `options` - Options for this payment. Use `Default::default()` to not pass any options.
+    pub async fn pay(
+        &mut self,
+        bolt11: &str,
+        options: PayOptions,
+    ) -> Result<responses::Pay, Error> {
+        self.call("pay", pay_request(bolt11, options)).await
+    }
+
+    /// Send a spontaneous payment of {amount_msat} to {destination}, without an invoice.
+    ///
+    /// The payment is made by the daemon's `keysend` command, which takes care of constructing
+    /// the onion with the preimage in it.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - Node id of the recipient, which must support keysend.
+    /// * `amount_msat` - Amount to send, in millisatoshi.
+    /// * `options` - Options for this payment. Use `Default::default()` to not pass any options.
+    ///
+    /// Fails with `Error::InvalidInput` if {extratlvs} has an even type or a payload that is not
+    /// hex.
+    pub async fn keysend(
+        &mut self,
+        destination: &str,
+        amount_msat: u64,
+        options: KeySendOptions,
+    ) -> Result<responses::KeySend, Error> {
+        if let Some(ref tlvs) = options.extratlvs {
+            common::check_extratlvs(tlvs)?;
+        }
+        self.call(
+            "keysend",
+            requests::KeySend {
+                destination,
+                amount_msat,
+                label: options.label,
+                maxfeepercent: options.maxfeepercent,
+                retry_for: options.retry_for,
+                maxdelay: options.maxdelay,
+                exemptfee: options.exemptfee,
+                routehints: options.routehints,
+                extratlvs: options.extratlvs,
+            },
+        )
+        .await
+    }
+
+    /// Pay {invstring}, a bolt11 or bolt12 invoice, using the `renepay` plugin's min-cost-flow
+    /// pathfinding; {amount_msat} is needed if and only if the invoice has no amount.
+    ///
+    /// `renepay` is missing on older daemons (and when the plugin is disabled), in which case
+    /// this fails with an `Error::Rpc` for which `RpcError::is_method_not_found` is true, and
+    /// callers can fall back to `pay`.
+    pub async fn renepay(
+        &mut self,
+        invstring: &str,
+        amount_msat: Option<u64>,
+        options: RenePayOptions,
+    ) -> Result<responses::Pay, Error> {
+        self.call(
+            "renepay",
+            requests::RenePay {
+                invstring,
+                amount_msat,
+                maxfee: options.maxfee,
+                maxdelay: options.maxdelay,
+                retry_for: options.retry_for,
+                description: options.description,
+                label: options.label,
+            },
+        )
+        .await
+    }
+
+    /// Show the status of `renepay` payments, of {invstring} only if given.
+    pub async fn renepaystatus(
+        &mut self,
+        invstring: Option<&str>,
+    ) -> Result<responses::RenePayStatus, Error> {
+        self.call("renepaystatus", requests::RenePayStatus { invstring })
+            .await
+    }
+
+    /// Create a BOLT12 offer to be paid {amount} ("any", msat, or a currency amount such as
+    /// "5USD") for {description}.
+    ///
+    /// Offers are experimental and need lightningd to run with `--experimental-offers`, without
+    /// it this fails with an `Error::Rpc` for which `RpcError::is_method_not_found` is true.
+    /// Creating an offer that already exists returns it with `created` false.
+    pub async fn offer(
+        &mut self,
+        amount: &str,
+        description: &str,
+        options: OfferOptions,
+    ) -> Result<responses::Offer, Error> {
+        self.call(
+            "offer",
+            requests::Offer {
+                amount,
+                description,
+                issuer: options.issuer,
+                label: options.label,
+                quantity_max: options.quantity_max,
+                absolute_expiry: options.absolute_expiry,
+                recurrence: options.recurrence,
+                recurrence_base: options.recurrence_base,
+                recurrence_paywindow: options.recurrence_paywindow,
+                recurrence_limit: options.recurrence_limit,
+                single_use: options.single_use,
+            },
+        )
+        .await
+    }
+
+    /// Request an invoice for BOLT12 {offer} from its issuer, for {amount_msat} if the offer
+    /// does not specify an amount.
+    ///
+    /// The invoice can be paid with `pay`. As with `offer`, this needs `--experimental-offers`.
+    pub async fn fetchinvoice(
+        &mut self,
+        offer: &str,
+        amount_msat: Option<u64>,
+        options: FetchInvoiceOptions,
+    ) -> Result<responses::FetchInvoice, Error> {
+        self.call(
+            "fetchinvoice",
+            requests::FetchInvoice {
+                offer,
+                amount_msat,
+                quantity: options.quantity,
+                recurrence_counter: options.recurrence_counter,
+                recurrence_start: options.recurrence_start,
+                recurrence_label: options.recurrence_label,
+                timeout: options.timeout,
+                payer_note: options.payer_note,
+            },
+        )
+        .await
+    }
+
+    /// Send along {route} in return for preimage of {payment_hash}, with optional {description}.
+    pub async fn sendpay(
+        &mut self,
+        route: Vec<common::RouteItem>,
+        payment_hash: String,
+        description: Option<String>,
+        msatoshi: Option<i64>,
+    ) -> Result<responses::SendPay, Error> {
+        self.call(
+            "sendpay",
+            requests::SendPay {
+                route,
+                payment_hash,
+                description,
+                msatoshi,
+            },
+        )
+        .await
+    }
+
+    /// Wait for payment attempt on {payment_hash} to succeed or fail, but only up to {timeout} seconds.
+    ///
+    /// A failed attempt is returned as `Error::Rpc`, use `RpcError::sendpay_failure` on it to get
+    /// at the details of the failure.
+    pub async fn waitsendpay(
+        &mut self,
+        payment_hash: &str,
+        timeout: Option<u64>,
+    ) -> Result<responses::WaitSendPay, Error> {
+        self.call(
+            "waitsendpay",
+            requests::WaitSendPay {
+                payment_hash,
+                timeout,
+            },
+        )
+        .await
+    }
+
+    /// Show outgoing payment attempts, optionally limited to {bolt11} or {payment_hash}.
+    pub async fn listsendpays(
+        &mut self,
+        bolt11: Option<&str>,
+        payment_hash: Option<&str>,
+    ) -> Result<responses::ListSendPays, Error> {
+        self.call(
+            "listsendpays",
+            requests::ListSendPays {
+                bolt11,
+                payment_hash,
+            },
+        )
+        .await
+    }
+
+    /// Delete the record of the payment attempt on {payment_hash} with {status} from
+    /// 'listsendpays', returning the deleted attempts.
+    ///
+    /// {status} must be the attempt's current status, "complete" or "failed", otherwise lightningd
+    /// refuses and the refusal is returned as `Error::Rpc`. Pending attempts cannot be deleted.
+    /// Without {partid} and {groupid} all parts of the payment are deleted, with both only the
+    /// part {partid} of attempt {groupid} of a multi-part payment.
+    pub async fn delpay(
+        &mut self,
+        payment_hash: &str,
+        status: &str,
+        partid: Option<u64>,
+        groupid: Option<u64>,
+    ) -> Result<responses::DelPay, Error> {
+        self.call(
+            "delpay",
+            requests::DelPay {
+                payment_hash,
+                status,
+                partid,
+                groupid,
+            },
+        )
+        .await
+    }
+
+    /// Show outgoing payments, with all attempts for the same payment combined, optionally
+    /// limited to {bolt11} or {payment_hash}, and to those with {status}.
+    pub async fn listpays(
+        &mut self,
+        bolt11: Option<&str>,
+        payment_hash: Option<&str>,
+        status: Option<&str>,
+    ) -> Result<responses::ListPays, Error> {
+        self.call(
+            "listpays",
+            requests::ListPays {
+                bolt11,
+                payment_hash,
+                status,
+            },
+        )
+        .await
+    }
+
+    /// Show outgoing payments.
+    ///
+    /// This was renamed to `listsendpays` in c-lightning 0.7, and is kept for older daemons.
+    pub async fn listpayments(
+        &mut self,
+        bolt11: Option<String>,
+        payment_hash: Option<String>,
+    ) -> Result<responses::ListPayments, Error> {
+        self.call(
+            "listpayments",
+            requests::ListPayments {
+                bolt11,
+                payment_hash,
+            },
+        )
+        .await
+    }
+
+    /// Decode {bolt11}, using {description} if necessary.
+    pub async fn decodepay(
+        &mut self,
+        bolt11: &str,
+        description: Option<&str>,
+    ) -> Result<responses::DecodePay, Error> {
+        self.call(
+            "decodepay",
+            requests::DecodePay {
+                bolt11,
+                description,
+            },
+        )
+        .await
+    }
+
+    /// Decode {string}, which may be a bolt11 invoice, a bolt12 offer, invoice request or
+    /// invoice, or a rune.
+    pub async fn decode(&mut self, string: &str) -> Result<responses::Decode, Error> {
+        self.call("decode", requests::Decode { string }).await
+    }
+
+    /// Show route to {id} for {amount_msat}, using {riskfactor} and optional {cltv} (default 9).
+    /// If specified search from {fromid} otherwise use this node as source. Randomize the route
+    /// with up to {fuzzpercent} (0.0 -> 100.0, default 5.0). The channels in {exclude}, given as
+    /// "scid/direction", are not used, and the route is at most {maxhops} long.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn getroute(
+        &mut self,
+        id: &str,
+        amount_msat: u64,
+        riskfactor: f64,
+        cltv: Option<u64>,
+        fromid: Option<&str>,
+        fuzzpercent: Option<f64>,
+        exclude: &[String],
+        maxhops: Option<u64>,
+    ) -> Result<responses::GetRoute, Error> {
+        self.call(
+            "getroute",
+            requests::GetRoute {
+                id,
+                amount_msat,
+                riskfactor,
+                cltv,
+                fromid,
+                fuzzpercent,
+                exclude: if exclude.is_empty() {
+                    None
+                } else {
+                    Some(exclude.to_vec())
+                },
+                maxhops,
+            },
+        )
+        .await
+    }
+
+    /// Create an onion for {hops}, bound to {assocdata} (usually the payment hash), optionally
+    /// using {session_key} instead of a random one.
+    ///
+    /// This is a low-level call: the caller is responsible for encoding each hop's payload
+    /// correctly, lightningd only wraps them.
+    pub async fn createonion(
+        &mut self,
+        hops: &[requests::OnionHop],
+        assocdata: &str,
+        session_key: Option<&str>,
+    ) -> Result<responses::CreateOnion, Error> {
+        self.call(
+            "createonion",
+            requests::CreateOnion {
+                hops: hops.to_vec(),
+                assocdata,
+                session_key,
+            },
+        )
+        .await
+    }
+
+    /// Send {onion} to {first_hop} in return for the preimage of {payment_hash}.
+    ///
+    /// As with `createonion`, nothing checks that the onion matches the payment; use `waitsendpay`
+    /// to learn the outcome.
+    pub async fn sendonion(
+        &mut self,
+        onion: &str,
+        first_hop: requests::FirstHop,
+        payment_hash: &str,
+        options: SendOnionOptions,
+    ) -> Result<responses::SendOnion, Error> {
+        self.call(
+            "sendonion",
+            requests::SendOnion {
+                onion,
+                first_hop,
+                payment_hash,
+                label: options.label,
+                shared_secrets: options.shared_secrets,
+                partid: options.partid,
+                bolt11: options.bolt11,
+                amount_msat: options.amount_msat,
+                destination: options.destination,
+            },
+        )
+        .await
+    }
+
+    /// Connect to {id} at {host} and optional {port} (default 9735). If {host} is not
+    /// given, {id} can also be of the form id@host or id@host:port.
+    pub async fn connect(
+        &mut self,
+        id: &str,
+        host: Option<&str>,
+        port: Option<u16>,
+    ) -> Result<responses::Connect, Error> {
+        if port.is_some() && host.is_none() {
+            return Err(Error::InvalidInput("port requires host".to_owned()));
+        }
+        self.call("connect", requests::Connect { id, host, port })
+            .await
+    }
+
+    /// Connect to {id} unless it is connected already, at {addr} ("host" or "host:port") if
+    /// given, like `connect` otherwise.
+    ///
+    /// If the peer was connected already, only `id` and `features` of the result are set. A
+    /// `connect` failing because the peer connected in the meantime is treated the same way,
+    /// with only `id` set.
+    pub async fn ensure_connected(
+        &mut self,
+        id: &str,
+        addr: Option<&str>,
+    ) -> Result<responses::Connect, Error> {
+        common::check_pubkey("id", id)?;
+        if let Some(connect) = connected_peer(&self.listpeers(Some(id), None).await?, id) {
+            return Ok(connect);
+        }
+        let target = match addr {
+            Some(addr) => format!("{}@{}", id, addr),
+            None => id.to_owned(),
+        };
+        already_connected(id, self.connect(&target, None, None).await)
+    }
+
+    /// Disconnect from peer with {id}. Unless {force} is set, this fails if there is an active
+    /// channel with the peer.
+    pub async fn disconnect(
+        &mut self,
+        id: &str,
+        force: bool,
+    ) -> Result<responses::Disconnect, Error> {
+        self.call("disconnect", requests::Disconnect { id, force })
+            .await
+    }
+
+    /// Fund channel with {id} using {amount} (e.g. "100000sat" or "all"), at optional
+    /// {feerate}, spending outputs with at least {minconf} confirmations (default 1). The
+    /// channel is announced to the network unless {announce} is false. {push_msat} is given to
+    /// the peer right away, and on a mutual close our funds go to {close_to} if the peer
+    /// supports it.
+    ///
+    /// To spend specific outputs, pass a `requests::FundChannel` with `utxos` to `call`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn fundchannel(
+        &mut self,
+        id: &str,
+        amount: &str,
+        feerate: Option<&str>,
+        announce: Option<bool>,
+        minconf: Option<u32>,
+        push_msat: Option<u64>,
+        close_to: Option<&str>,
+    ) -> Result<responses::FundChannel, Error> {
+        if let (Some(push), Some(total)) = (push_msat, common::amount_to_msat(amount)) {
+            if push > total {
+                return Err(Error::InvalidInput(
+                    "push_msat must not exceed amount".to_owned(),
+                ));
+            }
+        }
+        self.call(
+            "fundchannel",
+            requests::FundChannel {
+                id,
+                amount,
+                feerate,
+                announce,
+                minconf,
+                utxos: None,
+                push_msat,
+                close_to,
+            },
+        )
+        .await
+    }
+
+    /// Fund channels to all {destinations} with a single transaction, at optional {feerate},
+    /// spending outputs with at least {minconf} confirmations (default 1).
+    ///
+    /// By default the whole operation fails if any destination fails. With {minchannels}, the
+    /// destinations that fail (to connect, or because the peer rejects the channel) are dropped
+    /// and listed in `failed`, and the others are funded as long as at least {minchannels}
+    /// remain.
+    ///
+    /// To spend specific outputs, pass a `requests::MultiFundChannel` with `utxos` to `call`.
+    pub async fn multifundchannel(
+        &mut self,
+        destinations: &[requests::MultiFundDest],
+        feerate: Option<&str>,
+        minconf: Option<u32>,
+        minchannels: Option<u32>,
+    ) -> Result<responses::MultiFundChannel, Error> {
+        for dest in destinations {
+            if let (Some(push), Some(total)) =
+                (dest.push_msat, common::amount_to_msat(&dest.amount))
+            {
+                if push > total {
+                    return Err(Error::InvalidInput(format!(
+                        "push_msat must not exceed amount for {}",
+                        dest.id
+                    )));
+                }
+            }
+        }
+        self.call(
+            "multifundchannel",
+            requests::MultiFundChannel {
+                destinations: destinations.to_vec(),
+                feerate,
+                minconf,
+                utxos: None,
+                minchannels,
+            },
+        )
+        .await
+    }
+
+    /// Start opening a channel with {id} for {amount} satoshis, to be funded by a transaction
+    /// built outside of the node.
+    ///
+    /// This is the first step of a stateful sequence on the daemon:
+    ///
+    /// 1. `fundchannel_start` returns the `funding_address` to pay to.
+    /// 2. The caller builds (but does not broadcast) a transaction paying exactly {amount} to
+    ///    that address.
+    /// 3. `fundchannel_complete` with the txid and output index of that transaction. Only when
+    ///    this returns with `commitments_secured` is it safe to broadcast the transaction.
+    ///
+    /// `fundchannel_cancel` aborts the sequence at any point before broadcasting.
+    pub async fn fundchannel_start(
+        &mut self,
+        id: &str,
+        amount: u64,
+        feerate: Option<&str>,
+        announce: Option<bool>,
+        close_to: Option<&str>,
+        push_msat: Option<u64>,
+    ) -> Result<responses::FundChannelStart, Error> {
+        self.call(
+            "fundchannel_start",
+            requests::FundChannelStart {
+                id,
+                amount,
+                feerate,
+                announce,
+                close_to,
+                push_msat,
+            },
+        )
+        .await
+    }
+
+    /// Complete a channel open with {id} started with `fundchannel_start`, given the {txid} and
+    /// {txout} of the (not yet broadcast) funding transaction.
+    pub async fn fundchannel_complete(
+        &mut self,
+        id: &str,
+        txid: &str,
+        txout: u32,
+    ) -> Result<responses::FundChannelComplete, Error> {
+        self.call(
+            "fundchannel_complete",
+            requests::FundChannelComplete { id, txid, txout },
+        )
+        .await
+    }
+
+    /// Cancel a channel open with {id} started with `fundchannel_start`. This must be done
+    /// before the funding transaction is broadcast.
+    pub async fn fundchannel_cancel(
+        &mut self,
+        id: &str,
+    ) -> Result<responses::FundChannelCancel, Error> {
+        self.call("fundchannel_cancel", requests::FundChannelCancel { id })
+            .await
+    }
+
+    /// Start splicing {relative_amount} satoshis into the channel with {channel_id}, or out of it
+    /// if negative, at optional {feerate_per_kw}.
+    ///
+    /// A splice takes three steps, each passing the PSBT returned by the previous one on:
+    /// `splice_init` returns a PSBT to which the funding inputs (or, for a splice-out, the
+    /// destination outputs) are added, either up front through {initialpsbt} or afterwards.
+    /// `splice_update` is then called with the latest PSBT until it reports
+    /// `commitments_secured`, and finally the PSBT is signed (e.g. with `signpsbt`) and given to
+    /// `splice_signed`, which broadcasts the splice transaction. The node must run with
+    /// `--experimental-splicing`.
+    pub async fn splice_init(
+        &mut self,
+        channel_id: &str,
+        relative_amount: i64,
+        initialpsbt: Option<&str>,
+        feerate_per_kw: Option<u32>,
+    ) -> Result<responses::SpliceInit, Error> {
+        common::check_hex("channel_id", channel_id, 64)?;
+        if let Some(psbt) = initialpsbt {
+            common::check_base64("initialpsbt", psbt)?;
+        }
+        self.call(
+            "splice_init",
+            requests::SpliceInit {
+                channel_id,
+                relative_amount,
+                initialpsbt,
+                feerate_per_kw,
+            },
+        )
+        .await
+    }
+
+    /// Exchange the splice {psbt} for the channel with {channel_id} with the peer, returning the
+    /// PSBT with the peer's changes. Repeat with the returned PSBT until `commitments_secured`.
+    pub async fn splice_update(
+        &mut self,
+        channel_id: &str,
+        psbt: &str,
+    ) -> Result<responses::SpliceUpdate, Error> {
+        common::check_hex("channel_id", channel_id, 64)?;
+        common::check_base64("psbt", psbt)?;
+        self.call("splice_update", requests::SpliceUpdate { channel_id, psbt })
+            .await
+    }
+
+    /// Finish the splice of the channel with {channel_id} with the signed {psbt} from the last
+    /// `splice_update`, and broadcast the splice transaction.
+    pub async fn splice_signed(
+        &mut self,
+        channel_id: &str,
+        psbt: &str,
+    ) -> Result<responses::SpliceSigned, Error> {
+        common::check_hex("channel_id", channel_id, 64)?;
+        common::check_base64("psbt", psbt)?;
+        self.call("splice_signed", requests::SpliceSigned { channel_id, psbt })
+            .await
+    }
+
+    /// Close the channel with {id}, which is a peer id, channel id or short channel id.
+    ///
+    /// A mutual close is attempted first, after {unilateraltimeout} seconds (default 172800)
+    /// the channel is closed unilaterally, or never if it is 0. Our funds go to {destination}
+    /// if given, otherwise to the internal wallet. {fee_negotiation_step} sets how fast we move
+    /// towards the peer's fee proposal, e.g. "50%" (default) or "1" satoshi.
+    pub async fn close(
+        &mut self,
+        id: &str,
+        unilateraltimeout: Option<u32>,
+        destination: Option<&str>,
+        fee_negotiation_step: Option<&str>,
+    ) -> Result<responses::Close, Error> {
+        self.call(
+            "close",
+            requests::Close {
+                id,
+                unilateraltimeout,
+                destination,
+                fee_negotiation_step,
+            },
+        )
+        .await
+    }
+
+    /// Close every channel for which {predicate} holds, given the peer as listed by `listpeers`
+    /// and the channel as listed by `listpeerchannels`, with `close` and {unilateraltimeout}.
+    ///
+    /// Returns the channel id and the outcome of `close` for each matching channel. A failed
+    /// close is reported in its entry and does not stop the others; only a failure of
+    /// `listpeerchannels` or `listpeers` itself is returned as an error. That includes daemons
+    /// older than v23.02, which do not have `listpeerchannels`.
+    pub async fn close_channels_where<F: Fn(&responses::Peer, &responses::Channel) -> bool>(
+        &mut self,
+        predicate: F,
+        unilateraltimeout: Option<u32>,
+    ) -> Result<CloseResults, Error> {
+        let channels = self.listpeerchannels(None).await?.channels;
+        let peers = self.listpeers(None, None).await?.peers;
+        let channels = matching_channels(&peers, &channels, predicate);
+        let mut results = Vec::with_capacity(channels.len());
+        for channel_id in channels {
+            let result = self.close(&channel_id, unilateraltimeout, None, None).await;
+            results.push((channel_id, result));
+        }
+        Ok(results)
+    }
+
+    /// Update the fees and HTLC limits of the channel(s) {id}, which is a peer id, channel id,
+    /// short channel id or "all". The base fee {feebase} is in msat and the proportional fee
+    /// {feeppm} in millionths, {htlcmin} and {htlcmax} are in msat. Values that are not given stay
+    /// as they are. The old values keep being accepted for {enforcedelay} seconds (default 600).
+    #[allow(clippy::too_many_arguments)]
+    pub async fn setchannel(
+        &mut self,
+        id: &str,
+        feebase: Option<u64>,
+        feeppm: Option<u32>,
+        htlcmin: Option<u64>,
+        htlcmax: Option<u64>,
+        enforcedelay: Option<u32>,
+    ) -> Result<responses::SetChannel, Error> {
+        self.call(
+            "setchannel",
+            requests::SetChannel {
+                id,
+                feebase,
+                feeppm,
+                htlcmin,
+                htlcmax,
+                enforcedelay,
+            },
+        )
+        .await
+    }
+
+    /// Send {id} a ping of length {len} (default 128) asking for {pongbytes} (default 128).
+    ///
+    /// {len} can be at most 65529, so that the ping fits in a message, and {pongbytes} at most
+    /// 65531, as peers do not answer requests for more.
+    pub async fn ping(
+        &mut self,
+        id: &str,
+        len: Option<u16>,
+        pongbytes: Option<u16>,
+    ) -> Result<responses::Ping, Error> {
+        let len = len.unwrap_or(128);
+        let pongbytes = pongbytes.unwrap_or(128);
+        common::check_max("len", len, 65529)?;
+        common::check_max("pongbytes", pongbytes, 65531)?;
+        self.call("ping", requests::Ping { id, len, pongbytes })
+            .await
+    }
+
+    /// List all forwarded payments and their information.
+    pub async fn listforwards(&mut self) -> Result<responses::ListForwards, Error> {
+        self.call("listforwards", requests::ListForwards {}).await
+    }
+
+    /// List the HTLCs of all channels, or of the channel {id} (a channel id or short channel
+    /// id) only, both in flight and those kept from the past.
+    ///
+    /// HTLCs of closed channels are forgotten after some time.
+    pub async fn listhtlcs(&mut self, id: Option<&str>) -> Result<responses::ListHtlcs, Error> {
+        self.call("listhtlcs", requests::ListHtlcs { id }).await
+    }
+
+    /// Show available funds from the internal wallet, including spent outputs if {spent}.
+    pub async fn listfunds(&mut self, spent: Option<bool>) -> Result<responses::ListFunds, Error> {
+        self.call("listfunds", requests::ListFunds { spent }).await
+    }
+
+    /// Sum up the funds of the wallet and the channels from one `listfunds` call.
+    pub async fn balance(&mut self) -> Result<responses::Balance, Error> {
+        Ok(self.listfunds(None).await?.balance())
+    }
+
+    /// Show the transactions tracked by the internal wallet.
+    pub async fn listtransactions(&mut self) -> Result<responses::ListTransactions, Error> {
+        self.call("listtransactions", requests::ListTransactions {})
+            .await
+    }
+
+    /// Send {satoshi} (e.g. "10000sat", "1btc" or "all") to {destination} address via a Bitcoin
+    /// transaction, at optional {feerate}, spending outputs with at least {minconf}
+    /// confirmations or exactly the {utxos} given as "txid:vout".
+    pub async fn withdraw(
+        &mut self,
+        destination: &str,
+        satoshi: &str,
+        feerate: Option<&str>,
+        minconf: Option<u32>,
+        utxos: Option<&[String]>,
+    ) -> Result<responses::Withdraw, Error> {
+        for utxo in utxos.unwrap_or_default() {
+            common::check_utxo(utxo)?;
+        }
+        self.call(
+            "withdraw",
+            requests::Withdraw {
+                destination,
+                satoshi,
+                feerate,
+                minconf,
+                utxos: utxos.map(<[String]>::to_vec),
+            },
+        )
+        .await
+    }
+
+    /// Create a transaction paying {outputs}, a list of (address, amount) pairs where the amount
+    /// is e.g. "10000sat" or "all", at optional {feerate}, spending outputs with at least
+    /// {minconf} confirmations or exactly the {utxos} given as "txid:vout".
+    ///
+    /// The transaction is not broadcast, its inputs stay reserved until it is sent with `txsend`
+    /// or released with `txdiscard` (or lightningd restarts).
+    pub async fn txprepare(
+        &mut self,
+        outputs: &[(String, String)],
+        feerate: Option<&str>,
+        minconf: Option<u32>,
+        utxos: Option<&[String]>,
+    ) -> Result<responses::TxPrepare, Error> {
+        for utxo in utxos.unwrap_or_default() {
+            common::check_utxo(utxo)?;
+        }
+        self.call(
+            "txprepare",
+            requests::TxPrepare {
+                outputs: outputs
+                    .iter()
+                    .map(|(address, amount)| {
+                        std::iter::once((address.clone(), amount.clone())).collect()
+                    })
+                    .collect(),
+                feerate,
+                minconf,
+                utxos: utxos.map(<[String]>::to_vec),
+            },
+        )
+        .await
+    }
+
+    /// Sign and broadcast the transaction {txid} prepared with `txprepare`.
+    pub async fn txsend(&mut self, txid: &str) -> Result<responses::TxSend, Error> {
+        self.call("txsend", requests::TxSend { txid }).await
+    }
+
+    /// Abandon the transaction {txid} prepared with `txprepare`, releasing its inputs.
+    pub async fn txdiscard(&mut self, txid: &str) -> Result<responses::TxDiscard, Error> {
+        self.call("txdiscard", requests::TxDiscard { txid }).await
+    }
+
+    /// Select wallet inputs worth at least {satoshi} (or "all") plus the fees at {feerate} for a
+    /// transaction whose outputs and non-wallet inputs weigh {startweight}, and return them as a
+    /// PSBT without outputs.
+    ///
+    /// Inputs need {minconf} confirmations (1 by default) and are reserved for {reserve} blocks
+    /// (72 by default, 0 to not reserve). With {excess_as_change} the leftover amount is added as
+    /// a change output instead of being reported in `excess_msat`. This is the first step of
+    /// building a transaction, such as a dual-funded channel open, outside of lightningd: add the
+    /// outputs to the returned PSBT, then pass it to `signpsbt` and finally `sendpsbt`. Use
+    /// `unreserveinputs` to release the inputs if the transaction is abandoned.
+    ///
+    /// To set `locktime` or `min_witness_weight`, pass a `requests::FundPsbt` to `call`.
+    pub async fn fundpsbt(
+        &mut self,
+        satoshi: &str,
+        feerate: &str,
+        startweight: u32,
+        minconf: Option<u32>,
+        reserve: Option<u32>,
+        excess_as_change: Option<bool>,
+    ) -> Result<responses::FundPsbt, Error> {
+        self.call(
+            "fundpsbt",
+            requests::FundPsbt {
+                satoshi,
+                feerate,
+                startweight,
+                minconf,
+                reserve,
+                locktime: None,
+                min_witness_weight: None,
+                excess_as_change,
+            },
+        )
+        .await
+    }
+
+    /// Like `fundpsbt`, but spend exactly the {utxos} given as "txid:vout" instead of selecting
+    /// inputs from the wallet.
+    ///
+    /// To set `reservedok`, `locktime` or `min_witness_weight`, pass a `requests::UtxoPsbt` to
+    /// `call`.
+    pub async fn utxopsbt(
+        &mut self,
+        satoshi: &str,
+        feerate: &str,
+        startweight: u32,
+        utxos: &[String],
+        reserve: Option<u32>,
+        excess_as_change: Option<bool>,
+    ) -> Result<responses::UtxoPsbt, Error> {
+        for utxo in utxos {
+            common::check_utxo(utxo)?;
+        }
+        self.call(
+            "utxopsbt",
+            requests::UtxoPsbt {
+                satoshi,
+                feerate,
+                startweight,
+                utxos: utxos.to_vec(),
+                reserve,
+                reservedok: None,
+                locktime: None,
+                min_witness_weight: None,
+                excess_as_change,
+            },
+        )
+        .await
+    }
+
+    /// Sign the inputs of {psbt} that belong to our wallet, or only those at the indexes in
+    /// {signonly}.
+    pub async fn signpsbt(
+        &mut self,
+        psbt: &str,
+        signonly: Option<&[u32]>,
+    ) -> Result<responses::SignPsbt, Error> {
+        common::check_base64("psbt", psbt)?;
+        self.call("signpsbt", requests::SignPsbt { psbt, signonly })
+            .await
+    }
+
+    /// Finalize the fully signed {psbt} and broadcast the transaction.
+    pub async fn sendpsbt(&mut self, psbt: &str) -> Result<responses::SendPsbt, Error> {
+        common::check_base64("psbt", psbt)?;
+        self.call("sendpsbt", requests::SendPsbt { psbt }).await
+    }
+
+    /// Convert {psbt} to PSBT {version} 0 or 2, for wallets that only handle one of them.
+    ///
+    /// lightningd produces version 2 PSBTs, while many wallets still expect version 0.
+    pub async fn setpsbtversion(
+        &mut self,
+        psbt: &str,
+        version: u8,
+    ) -> Result<responses::SetPsbtVersion, Error> {
+        if version != 0 && version != 2 {
+            return Err(Error::InvalidInput(format!(
+                "psbt version must be 0 or 2, not {}",
+                version
+            )));
+        }
+        common::check_base64("psbt", psbt)?;
+        self.call("setpsbtversion", requests::SetPsbtVersion { psbt, version })
+            .await
+    }
+
+    /// Reserve the wallet inputs of {psbt} so automatic coin selection skips them.
+    ///
+    /// The reservation lasts {reserve} blocks (72 by default) past the current height, or past
+    /// the existing reservation when an input is already reserved; with {exclusive} set to
+    /// false already reserved inputs are extended instead of rejected.
+    pub async fn reserveinputs(
+        &mut self,
+        psbt: &str,
+        exclusive: Option<bool>,
+        reserve: Option<u32>,
+    ) -> Result<responses::ReserveInputs, Error> {
+        common::check_base64("psbt", psbt)?;
+        self.call(
+            "reserveinputs",
+            requests::ReserveInputs {
+                psbt,
+                exclusive,
+                reserve,
+            },
+        )
+        .await
+    }
+
+    /// Shorten the reservation on the inputs of {psbt} by {reserve} blocks (72 by default),
+    /// releasing them once it reaches the current height.
+    pub async fn unreserveinputs(
+        &mut self,
+        psbt: &str,
+        reserve: Option<u32>,
+    ) -> Result<responses::UnreserveInputs, Error> {
+        common::check_base64("psbt", psbt)?;
+        self.call(
+            "unreserveinputs",
+            requests::UnreserveInputs { psbt, reserve },
+        )
+        .await
+    }
+
+    /// Get a new address of {addresstype} to fund a channel, both kinds if it is `All`.
+    pub async fn newaddr(
+        &mut self,
+        addresstype: requests::AddressType,
+    ) -> Result<responses::NewAddr, Error> {
+        self.call("newaddr", requests::NewAddr { addresstype })
+            .await
+    }
+
+    /// Create a digital signature of {message} using this node's secret key.
+    pub async fn signmessage(&mut self, message: &str) -> Result<responses::SignMessage, Error> {
+        self.call("signmessage", requests::SignMessage { message })
+            .await
+    }
+
+    /// Verify a digital signature {zbase} of {message} signed with {pubkey}.
+    ///
+    /// If {pubkey} is given, `verified` tells whether the signature was made by that key. If it
+    /// is omitted, the key is recovered from the signature and returned in `pubkey`, and
+    /// `verified` only tells whether it belongs to a node known to us from gossip; an
+    /// unverified result does not mean the signature itself is invalid.
+    pub async fn checkmessage(
+        &mut self,
+        message: &str,
+        zbase: &str,
+        pubkey: Option<&str>,
+    ) -> Result<responses::CheckMessage, Error> {
+        self.call(
+            "checkmessage",
+            requests::CheckMessage {
+                message,
+                zbase,
+                pubkey,
+            },
+        )
+        .await
+    }
+
+    /// Compute the ECDH shared secret of this node's secret key and {point}, a compressed
+    /// public key in hex.
+    ///
+    /// The secret is the SHA256 of the compressed point resulting from the multiplication, as
+    /// used for onion encryption and the noise handshake.
+    pub async fn getsharedsecret(
+        &mut self,
+        point: &str,
+    ) -> Result<responses::GetSharedSecret, Error> {
+        common::check_pubkey("point", point)?;
+        self.call("getsharedsecret", requests::GetSharedSecret { point })
+            .await
+    }
+
+    /// List the plugins that lightningd runs.
+    pub async fn plugin_list(&mut self) -> Result<responses::Plugins, Error> {
+        self.plugin(requests::PluginSubcommand::List, None).await
+    }
+
+    /// Start the plugin at {path}, relative to the plugin directory if it is not absolute.
+    ///
+    /// On success this returns the updated list of plugins, including the new one.
+    pub async fn plugin_start(&mut self, path: &str) -> Result<responses::Plugins, Error> {
+        self.plugin(requests::PluginSubcommand::Start, Some(path))
+            .await
+    }
+
+    /// Stop the plugin {name}, the file name or path of a dynamic plugin.
+    pub async fn plugin_stop(&mut self, name: &str) -> Result<responses::PluginStop, Error> {
+        self.plugin(requests::PluginSubcommand::Stop, Some(name))
+            .await
+    }
+
+    /// Start the plugins in the plugin directories that are not running yet, returning the updated
+    /// list of plugins.
+    pub async fn plugin_rescan(&mut self) -> Result<responses::Plugins, Error> {
+        self.plugin(requests::PluginSubcommand::Rescan, None).await
+    }
+
+    async fn plugin<R: DeserializeOwned>(
+        &mut self,
+        subcommand: requests::PluginSubcommand,
+        plugin: Option<&str>,
+    ) -> Result<R, Error> {
+        self.call("plugin", requests::Plugin { subcommand, plugin })
+            .await
+    }
+
+    /// Check that {params} are valid for {command_to_check}, without running it, see
+    /// `LightningRPC::check`.
+    pub async fn check(
+        &mut self,
+        command_to_check: &str,
+        params: Value,
+    ) -> Result<responses::Check, Error> {
+        let named = match params {
+            Value::Array(mut params) => {
+                params.insert(0, Value::from(command_to_check));
+                return self.call("check", Value::Array(params)).await;
+            }
+            params => {
+                let mut extra = Map::new();
+                extra.insert("command_to_check".to_owned(), command_to_check.into());
+                match framing::merge_params(params, extra, false)? {
+                    Value::Object(named) => named,
+                    _ => unreachable!("extra parameters are merged into an object"),
+                }
+            }
+        };
+        // Extra parameters are always sent by name, with `ParamStyle::Positional` the keys of
+        // {params} would otherwise be taken in sorted order
+        self.call_with_extra("check", Value::Null, named, false)
+            .await
+    }
+
+    /// Store a value, given as {string} or {hex}, under {key} in the node's datastore.
+    ///
+    /// Keys are paths, such as `["myplugin", "config"]`, and a key cannot hold a value and have
+    /// children at the same time. {mode} says what to do if the key already exists, by default
+    /// this fails. With {generation} the update only happens if the entry was not modified since
+    /// it had that generation.
+    pub async fn datastore(
+        &mut self,
+        key: &[String],
+        string: Option<&str>,
+        hex: Option<&str>,
+        mode: Option<requests::DataStoreMode>,
+        generation: Option<u64>,
+    ) -> Result<responses::DataStore, Error> {
+        if string.is_some() && hex.is_some() {
+            return Err(Error::InvalidInput(
+                "datastore takes either string or hex, not both".to_owned(),
+            ));
+        }
+        if let Some(hex) = hex {
+            common::check_hex("hex", hex, hex.len())?;
+        }
+        self.call(
+            "datastore",
+            requests::DataStore {
+                key: key.to_vec(),
+                string,
+                hex,
+                mode,
+                generation,
+            },
+        )
+        .await
+    }
+
+    /// Delete {key} from the datastore, only if it still has {generation} if given.
+    pub async fn deldatastore(
+        &mut self,
+        key: &[String],
+        generation: Option<u64>,
+    ) -> Result<responses::DelDataStore, Error> {
+        self.call(
+            "deldatastore",
+            requests::DelDataStore {
+                key: key.to_vec(),
+                generation,
+            },
+        )
+        .await
+    }
+
+    /// List the datastore entries directly under {key}, or the top level entries if no {key}.
+    pub async fn listdatastore(
+        &mut self,
+        key: Option<&[String]>,
+    ) -> Result<responses::ListDataStore, Error> {
+        self.call(
+            "listdatastore",
+            requests::ListDataStore {
+                key: key.map(<[String]>::to_vec),
+            },
+        )
+        .await
+    }
+
+    /// Run the read-only SQL {query} over the node's state, e.g.
+    /// `"SELECT id, connected FROM peers"`.
+    ///
+    /// Tables mirror the list commands, so one query can combine what would take several of
+    /// them. The result does not name the columns; use `listsqlschemas` to find them and their
+    /// types. This needs the `sql` plugin, without it this fails with an `Error::Rpc` for which
+    /// `RpcError::is_method_not_found` is true.
+    pub async fn sql(&mut self, query: &str) -> Result<responses::Sql, Error> {
+        self.call("sql", requests::Sql { query }).await
+    }
+
+    /// Describe the tables (of {table} only, if given) that `sql` can query.
+    pub async fn listsqlschemas(
+        &mut self,
+        table: Option<&str>,
+    ) -> Result<responses::ListSqlSchemas, Error> {
+        self.call("listsqlschemas", requests::ListSqlSchemas { table })
+            .await
+    }
+
+    /// List the balance of every account tracked by the bookkeeper plugin: the wallet, each
+    /// channel and funds sent to external addresses.
+    ///
+    /// The `bkpr-*` commands are provided by the bookkeeper plugin, which lightningd starts by
+    /// default; they fail with `RpcErrorKind::MethodNotFound` if it is disabled.
+    pub async fn bkpr_listbalances(&mut self) -> Result<responses::BkprListBalances, Error> {
+        self.call("bkpr-listbalances", requests::BkprListBalances {})
+            .await
+    }
+
+    /// List all events of {account} (or of all accounts) recorded by the bookkeeper plugin.
+    pub async fn bkpr_listaccountevents(
+        &mut self,
+        account: Option<&str>,
+    ) -> Result<responses::BkprListAccountEvents, Error> {
+        self.call(
+            "bkpr-listaccountevents",
+            requests::BkprListAccountEvents { account },
+        )
+        .await
+    }
+
+    /// List the income and expense events between the UNIX timestamps {start_time} and
+    /// {end_time}, for example for an accounting export.
+    ///
+    /// Unless {consolidate_fees} is false, the onchain fees of a transaction are reported as one
+    /// event rather than one per account.
+    pub async fn bkpr_listincome(
+        &mut self,
+        consolidate_fees: Option<bool>,
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+    ) -> Result<responses::ListIncome, Error> {
+        self.call(
+            "bkpr-listincome",
+            requests::BkprListIncome {
+                consolidate_fees,
+                start_time,
+                end_time,
+            },
+        )
+        .await
+    }
+
+    /// Send the custom message {msg} to the connected peer {node_id}. {msg} is the hex encoded
+    /// message, starting with its two byte type, which must be odd so that peers that do not
+    /// understand it ignore it.
+    ///
+    /// Custom messages sent by peers are not delivered over the RPC socket, lightningd passes
+    /// them to plugins through the `custommsg` hook.
+    pub async fn sendcustommsg(
+        &mut self,
+        node_id: &str,
+        msg: &str,
+    ) -> Result<responses::SendCustomMsg, Error> {
+        common::check_pubkey("node_id", node_id)?;
+        common::check_custommsg(msg)?;
+        self.call("sendcustommsg", requests::SendCustomMsg { node_id, msg })
+            .await
+    }
+
+    /// Inject the gossip {message}, hex encoded and starting with its two byte type, such as a
+    /// `channel_announcement`, `node_announcement` or `channel_update`.
+    ///
+    /// lightningd checks the message as if a peer had sent it, so invalid signatures or
+    /// unknown channels are reported as `Error::Rpc`.
+    pub async fn addgossip(&mut self, message: &str) -> Result<responses::AddGossip, Error> {
+        common::check_hex_bytes("message", message, 2)?;
+        self.call("addgossip", requests::AddGossip { message })
+            .await
+    }
+
+    /// Get the static channel backups of all channels, to store somewhere safe.
+    ///
+    /// This only reads them and can be run periodically. The returned `scb` list can be passed
+    /// as is to `recoverchannel`.
+    pub async fn staticbackup(&mut self) -> Result<responses::StaticBackup, Error> {
+        self.call("staticbackup", requests::StaticBackup {}).await
+    }
+
+    /// Recover the channels in {scb}, a list of static channel backups in hex as found in the
+    /// `emergency.recover` file or returned by `staticbackup`.
+    ///
+    /// This is a last resort after losing the node's database. For each backup lightningd
+    /// creates a stub channel and asks the peer to force close it, which only works if the peer
+    /// is online and cooperates; our funds are returned onchain once the peer's commitment
+    /// transaction confirms. Do not use it while the original node or a copy of its database
+    /// may still be running: the peer could then see an old state and take all funds of the
+    /// channel as a penalty.
+    pub async fn recoverchannel(
+        &mut self,
+        scb: &[String],
+    ) -> Result<responses::RecoverChannel, Error> {
+        self.call(
+            "recoverchannel",
+            requests::RecoverChannel { scb: scb.to_vec() },
+        )
+        .await
+    }
+
+    /// Recover the channels in the node's `emergency.recover` file, like `recoverchannel`.
+    ///
+    /// The same caveats apply: every channel is force closed by its peer and this must never be
+    /// done while another instance of the node may be running.
+    pub async fn emergencyrecover(&mut self) -> Result<responses::EmergencyRecover, Error> {
+        self.call("emergencyrecover", requests::EmergencyRecover {})
+            .await
+    }
+
+    /// Shut down the lightningd process.
+    ///
+    /// lightningd may close the socket before its answer arrives, this is reported as success
+    /// with the message "Shutdown complete". Failing to send the request, for example because
+    /// lightningd is not running, is still an error.
+    pub async fn stop(&mut self) -> Result<responses::Stop, Error> {
+        stopped(self.call("stop", requests::Stop {}).await)
+    }
+}
+
+/// Developer commands, only available if lightningd was built with developer mode and the `dev`
+/// feature is enabled.
+///
+/// These are unstable, lightningd may change or remove them in any release, and are meant for
+/// testing against regtest nodes only. Several of them can lose funds when used on a real node.
+#[cfg(feature = "dev")]
+impl AsyncLightningRPC {
+    /// Synchronize the state of the wallet outputs with bitcoind.
+    pub async fn dev_rescan_outputs(&mut self) -> Result<responses::DevRescanOutputs, Error> {
+        self.call("dev-rescan-outputs", requests::DevRescanOutputs {})
+            .await
+    }
+
+    /// Forget the channel with peer {id}, selected by {short_channel_id} or {channel_id} if there
+    /// are several, without closing it. Unless {force} is set, this fails if the funding output
+    /// is still unspent.
+    pub async fn dev_forget_channel(
+        &mut self,
+        id: &str,
+        short_channel_id: Option<&str>,
+        channel_id: Option<&str>,
+        force: Option<bool>,
+    ) -> Result<responses::DevForgetChannel, Error> {
+        self.call(
+            "dev-forget-channel",
+            requests::DevForgetChannel {
+                id,
+                short_channel_id,
+                channel_id,
+                force,
+            },
+        )
+        .await
+    }
+
+    /// Sign and return the latest commitment transaction of the channel with peer {id}, without
+    /// broadcasting it. Broadcasting it afterwards will make the peer penalize us if the channel
+    /// state moved on.
+    pub async fn dev_sign_last_tx(&mut self, id: &str) -> Result<responses::DevSignLastTx, Error> {
+        self.call("dev-sign-last-tx", requests::DevSignLastTx { id })
+            .await
+    }
+}
@@ -15,79 +15,404 @@
 
 //! Client support
 //!
-//! Support for connecting to JSONRPC servers over UNIX socets, sending requests,
-//! and parsing responses
+//! Support for connecting to JSONRPC servers over UNIX sockets or other transports, sending
+//! requests, and parsing responses
 //!
 
-use std::io::Write;
-use std::os::unix::net::UnixStream;
-use std::path::{Path, PathBuf};
+use std::collections::vec_deque::Drain;
+use std::collections::VecDeque;
+use std::io;
+use std::mem;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use strason::Json;
+use serde_json::Value;
 
-use super::{Request, Response};
-use error::Error;
+use super::{Notification, Request, Response};
+use crate::error::Error;
+use crate::framing::{self, Incoming, JsonFramer};
+use crate::transport::{RpcTransport, TcpTransport, UnixTransport};
+
+/// Maximum number of notifications kept while waiting for responses, beyond this the oldest
+/// ones are dropped
+const MAX_QUEUED_NOTIFICATIONS: usize = 1024;
+
+/// Default for `Client::set_max_response_bytes`
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Strategy for generating the `id` of requests
+///
+/// The id is echoed back by the server, and checked against the request by the client. Using
+/// unique ids, for example UUIDs, makes it possible to correlate requests in logs across
+/// connections.
+pub trait IdGenerator: Send {
+    /// Return the id for the next request. `nonce` is a counter that is incremented for every
+    /// request the client builds, starting at 1.
+    fn next_id(&mut self, nonce: u64) -> Value;
+}
+
+/// The default id generator, which uses the client's request counter as id
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CounterIdGenerator;
+
+impl IdGenerator for CounterIdGenerator {
+    fn next_id(&mut self, nonce: u64) -> Value {
+        Value::from(nonce)
+    }
+}
 
 /// A handle to a remote JSONRPC server
+///
+/// The connection is opened on the first request, and kept open for subsequent ones.
 pub struct Client {
-    sockpath: PathBuf,
+    transport: Box<dyn RpcTransport>,
+    framer: JsonFramer,
+    notifications: VecDeque<Notification>,
+    timeout: Option<Duration>,
+    auto_reconnect: bool,
+    max_response_bytes: usize,
+    /// Number of connections opened so far
+    connections: u64,
     nonce: Arc<Mutex<u64>>,
+    id_generator: Mutex<Box<dyn IdGenerator>>,
+    /// Requests are serialized into this, to reuse its allocation
+    scratch: Vec<u8>,
+}
+
+/// Iterator over notifications received by a `Client`, see `Client::notifications`
+pub struct Notifications<'a> {
+    client: &'a mut Client,
+}
+
+impl Iterator for Notifications<'_> {
+    type Item = Result<Notification, Error>;
+
+    fn next(&mut self) -> Option<Result<Notification, Error>> {
+        if let Some(notification) = self.client.notifications.pop_front() {
+            return Some(Ok(notification));
+        }
+        if !self.client.transport.is_connected() {
+            if let Err(e) = self.client.reconnect() {
+                return Some(Err(e));
+            }
+        }
+        let frame = match self.client.read_frame() {
+            Ok(frame) => frame,
+            Err(Error::Io(ref e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                self.client.disconnect();
+                return None;
+            }
+            Err(e) => return Some(Err(e)),
+        };
+        match framing::demux(&frame) {
+            Ok(Incoming::Notification(notification)) => Some(Ok(notification)),
+            // No request is outstanding, so this cannot be matched to anything
+            Ok(Incoming::Reply(reply)) => Some(Err(Error::UnexpectedReply {
+                got: reply.get("id").cloned().unwrap_or(Value::Null),
+            })),
+            Err(e) => Some(Err(e)),
+        }
+    }
 }
 
-/// Filter out top-level parameters with value None, this is used for handling optional
-/// parameters correctly as c-lightning expects.
-fn filter_nones(params: Json) -> Json {
-    let mut rv: Vec<(String, Json)> = Vec::new();
-    for (k, v) in params.object().unwrap() {
-        if let None = v.null() {
-            rv.push((k.clone(), v.clone()));
+/// Flushes and closes the connection, if one is open. Notifications that were not read are lost.
+impl Drop for Client {
+    fn drop(&mut self) {
+        if self.transport.is_connected() {
+            let _ = self.transport.flush();
+            log_debug!(
+                "closing connection, {} unread notifications",
+                self.notifications.len()
+            );
+            self.disconnect();
         }
     }
-    Json::from(rv)
+}
+
+/// Convert an IO error to a library error, mapping timeouts to `Error::Timeout`.
+pub(crate) fn io_error(e: io::Error) -> Error {
+    match e.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => Error::Timeout,
+        _ => Error::Io(e),
+    }
+}
+
+/// Convert an IO error while reading a reply. The connection being reset is reported like
+/// it being closed, as `UnexpectedEof`: the request was written, but no answer will come.
+pub(crate) fn read_error(e: io::Error) -> Error {
+    match e.kind() {
+        io::ErrorKind::ConnectionReset => {
+            Error::Io(io::Error::new(io::ErrorKind::UnexpectedEof, e))
+        }
+        _ => io_error(e),
+    }
+}
+
+/// Returns whether an IO error means that the other side went away.
+fn is_disconnect(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::BrokenPipe
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+    )
 }
 
 impl Client {
-    /// Creates a new client
+    /// Creates a new client for the UNIX socket at `sockpath`
     pub fn new<P: AsRef<Path>>(sockpath: P) -> Client {
+        Client::with_transport(UnixTransport::new(sockpath))
+    }
+
+    /// Creates a new client connecting over TCP to `addr`, given as "host:port"
+    pub fn from_tcp<A: Into<String>>(addr: A) -> Client {
+        Client::with_transport(TcpTransport::new(addr))
+    }
+
+    /// Creates a new client using a custom transport
+    pub fn with_transport<T: RpcTransport + 'static>(transport: T) -> Client {
         Client {
-            sockpath: sockpath.as_ref().to_path_buf(),
+            transport: Box::new(transport),
+            framer: JsonFramer::new(),
+            notifications: VecDeque::new(),
+            timeout: None,
+            auto_reconnect: false,
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            connections: 0,
             nonce: Arc::new(Mutex::new(0)),
+            id_generator: Mutex::new(Box::new(CounterIdGenerator)),
+            scratch: Vec::new(),
         }
     }
 
-    /// Sends a request to a client
-    pub fn send_request(&self, request: &Request) -> Result<Response, Error> {
-        // Build request
-        let request_raw = Json::from_serialize(request)?.to_bytes();
+    /// Replace the strategy used to generate request ids, the default is
+    /// `CounterIdGenerator`.
+    pub fn set_id_generator<G: IdGenerator + 'static>(&mut self, id_generator: G) {
+        self.id_generator = Mutex::new(Box::new(id_generator));
+    }
 
-        // Setup connection
-        let mut stream = UnixStream::connect(&self.sockpath)?;
+    /// Set the timeout for socket operations, `None` (the default) means block forever.
+    ///
+    /// The timeout applies to every individual read and write on the socket, not to the
+    /// request as a whole, so a daemon that keeps trickling data can take longer than this to
+    /// answer. When it expires the request fails with `Error::Timeout`.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+        // Failing here means the connection is broken, which will surface on the next request
+        let _ = self.transport.set_timeout(timeout);
+    }
 
-        stream.write_all(&request_raw)?;
+    /// Enable or disable automatic reconnection, this is disabled by default.
+    ///
+    /// When enabled, a request that fails to be written because the connection broke (for
+    /// example because lightningd was restarted) is sent again once over a new connection
+    /// before the error is returned.
+    pub fn set_auto_reconnect(&mut self, auto_reconnect: bool) {
+        self.auto_reconnect = auto_reconnect;
+    }
 
-        let response: Response = Json::from_reader(&mut stream)?.into_deserialize()?;
-        if response.jsonrpc != None && response.jsonrpc != Some(From::from("2.0")) {
-            return Err(Error::VersionMismatch);
+    /// Limit the size of a single response or notification to {limit} bytes, 64 MiB by default.
+    ///
+    /// A message that grows beyond the limit while it is being read fails the request with
+    /// `Error::ResponseTooLarge` and drops the connection, instead of buffering it indefinitely.
+    /// Responses such as `listchannels` on mainnet can be tens of megabytes.
+    pub fn set_max_response_bytes(&mut self, limit: usize) {
+        self.max_response_bytes = limit;
+    }
+
+    /// Path of the socket this client connects to.
+    ///
+    /// This is `None` if the transport does not use one: a client made with `from_tcp`
+    /// connects to an address instead, and a custom `RpcTransport`, such as an SSH tunnel or
+    /// `testing::MockTransport`, may have no local path at all. Returning a made up path for
+    /// those would be mistaken for a socket that can be checked or connected to.
+    pub fn socket_path(&self) -> Option<&Path> {
+        self.transport.socket_path()
+    }
+
+    /// Whether a connection is open. It is dropped when a request fails, and opened again by
+    /// the next one.
+    pub fn is_connected(&self) -> bool {
+        self.transport.is_connected()
+    }
+
+    /// Close the current connection, if any, and open a new one.
+    pub fn reconnect(&mut self) -> Result<(), Error> {
+        self.disconnect();
+        self.transport.set_timeout(self.timeout)?;
+        self.transport.connect()?;
+        self.connections += 1;
+        Ok(())
+    }
+
+    /// Number of connections opened so far, including automatic reconnections. A change means
+    /// the daemon may have been restarted in between.
+    pub(crate) fn connection_count(&self) -> u64 {
+        self.connections
+    }
+
+    /// Forget the current connection, along with any data buffered from it.
+    fn disconnect(&mut self) {
+        self.transport.disconnect();
+        self.framer = JsonFramer::new();
+    }
+
+    /// Read until a complete JSON value has been received
+    fn read_frame(&mut self) -> Result<Vec<u8>, Error> {
+        let mut buf = [0u8; 4096];
+        loop {
+            let too_large = match self.framer.next_frame() {
+                Some(frame) if frame.len() <= self.max_response_bytes => return Ok(frame),
+                Some(_) => true,
+                None => self.framer.buffered() > self.max_response_bytes,
+            };
+            if too_large {
+                self.disconnect();
+                return Err(Error::ResponseTooLarge {
+                    limit: self.max_response_bytes,
+                });
+            }
+            let n = self.transport.read(&mut buf).map_err(read_error)?;
+            if n == 0 {
+                return Err(Error::Io(io::ErrorKind::UnexpectedEof.into()));
+            }
+            self.framer.push(&buf[..n]);
         }
-        if response.id != request.id {
-            return Err(Error::NonceMismatch);
+    }
+
+    /// Read until a reply has been received, queueing any notifications that come before it
+    fn read_reply(&mut self) -> Result<Value, Error> {
+        loop {
+            let frame = self.read_frame()?;
+            match framing::demux(&frame)? {
+                Incoming::Reply(reply) => return Ok(reply),
+                Incoming::Notification(notification) => {
+                    if self.notifications.len() == MAX_QUEUED_NOTIFICATIONS {
+                        self.notifications.pop_front();
+                    }
+                    self.notifications.push_back(notification);
+                }
+            }
         }
+    }
 
+    /// Write a message and read back the reply, (re)connecting if needed
+    fn try_exchange(&mut self, raw: &[u8]) -> Result<Value, Error> {
+        if !self.transport.is_connected() {
+            self.reconnect()?;
+        }
+        let written = self.transport.write_all(raw);
+        match written {
+            Err(ref e) if self.auto_reconnect && is_disconnect(e) => {
+                self.reconnect()?;
+                self.transport.write_all(raw).map_err(io_error)?;
+            }
+            other => other.map_err(io_error)?,
+        }
+        self.read_reply()
+    }
+
+    /// Write a message and read back the reply. On failure the connection is dropped, so that a
+    /// late answer cannot be mistaken for the response to the next request.
+    fn exchange(&mut self, raw: &[u8]) -> Result<Value, Error> {
+        let result = self.try_exchange(raw);
+        if result.is_err() {
+            self.disconnect();
+        }
+        result
+    }
+
+    /// Sends a request to a client
+    ///
+    /// A response with a different id than the request, for example one left over from a request
+    /// that timed out, is reported as `Error::IdMismatch` and drops the connection.
+    ///
+    /// With the `logging` feature, the method and id of the request and the outcome of the
+    /// response are logged at debug level, and the full JSON sent and received at trace level.
+    pub fn send_request(&mut self, request: &Request) -> Result<Response, Error> {
+        self.send_ref(&request.into())
+    }
+
+    /// Build and send a request for {method} in one go, without allocating a `Request`.
+    /// {params} are sent as they are, `null` parameters are not filtered out.
+    pub(crate) fn send_call(&mut self, method: &str, params: &Value) -> Result<Response, Error> {
+        let nonce = self.next_nonce();
+        let id = self.id_generator.lock().unwrap().next_id(nonce);
+        self.send_ref(&framing::RequestRef {
+            method,
+            params,
+            id: &id,
+            jsonrpc: Some("2.0"),
+        })
+    }
+
+    fn send_ref(&mut self, request: &framing::RequestRef) -> Result<Response, Error> {
+        let mut request_raw = mem::take(&mut self.scratch);
+        framing::encode_request_into(&mut request_raw, request)?;
+        framing::log_request(request.method, request.id);
+        log_trace!("-> {}", String::from_utf8_lossy(&request_raw));
+        let reply = self.exchange(&request_raw);
+        self.scratch = request_raw;
+        let reply = reply?;
+        log_trace!("<- {}", reply);
+        let response =
+            framing::reply_to_response(reply, request.id).inspect_err(|_| self.disconnect())?;
+        framing::log_response(&response);
         Ok(response)
     }
 
+    /// Sends a batch of requests in one go
+    ///
+    /// The responses are returned in the same order as the requests, regardless of the order
+    /// in which the server sent them.
+    pub fn send_batch(&mut self, requests: &[Request]) -> Result<Vec<Response>, Error> {
+        let request_raw = framing::encode_batch(requests)?;
+        requests
+            .iter()
+            .for_each(|r| framing::log_request(&r.method, &r.id));
+        log_trace!("-> {}", String::from_utf8_lossy(&request_raw));
+        let reply = self.exchange(&request_raw)?;
+        log_trace!("<- {}", reply);
+        let responses =
+            framing::reply_to_batch(reply, requests).inspect_err(|_| self.disconnect())?;
+        responses.iter().for_each(framing::log_response);
+        Ok(responses)
+    }
+
+    /// Iterate over notifications sent by the server, connecting first if needed.
+    ///
+    /// lightningd only sends notifications about requests made on the same connection, once
+    /// they were enabled with `notifications {"enable": true}`, see the `notifications` module.
+    ///
+    /// Notifications that arrived while waiting for a response are returned first, after that
+    /// the iterator blocks reading from the socket (subject to the timeout) and ends when the
+    /// server closes the connection. Up to 1024 notifications are kept between calls, older ones
+    /// are dropped. An error drops the connection, losing any notifications still in transit. A
+    /// response read here cannot belong to any request and is reported as
+    /// `Error::UnexpectedReply`.
+    pub fn notifications(&mut self) -> Notifications<'_> {
+        Notifications { client: self }
+    }
+
+    /// Take the notifications that arrived while waiting for responses, without reading more
+    pub fn queued_notifications(&mut self) -> Drain<'_, Notification> {
+        self.notifications.drain(..)
+    }
+
     /// Builds a request
-    pub fn build_request(&self, name: String, params: Json) -> Request {
+    pub fn build_request(&self, name: String, params: Value) -> Request {
+        let nonce = self.next_nonce();
+        let id = self.id_generator.lock().unwrap().next_id(nonce);
+        framing::new_request(name, params, id)
+    }
+
+    /// Increment the nonce and return it, for building a request
+    pub(crate) fn next_nonce(&self) -> u64 {
         let mut nonce = self.nonce.lock().unwrap();
         *nonce += 1;
-
-        Request {
-            method: name,
-            params: filter_nones(params),
-            id: From::from(*nonce),
-            jsonrpc: Some(String::from("2.0")),
-        }
+        *nonce
     }
 
     /// Accessor for the last-used nonce
@@ -99,18 +424,348 @@ impl Client {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use strason::Json;
+    use serde_json::json;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::os::unix::net::UnixListener;
+    use std::thread;
+
+    /// Answer `count` requests on `stream` with `true`
+    fn answer<S: Read + Write>(mut stream: S, count: usize) {
+        let mut framer = JsonFramer::new();
+        let mut buf = [0u8; 1024];
+        for _ in 0..count {
+            let frame = loop {
+                if let Some(frame) = framer.next_frame() {
+                    break frame;
+                }
+                let n = stream.read(&mut buf).unwrap();
+                framer.push(&buf[..n]);
+            };
+            let request: Request = serde_json::from_slice(&frame).unwrap();
+            let response = crate::error::result_to_response(Ok(From::from(true)), request.id);
+            stream
+                .write_all(&serde_json::to_vec(&response).unwrap())
+                .unwrap();
+        }
+    }
+
+    /// Accept one connection and answer `count` requests on it with `true`
+    fn serve(listener: UnixListener, count: usize) -> thread::JoinHandle<()> {
+        thread::spawn(move || answer(listener.accept().unwrap().0, count))
+    }
+
+    fn test_request(client: &Client) -> Request {
+        client.build_request("test".to_owned(), json!({}))
+    }
 
     #[test]
     fn sanity() {
         let client = Client::new("/tmp/socket/localhost");
         assert_eq!(client.last_nonce(), 0);
-        let req1 =
-            client.build_request("test".to_owned(), Json::from(Vec::<(String, Json)>::new()));
+        let req1 = client.build_request("test".to_owned(), json!({}));
         assert_eq!(client.last_nonce(), 1);
-        let req2 =
-            client.build_request("test".to_owned(), Json::from(Vec::<(String, Json)>::new()));
+        let req2 = client.build_request("test".to_owned(), json!({}));
         assert_eq!(client.last_nonce(), 2);
         assert!(req1 != req2);
     }
+
+    #[test]
+    fn custom_id_generator() {
+        struct Prefixed(&'static str);
+        impl IdGenerator for Prefixed {
+            fn next_id(&mut self, nonce: u64) -> Value {
+                Value::from(format!("{}-{}", self.0, nonce))
+            }
+        }
+
+        let mut client = Client::new("/tmp/socket/localhost");
+        let req1 = client.build_request("test".to_owned(), json!({}));
+        assert_eq!(req1.id, json!(1));
+        client.set_id_generator(Prefixed("myapp"));
+        let req2 = client.build_request("test".to_owned(), json!({}));
+        assert_eq!(req2.id, json!("myapp-2"));
+        assert_eq!(client.last_nonce(), 2);
+    }
+
+    #[test]
+    fn timeout_on_silent_socket() {
+        let path =
+            std::env::temp_dir().join(format!("clightningrpc-silent-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        thread::spawn(move || {
+            // Accept and read the request, but never answer
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = Vec::new();
+            let _ = stream.read_to_end(&mut buf);
+        });
+
+        let mut client = Client::new(&path);
+        client.set_timeout(Some(Duration::from_millis(100)));
+        let request = client.build_request("getinfo".to_owned(), json!({}));
+        match client.send_request(&request) {
+            Err(Error::Timeout) => {}
+            other => panic!("expected timeout, got {:?}", other),
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reconnect_after_restart() {
+        let path =
+            std::env::temp_dir().join(format!("clightningrpc-restart-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let server = serve(UnixListener::bind(&path).unwrap(), 2);
+
+        let mut client = Client::new(&path);
+        assert_eq!(client.socket_path(), Some(path.as_path()));
+        // Two requests over the same connection
+        for _ in 0..2 {
+            let request = test_request(&client);
+            assert!(client
+                .send_request(&request)
+                .unwrap()
+                .result::<bool>()
+                .unwrap());
+        }
+
+        // "Restart" the daemon
+        server.join().unwrap();
+        fs::remove_file(&path).unwrap();
+        let server = serve(UnixListener::bind(&path).unwrap(), 1);
+
+        let request = test_request(&client);
+        assert!(client.send_request(&request).is_err());
+        client.reconnect().unwrap();
+        let request = test_request(&client);
+        assert!(client
+            .send_request(&request)
+            .unwrap()
+            .result::<bool>()
+            .unwrap());
+        server.join().unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn auto_reconnect() {
+        let path =
+            std::env::temp_dir().join(format!("clightningrpc-autore-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let server = serve(UnixListener::bind(&path).unwrap(), 1);
+
+        let mut client = Client::new(&path);
+        client.set_auto_reconnect(true);
+        let request = test_request(&client);
+        assert!(client
+            .send_request(&request)
+            .unwrap()
+            .result::<bool>()
+            .unwrap());
+
+        server.join().unwrap();
+        fs::remove_file(&path).unwrap();
+        let server = serve(UnixListener::bind(&path).unwrap(), 1);
+
+        let request = test_request(&client);
+        assert!(client
+            .send_request(&request)
+            .unwrap()
+            .result::<bool>()
+            .unwrap());
+        server.join().unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn notifications_interleaved() {
+        let path = std::env::temp_dir().join(format!("clightningrpc-notif-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).unwrap();
+            stream
+                .write_all(
+                    br#"{"jsonrpc": "2.0", "method": "log", "params": {"n": 1}}
+                    {"jsonrpc": "2.0", "id": 1, "result": true}
+                    {"jsonrpc": "2.0", "method": "log", "params": {"n": 2}}"#,
+                )
+                .unwrap();
+        });
+
+        let mut client = Client::new(&path);
+        let request = test_request(&client);
+        assert!(client
+            .send_request(&request)
+            .unwrap()
+            .result::<bool>()
+            .unwrap());
+        server.join().unwrap();
+        let queued: Vec<Notification> = client.queued_notifications().collect();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].params.get("n"), Some(&json!(1)));
+        assert_eq!(queued[0].method, "log");
+        // The one after the response is only read by the iterator
+        let received: Vec<Notification> = client.notifications().collect::<Result<_, _>>().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].params.get("n"), Some(&json!(2)));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn notifications_unexpected_reply() {
+        let path = std::env::temp_dir().join(format!("clightningrpc-stray-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream
+                .write_all(br#"{"jsonrpc": "2.0", "id": 7, "result": true}"#)
+                .unwrap();
+        });
+
+        let mut client = Client::new(&path);
+        match client.notifications().next() {
+            Some(Err(Error::UnexpectedReply { got })) => assert_eq!(got, json!(7)),
+            other => panic!("expected an unexpected reply, got {:?}", other),
+        }
+        server.join().unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn tcp_loopback() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || answer(listener.accept().unwrap().0, 2));
+
+        let mut client = Client::from_tcp(addr.to_string());
+        assert_eq!(client.socket_path(), None);
+        for _ in 0..2 {
+            let request = test_request(&client);
+            assert!(client
+                .send_request(&request)
+                .unwrap()
+                .result::<bool>()
+                .unwrap());
+        }
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn mismatched_id() {
+        let path =
+            std::env::temp_dir().join(format!("clightningrpc-mismatch-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = thread::spawn(move || {
+            // A stale response to an earlier request, followed by the right one on a new
+            // connection
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).unwrap();
+            let stale = crate::error::result_to_response(Ok(From::from("stale")), json!(999));
+            stream
+                .write_all(&serde_json::to_vec(&stale).unwrap())
+                .unwrap();
+            answer(listener.accept().unwrap().0, 1);
+        });
+
+        let mut client = Client::new(&path);
+        let request = test_request(&client);
+        match client.send_request(&request) {
+            Err(Error::IdMismatch { expected, got }) => {
+                assert_eq!(expected, request.id);
+                assert_eq!(got, json!(999));
+            }
+            other => panic!("expected id mismatch, got {:?}", other),
+        }
+        // The connection was dropped, so the next request gets its own answer
+        let request = test_request(&client);
+        assert!(client
+            .send_request(&request)
+            .unwrap()
+            .result::<bool>()
+            .unwrap());
+        server.join().unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn response_in_small_chunks() {
+        let path =
+            std::env::temp_dir().join(format!("clightningrpc-chunks-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        // Larger than the read buffer, with braces and escapes inside strings and a multi-byte
+        // character, all of which are likely to be split between chunks
+        let channels: Vec<Value> = (0..200)
+            .map(|i| json!({"short_channel_id": format!("{}x1x0", i), "alias": "{\"ünïcode\"}"}))
+            .collect();
+        let expected = json!({ "channels": channels });
+        let result = expected.clone();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).unwrap();
+            let response = crate::error::result_to_response(Ok(result), json!(1));
+            let raw = serde_json::to_vec(&response).unwrap();
+            assert!(raw.len() > 4096);
+            for chunk in raw.chunks(7) {
+                stream.write_all(chunk).unwrap();
+                stream.flush().unwrap();
+            }
+        });
+
+        let mut client = Client::new(&path);
+        let request = test_request(&client);
+        let response = client.send_request(&request).unwrap();
+        assert_eq!(response.result::<Value>().unwrap(), expected);
+        server.join().unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn drop_disconnects() {
+        let mock = crate::testing::MockTransport::new();
+        mock.push_result("test", true).unwrap();
+        let mut client = Client::with_transport(mock.clone());
+        let request = test_request(&client);
+        client.send_request(&request).unwrap();
+        assert!(mock.is_connected());
+        drop(client);
+        assert!(!mock.is_connected());
+    }
+
+    #[test]
+    fn response_too_large() {
+        let path =
+            std::env::temp_dir().join(format!("clightningrpc-too-large-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).unwrap();
+            // An array that never ends, until the client hangs up
+            stream.write_all(b"[").unwrap();
+            let chunk = [b'1', b','].repeat(512);
+            while stream.write_all(&chunk).is_ok() {}
+        });
+
+        let mut client = Client::new(&path);
+        client.set_max_response_bytes(16 * 1024);
+        let request = test_request(&client);
+        match client.send_request(&request) {
+            Err(Error::ResponseTooLarge { limit }) => assert_eq!(limit, 16 * 1024),
+            other => panic!("expected response too large, got {:?}", other),
+        }
+        assert!(!client.is_connected());
+        server.join().unwrap();
+        fs::remove_file(&path).unwrap();
+    }
 }
@@ -1,11 +1,1088 @@
 #![allow(missing_docs)]
 //! Common structures between requests and responses
 
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::error::Error;
+
+/// An amount in millisatoshi
+///
+/// Serializes as an integer. Deserializes from either an integer or the `"<n>msat"` string form
+/// that c-lightning uses for amounts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct MSat(pub u64);
+
+impl MSat {
+    /// Construct an amount from a number of satoshi, `None` if it does not fit in millisatoshi
+    pub fn from_sat(sat: u64) -> Option<MSat> {
+        sat.checked_mul(1000).map(MSat)
+    }
+
+    /// Construct an amount from a number of bitcoin, rounded to the nearest millisatoshi
+    ///
+    /// `None` if {btc} is negative, not finite or too large.
+    pub fn from_btc(btc: f64) -> Option<MSat> {
+        let msat = (btc * 100_000_000_000.0).round();
+        // Up to 2^64, the first value that does not fit; NaN is not in the range either
+        if (0.0..18_446_744_073_709_551_616.0).contains(&msat) {
+            Some(MSat(msat as u64))
+        } else {
+            None
+        }
+    }
+
+    /// Return the amount in millisatoshi
+    pub fn to_msat(self) -> u64 {
+        self.0
+    }
+
+    /// Return the amount in whole satoshi, rounded down
+    pub fn to_sat(self) -> u64 {
+        self.0 / 1000
+    }
+}
+
+impl From<u64> for MSat {
+    fn from(msat: u64) -> MSat {
+        MSat(msat)
+    }
+}
+
+impl fmt::Display for MSat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}msat", self.0)
+    }
+}
+
+impl Serialize for MSat {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for MSat {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<MSat, D::Error> {
+        struct MSatVisitor;
+
+        impl<'de> de::Visitor<'de> for MSatVisitor {
+            type Value = MSat;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an amount in millisatoshi, as integer or \"<n>msat\" string")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<MSat, E> {
+                Ok(MSat(v))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<MSat, E> {
+                if v < 0 {
+                    return Err(E::invalid_value(de::Unexpected::Signed(v), &self));
+                }
+                Ok(MSat(v as u64))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<MSat, E> {
+                let digits = v.strip_suffix("msat").unwrap_or(v);
+                digits
+                    .parse()
+                    .map(MSat)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
+            }
+        }
+
+        deserializer.deserialize_any(MSatVisitor)
+    }
+}
+
+/// Version of lightningd, as reported by 'getinfo'
+///
+/// Only the numeric part is kept, so "v23.08.1-modded" and "v23.08.1" are the same version and
+/// release candidates compare equal to the release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    /// Major version, the year for releases since v0.12
+    pub major: u32,
+    /// Minor version, the month for releases since v0.12
+    pub minor: u32,
+    /// Patch level, 0 if not given
+    pub patch: u32,
+}
+
+impl Version {
+    /// Construct a version, for comparing against
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Version {
+        Version {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl FromStr for Version {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Version, String> {
+        let unrecognized = || format!("unrecognized version {:?}", s);
+        let trimmed = s.strip_prefix('v').unwrap_or(s);
+        let end = trimmed
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(trimmed.len());
+        let mut parts = trimmed[..end].trim_end_matches('.').split('.');
+        let mut next = || {
+            parts
+                .next()
+                .map(|p| p.parse::<u32>().map_err(|_| unrecognized()))
+        };
+        let major = next().ok_or_else(unrecognized)??;
+        let minor = next().ok_or_else(unrecognized)??;
+        let patch = next().transpose()?.unwrap_or(0);
+        if next().is_some() {
+            return Err(unrecognized());
+        }
+        Ok(Version::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl<'de> Deserialize<'de> for Version {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Version, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Short channel id, locating the funding output of a channel in the blockchain
+///
+/// (De)serializes as the `"<block>x<txindex>x<outnum>"` string form, e.g. `"103x1x0"`. Like in
+/// the gossip encoding, the block height and transaction index are limited to 24 bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShortChannelId {
+    block: u32,
+    txindex: u32,
+    outnum: u16,
+}
+
+impl ShortChannelId {
+    /// Largest block height or transaction index a short channel id can hold
+    pub const MAX_BLOCK: u32 = 0xff_ffff;
+
+    /// Construct a short channel id, `None` if {block} or {txindex} exceed `MAX_BLOCK`
+    pub fn new(block: u32, txindex: u32, outnum: u16) -> Option<ShortChannelId> {
+        if block > Self::MAX_BLOCK || txindex > Self::MAX_BLOCK {
+            return None;
+        }
+        Some(ShortChannelId {
+            block,
+            txindex,
+            outnum,
+        })
+    }
+
+    /// Height of the block containing the funding transaction
+    pub fn block(&self) -> u32 {
+        self.block
+    }
+
+    /// Index of the funding transaction within its block
+    pub fn txindex(&self) -> u32 {
+        self.txindex
+    }
+
+    /// Index of the funding output within the funding transaction
+    pub fn outnum(&self) -> u16 {
+        self.outnum
+    }
+}
+
+impl FromStr for ShortChannelId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ShortChannelId, String> {
+        let invalid = || {
+            format!(
+                "invalid short channel id {:?}, expected <block>x<txindex>x<outnum>",
+                s
+            )
+        };
+        let mut parts = s.split('x').map(|p| {
+            if !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit()) {
+                Some(p)
+            } else {
+                None
+            }
+        });
+        let mut next = || parts.next().flatten().ok_or_else(invalid);
+        let block = next()?.parse().map_err(|_| invalid())?;
+        let txindex = next()?.parse().map_err(|_| invalid())?;
+        let outnum = next()?.parse().map_err(|_| invalid())?;
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        ShortChannelId::new(block, txindex, outnum).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for ShortChannelId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}x{}", self.block, self.txindex, self.outnum)
+    }
+}
+
+impl Serialize for ShortChannelId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ShortChannelId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ShortChannelId, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Feature bitfield of a node, channel or invoice
+///
+/// (De)serializes as the big-endian hex string lightningd reports, e.g. `"08a0000a8a5961"`, with
+/// bit 0 the lowest bit of the last byte. Features come in pairs: the even bit means the feature
+/// is required, the odd bit that it is supported.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct FeatureBits(Vec<u8>);
+
+impl FeatureBits {
+    pub const OPTION_DATA_LOSS_PROTECT: u16 = 0;
+    pub const VAR_ONION_OPTIN: u16 = 8;
+    pub const OPTION_STATIC_REMOTEKEY: u16 = 12;
+    pub const PAYMENT_SECRET: u16 = 14;
+    pub const BASIC_MPP: u16 = 16;
+    pub const OPTION_SUPPORT_LARGE_CHANNEL: u16 = 18;
+    pub const OPTION_ANCHORS: u16 = 22;
+    pub const OPTION_ROUTE_BLINDING: u16 = 24;
+    pub const OPTION_DUAL_FUND: u16 = 28;
+    pub const OPTION_ONION_MESSAGES: u16 = 38;
+    pub const OPTION_CHANNEL_TYPE: u16 = 44;
+    pub const OPTION_SCID_ALIAS: u16 = 46;
+    pub const OPTION_ZEROCONF: u16 = 50;
+    pub const KEYSEND: u16 = 54;
+
+    /// Whether {bit} is set
+    pub fn has(&self, bit: u16) -> bool {
+        let byte = usize::from(bit / 8);
+        byte < self.0.len() && self.0[self.0.len() - 1 - byte] & (1 << (bit % 8)) != 0
+    }
+
+    /// Whether the feature of the pair {bit} belongs to is set, as required or as supported
+    pub fn supports(&self, bit: u16) -> bool {
+        self.has(bit & !1) || self.has(bit | 1)
+    }
+
+    /// Whether the bitfield is empty
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|b| *b == 0)
+    }
+
+    pub fn var_onion_optin(&self) -> bool {
+        self.supports(Self::VAR_ONION_OPTIN)
+    }
+
+    pub fn payment_secret(&self) -> bool {
+        self.supports(Self::PAYMENT_SECRET)
+    }
+
+    pub fn basic_mpp(&self) -> bool {
+        self.supports(Self::BASIC_MPP)
+    }
+
+    pub fn large_channels(&self) -> bool {
+        self.supports(Self::OPTION_SUPPORT_LARGE_CHANNEL)
+    }
+
+    /// `option_anchors_zero_fee_htlc_tx`
+    pub fn anchors(&self) -> bool {
+        self.supports(Self::OPTION_ANCHORS)
+    }
+
+    pub fn route_blinding(&self) -> bool {
+        self.supports(Self::OPTION_ROUTE_BLINDING)
+    }
+
+    pub fn dual_fund(&self) -> bool {
+        self.supports(Self::OPTION_DUAL_FUND)
+    }
+
+    pub fn onion_messages(&self) -> bool {
+        self.supports(Self::OPTION_ONION_MESSAGES)
+    }
+
+    pub fn scid_alias(&self) -> bool {
+        self.supports(Self::OPTION_SCID_ALIAS)
+    }
+
+    pub fn zeroconf(&self) -> bool {
+        self.supports(Self::OPTION_ZEROCONF)
+    }
+
+    pub fn keysend(&self) -> bool {
+        self.supports(Self::KEYSEND)
+    }
+}
+
+impl FromStr for FeatureBits {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<FeatureBits, String> {
+        if !s.len().is_multiple_of(2) || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(format!("invalid feature bits {:?}, expected hex", s));
+        }
+        Ok(FeatureBits(
+            (0..s.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+                .collect(),
+        ))
+    }
+}
+
+impl fmt::Display for FeatureBits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for b in &self.0 {
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for FeatureBits {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for FeatureBits {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<FeatureBits, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Network address of a node, depending on its type
+///
+/// Serializes as a `{"type", "address", "port"}` object, as in 'getinfo' and 'connect'.
+/// Deserializes from that or from the `"<host>:<port>"` string form of 'listpeers' netaddr,
+/// e.g. `"[::1]:9735"` or `"<56 characters>.onion:9735"`; host names that are not an IP or onion
+/// address become `Dns`, and strings that cannot be parsed are kept as `Raw`.
+// `remote = "Self"` derives the object form as inherent functions, which the trait impls below
+// build on
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(remote = "Self", tag = "type")]
+pub enum NetAddress {
+    #[serde(rename = "local socket")]
+    LocalSocket { socket: String },
+    #[serde(rename = "ipv4")]
+    Ipv4 { address: String, port: u16 },
+    #[serde(rename = "ipv6")]
+    Ipv6 { address: String, port: u16 },
+    #[serde(rename = "torv2")]
+    Torv2 { address: String, port: u16 },
+    #[serde(rename = "torv3")]
+    Torv3 { address: String, port: u16 },
+    /// A websocket listener
+    #[serde(rename = "websocket", alias = "wss")]
+    Wss { address: String, port: u16 },
+    #[serde(rename = "dns")]
+    Dns { address: String, port: u16 },
+    /// An address in string form that could not be parsed, as received
+    #[serde(skip)]
+    Raw(String),
+    /// An address type not known to this version of the crate
+    #[serde(other)]
+    Unknown,
+}
+
+impl FromStr for NetAddress {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<NetAddress, String> {
+        let invalid = || format!("invalid address {:?}, expected <host>:<port>", s);
+        let (host, port) = s.rsplit_once(':').ok_or_else(invalid)?;
+        let port = port.parse().map_err(|_| invalid())?;
+        if let Some(ip) = host.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+            return Ok(NetAddress::Ipv6 {
+                address: ip.to_owned(),
+                port,
+            });
+        }
+        let address = host.to_owned();
+        if host.parse::<Ipv4Addr>().is_ok() {
+            Ok(NetAddress::Ipv4 { address, port })
+        } else if host.parse::<Ipv6Addr>().is_ok() {
+            Ok(NetAddress::Ipv6 { address, port })
+        } else if let Some(onion) = host.strip_suffix(".onion") {
+            match onion.len() {
+                16 => Ok(NetAddress::Torv2 { address, port }),
+                56 => Ok(NetAddress::Torv3 { address, port }),
+                _ => Err(invalid()),
+            }
+        } else if !host.is_empty() {
+            Ok(NetAddress::Dns { address, port })
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+impl fmt::Display for NetAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetAddress::LocalSocket { socket } => f.write_str(socket),
+            NetAddress::Ipv6 { address, port } => write!(f, "[{}]:{}", address, port),
+            NetAddress::Ipv4 { address, port }
+            | NetAddress::Torv2 { address, port }
+            | NetAddress::Torv3 { address, port }
+            | NetAddress::Wss { address, port }
+            | NetAddress::Dns { address, port } => write!(f, "{}:{}", address, port),
+            NetAddress::Unknown => f.write_str("unknown"),
+            NetAddress::Raw(raw) => f.write_str(raw),
+        }
+    }
+}
+
+impl Serialize for NetAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            NetAddress::Raw(raw) => serializer.serialize_str(raw),
+            other => NetAddress::serialize(other, serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for NetAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<NetAddress, D::Error> {
+        match Value::deserialize(deserializer)? {
+            // One odd address should not make the whole response unreadable
+            Value::String(s) => Ok(s.parse().unwrap_or(NetAddress::Raw(s))),
+            other => NetAddress::deserialize(other).map_err(de::Error::custom),
+        }
+    }
+}
+
 /// Sub-structure for route in 'pay', 'getroute' and 'sendpay'
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct RouteItem {
     pub id: String,
-    pub channel: String,
-    pub msatoshi: i64,
+    pub channel: ShortChannelId,
+    pub msatoshi: MSat,
     pub delay: i64,
 }
+
+string_enum! {
+    /// State of a channel in 'listpeers' and 'listfunds'
+    pub enum ChannelState {
+        /// Negotiating the channel with the peer
+        Openingd => "OPENINGD",
+        /// Waiting for the funding transaction to confirm
+        ChanneldAwaitingLockin => "CHANNELD_AWAITING_LOCKIN",
+        /// Channel is open and can be used for payments
+        ChanneldNormal => "CHANNELD_NORMAL",
+        /// Waiting for a splice transaction to confirm, the channel can still be used
+        ChanneldAwaitingSplice => "CHANNELD_AWAITING_SPLICE",
+        /// A mutual close was requested, no new HTLCs are accepted
+        ChanneldShuttingDown => "CHANNELD_SHUTTING_DOWN",
+        /// Negotiating the mutual close fee with the peer
+        ClosingdSigexchange => "CLOSINGD_SIGEXCHANGE",
+        /// Mutual close transaction was broadcast
+        ClosingdComplete => "CLOSINGD_COMPLETE",
+        /// Waiting for the peer to close the channel unilaterally
+        AwaitingUnilateral => "AWAITING_UNILATERAL",
+        /// A spend of the funding output was seen
+        FundingSpendSeen => "FUNDING_SPEND_SEEN",
+        /// The funding output was spent and outputs are being watched onchain
+        Onchain => "ONCHAIN",
+        /// Channel is closed and everything was resolved onchain
+        Closed => "CLOSED",
+        /// Dual-funded channel negotiation was started
+        DualopendOpenInit => "DUALOPEND_OPEN_INIT",
+        /// Dual-funded channel commitment was exchanged
+        DualopendOpenCommitted => "DUALOPEND_OPEN_COMMITTED",
+        /// Dual-funded channel is ready to be committed
+        DualopendOpenCommitReady => "DUALOPEND_OPEN_COMMIT_READY",
+        /// Waiting for the dual-funded transaction to confirm
+        DualopendAwaitingLockin => "DUALOPEND_AWAITING_LOCKIN",
+    }
+}
+
+impl ChannelState {
+    /// Whether the channel can be used for payments
+    pub fn is_active(&self) -> bool {
+        matches!(
+            *self,
+            ChannelState::ChanneldNormal | ChannelState::ChanneldAwaitingSplice
+        )
+    }
+
+    /// Whether the channel is being closed but the funding output was not resolved onchain yet
+    pub fn is_closing(&self) -> bool {
+        matches!(
+            *self,
+            ChannelState::ChanneldShuttingDown
+                | ChannelState::ClosingdSigexchange
+                | ChannelState::ClosingdComplete
+                | ChannelState::AwaitingUnilateral
+                | ChannelState::FundingSpendSeen
+        )
+    }
+
+    /// Whether the funding output was spent and lightningd is watching the outputs onchain
+    pub fn is_onchain(&self) -> bool {
+        *self == ChannelState::Onchain
+    }
+}
+
+string_enum! {
+    /// Chain lightningd runs on, as in 'getinfo'
+    pub enum Network {
+        /// Bitcoin mainnet
+        Bitcoin => "bitcoin",
+        /// Testnet3
+        Testnet => "testnet",
+        /// Testnet4
+        Testnet4 => "testnet4",
+        /// Signet
+        Signet => "signet",
+        /// Local regression test network
+        Regtest => "regtest",
+    }
+}
+
+impl Network {
+    /// Subdirectory of the lightning directory that lightningd uses for this network, and where
+    /// it creates its RPC socket
+    pub fn subdirectory(&self) -> &str {
+        self.as_str()
+    }
+}
+
+string_enum! {
+    /// Subsystem whose changes 'wait' waits for
+    pub enum WaitSubsystem {
+        Invoices => "invoices",
+        Forwards => "forwards",
+        Sendpays => "sendpays",
+    }
+}
+
+string_enum! {
+    /// Index of a subsystem in 'wait', counting the entries that were created, updated or
+    /// deleted so far
+    pub enum WaitIndex {
+        Created => "created",
+        Updated => "updated",
+        Deleted => "deleted",
+    }
+}
+
+/// Check that {value} is a hex string of {len} characters, naming the parameter in the error
+pub(crate) fn check_hex(name: &str, value: &str, len: usize) -> Result<(), Error> {
+    if value.len() == len && value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        Ok(())
+    } else {
+        Err(Error::InvalidInput(format!(
+            "{} must be {} hex characters",
+            name, len
+        )))
+    }
+}
+
+/// Check that {value} is hex encoded bytes, at least {min_bytes} long, naming the parameter in
+/// the error
+pub(crate) fn check_hex_bytes(name: &str, value: &str, min_bytes: usize) -> Result<(), Error> {
+    if value.len() >= 2 * min_bytes
+        && value.len().is_multiple_of(2)
+        && value.bytes().all(|b| b.is_ascii_hexdigit())
+    {
+        Ok(())
+    } else if min_bytes == 0 {
+        Err(Error::InvalidInput(format!("{} must be hex bytes", name)))
+    } else {
+        Err(Error::InvalidInput(format!(
+            "{} must be hex bytes, at least {} long",
+            name, min_bytes
+        )))
+    }
+}
+
+/// Check that {value} is at most {max}, naming the parameter in the error
+pub(crate) fn check_max(name: &str, value: u16, max: u16) -> Result<(), Error> {
+    if value <= max {
+        Ok(())
+    } else {
+        Err(Error::InvalidInput(format!(
+            "{} must be at most {}",
+            name, max
+        )))
+    }
+}
+
+/// Check that the TLV records in {tlvs} have odd types, which recipients that do not understand
+/// them ignore, and hex payloads
+pub(crate) fn check_extratlvs(tlvs: &BTreeMap<u64, String>) -> Result<(), Error> {
+    for (tlv_type, payload) in tlvs {
+        if tlv_type.is_multiple_of(2) {
+            return Err(Error::InvalidInput(format!(
+                "extratlvs type {} must be odd",
+                tlv_type
+            )));
+        }
+        check_hex_bytes(
+            &format!("extratlvs payload of type {}", tlv_type),
+            payload,
+            0,
+        )?;
+    }
+    Ok(())
+}
+
+/// Convert {value} to unsigned, naming the parameter in the error if it is negative
+pub(crate) fn non_negative(name: &str, value: i64) -> Result<u64, Error> {
+    u64::try_from(value)
+        .map_err(|_| Error::InvalidInput(format!("{} must not be negative, got {}", name, value)))
+}
+
+/// Convert an amount as accepted by lightningd, e.g. "1000" (satoshi), "1000sat", "1000000msat"
+/// or "0.00001btc", to msat. `None` if it is not numeric, such as "all", or too large.
+pub(crate) fn amount_to_msat(amount: &str) -> Option<u64> {
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if let Some(msat) = amount.strip_suffix("msat") {
+        return if digits(msat) {
+            msat.parse().ok()
+        } else {
+            None
+        };
+    }
+    if let Some(btc) = amount.strip_suffix("btc") {
+        let (whole, frac) = match btc.find('.') {
+            Some(i) => (&btc[..i], &btc[i + 1..]),
+            None => (btc, ""),
+        };
+        if !digits(whole) || (!frac.is_empty() && !digits(frac)) || frac.len() > 11 {
+            return None;
+        }
+        let frac: u64 = format!("{:0<11}", frac).parse().ok()?;
+        let whole: u64 = whole.parse().ok()?;
+        return whole.checked_mul(100_000_000_000)?.checked_add(frac);
+    }
+    let sat = amount.strip_suffix("sat").unwrap_or(amount);
+    if !digits(sat) {
+        return None;
+    }
+    sat.parse::<u64>().ok()?.checked_mul(1000)
+}
+
+/// Check that {value} names an output as "txid:vout"
+pub(crate) fn check_utxo(value: &str) -> Result<(), Error> {
+    let valid = match value.find(':') {
+        Some(i) => {
+            check_hex("txid", &value[..i], 64).is_ok() && value[i + 1..].parse::<u32>().is_ok()
+        }
+        None => false,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidInput(format!(
+            "utxo must be of the form txid:vout, got {:?}",
+            value
+        )))
+    }
+}
+
+/// Check that {value} is a compressed public key in hex, naming the parameter in the error
+pub(crate) fn check_pubkey(name: &str, value: &str) -> Result<(), Error> {
+    check_hex(name, value, 66)?;
+    if value.starts_with("02") || value.starts_with("03") {
+        Ok(())
+    } else {
+        Err(Error::InvalidInput(format!(
+            "{} must be a compressed public key, starting with 02 or 03",
+            name
+        )))
+    }
+}
+
+/// Check that {value} is non-empty, padded base64, naming the parameter in the error
+pub(crate) fn check_base64(name: &str, value: &str) -> Result<(), Error> {
+    let data = value.trim_end_matches('=');
+    let valid = !data.is_empty()
+        && value.len().is_multiple_of(4)
+        && value.len() - data.len() <= 2
+        && data
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'/');
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidInput(format!("{} must be base64", name)))
+    }
+}
+
+/// Check that {msg} is a hex encoded peer message with an odd type
+pub(crate) fn check_custommsg(msg: &str) -> Result<(), Error> {
+    check_hex_bytes("msg", msg, 2)?;
+    let msgtype = u16::from_str_radix(&msg[..4], 16).expect("checked hex");
+    if msgtype.is_multiple_of(2) {
+        return Err(Error::InvalidInput(format!(
+            "msg type {} is even, custom messages must have an odd type",
+            msgtype
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn msat_deserialize_forms() {
+        let int: MSat = serde_json::from_str("1000").unwrap();
+        let string: MSat = serde_json::from_str("\"1000msat\"").unwrap();
+        assert_eq!(int, MSat(1000));
+        assert_eq!(string, MSat(1000));
+        assert!(serde_json::from_str::<MSat>("\"1000sat\"").is_err());
+        assert!(serde_json::from_str::<MSat>("-1").is_err());
+    }
+
+    #[test]
+    fn msat_conversions() {
+        assert_eq!(MSat::from_sat(21), Some(MSat(21_000)));
+        assert_eq!(MSat::from_sat(u64::MAX / 100), None);
+        assert_eq!(MSat::from_btc(0.001), Some(MSat(100_000_000)));
+        assert_eq!(MSat::from_btc(21e6), Some(MSat(2_100_000_000_000_000_000)));
+        assert_eq!(MSat::from_btc(-0.001), None);
+        assert_eq!(MSat::from_btc(f64::NAN), None);
+        assert_eq!(MSat::from_btc(f64::INFINITY), None);
+        assert_eq!(MSat::from_btc(1e9), None);
+        assert_eq!(MSat::from(5u64).to_string(), "5msat");
+        assert_eq!(MSat(123_456).to_sat(), 123);
+        let ser = serde_json::to_value(MSat(42)).unwrap();
+        assert_eq!(ser, serde_json::Value::from(42));
+        assert_eq!(serde_json::from_value::<MSat>(ser).unwrap(), MSat(42));
+    }
+
+    #[test]
+    fn hex_check() {
+        let preimage = "00".repeat(32);
+        assert!(check_hex("preimage", &preimage, 64).is_ok());
+        assert!(check_hex("preimage", &preimage[1..], 64).is_err());
+        match check_hex("preimage", &"zz".repeat(32), 64) {
+            Err(Error::InvalidInput(msg)) => assert_eq!(msg, "preimage must be 64 hex characters"),
+            other => panic!("expected invalid input, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn pubkey_check() {
+        let point = format!("02{}", "ab".repeat(32));
+        assert!(check_pubkey("point", &point).is_ok());
+        assert!(check_pubkey("point", &point[..64]).is_err());
+        match check_pubkey("point", &format!("04{}", "ab".repeat(32))) {
+            Err(Error::InvalidInput(msg)) => assert!(msg.starts_with("point must be a compressed")),
+            other => panic!("expected invalid input, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn base64_check() {
+        assert!(check_base64("psbt", "cHNidP8BAHEC").is_ok());
+        assert!(check_base64("psbt", "cHNidA==").is_ok());
+        for invalid in ["", "====", "cHNidP8", "cHNi dA==", "cHN=dA==", "cHNidA==="].iter() {
+            assert!(check_base64("psbt", invalid).is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn short_channel_id_parse() {
+        let scid: ShortChannelId = "682149x1423x0".parse().unwrap();
+        assert_eq!(
+            (scid.block(), scid.txindex(), scid.outnum()),
+            (682149, 1423, 0)
+        );
+        assert_eq!(scid.to_string(), "682149x1423x0");
+
+        let max = "16777215x16777215x65535";
+        let scid: ShortChannelId = max.parse().unwrap();
+        assert_eq!(
+            scid,
+            ShortChannelId::new(16_777_215, 16_777_215, 65535).unwrap()
+        );
+        assert_eq!(scid.to_string(), max);
+        assert_eq!("0x0x0".parse(), Ok(ShortChannelId::new(0, 0, 0).unwrap()));
+        assert!(ShortChannelId::new(ShortChannelId::MAX_BLOCK + 1, 0, 0).is_none());
+
+        for invalid in [
+            "",
+            "103x1",
+            "103x1x0x0",
+            "103:1:0",
+            "103x1x",
+            "x1x0",
+            "103x+1x0",
+            "103x1x65536",
+            "16777216x0x0",
+            "0x16777216x0",
+        ]
+        .iter()
+        {
+            assert!(invalid.parse::<ShortChannelId>().is_err(), "{:?}", invalid);
+        }
+
+        let ser = serde_json::to_value(scid).unwrap();
+        assert_eq!(ser, serde_json::Value::from(max));
+        assert_eq!(serde_json::from_value::<ShortChannelId>(ser).unwrap(), scid);
+        let err = serde_json::from_str::<ShortChannelId>("\"103x1\"").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("invalid short channel id \"103x1\""));
+        assert!(serde_json::from_str::<ShortChannelId>("103").is_err());
+    }
+
+    #[test]
+    fn hex_bytes_check() {
+        assert!(check_hex_bytes("payload", "", 0).is_ok());
+        assert!(check_hex_bytes("payload", "00ff", 2).is_ok());
+        for invalid in ["0", "00f", "0g"].iter() {
+            assert!(
+                check_hex_bytes("payload", invalid, 0).is_err(),
+                "{:?}",
+                invalid
+            );
+        }
+        match check_hex_bytes("message", "00", 2) {
+            Err(Error::InvalidInput(msg)) => {
+                assert_eq!(msg, "message must be hex bytes, at least 2 long")
+            }
+            other => panic!("expected invalid input, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn custommsg_check() {
+        assert!(check_custommsg("ffff").is_ok());
+        assert!(check_custommsg("a0010102").is_ok());
+        for invalid in ["", "ff", "fff", "a001010", "a00g", "a000"].iter() {
+            assert!(check_custommsg(invalid).is_err(), "{:?}", invalid);
+        }
+        match check_custommsg("80000102") {
+            Err(Error::InvalidInput(msg)) => assert!(msg.contains("32768 is even")),
+            other => panic!("expected invalid input, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn max_check() {
+        assert!(check_max("len", 65529, 65529).is_ok());
+        match check_max("len", 65530, 65529) {
+            Err(Error::InvalidInput(msg)) => assert_eq!(msg, "len must be at most 65529"),
+            other => panic!("expected invalid input, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn channel_state_round_trip() {
+        let known = [
+            "OPENINGD",
+            "CHANNELD_AWAITING_LOCKIN",
+            "CHANNELD_NORMAL",
+            "CHANNELD_AWAITING_SPLICE",
+            "CHANNELD_SHUTTING_DOWN",
+            "CLOSINGD_SIGEXCHANGE",
+            "CLOSINGD_COMPLETE",
+            "AWAITING_UNILATERAL",
+            "FUNDING_SPEND_SEEN",
+            "ONCHAIN",
+            "CLOSED",
+            "DUALOPEND_OPEN_INIT",
+            "DUALOPEND_OPEN_COMMITTED",
+            "DUALOPEND_OPEN_COMMIT_READY",
+            "DUALOPEND_AWAITING_LOCKIN",
+        ];
+        for name in known.iter() {
+            let json = serde_json::to_string(name).unwrap();
+            let state: ChannelState = serde_json::from_str(&json).unwrap();
+            if let ChannelState::Unknown(_) = state {
+                panic!("{} not recognized", name);
+            }
+            assert_eq!(serde_json::to_string(&state).unwrap(), json);
+        }
+
+        let state: ChannelState = serde_json::from_str("\"CHANNELD_FUTURE\"").unwrap();
+        assert_eq!(state, ChannelState::Unknown("CHANNELD_FUTURE".to_owned()));
+        assert_eq!(
+            serde_json::to_string(&state).unwrap(),
+            "\"CHANNELD_FUTURE\""
+        );
+        assert!(!state.is_active() && !state.is_closing() && !state.is_onchain());
+
+        assert!(ChannelState::ChanneldNormal.is_active());
+        assert!(ChannelState::ClosingdComplete.is_closing());
+        assert!(!ChannelState::Onchain.is_closing());
+        assert!(ChannelState::Onchain.is_onchain());
+    }
+
+    #[test]
+    fn network_round_trip() {
+        for name in ["bitcoin", "testnet", "testnet4", "signet", "regtest"].iter() {
+            let json = serde_json::to_string(name).unwrap();
+            let network: Network = serde_json::from_str(&json).unwrap();
+            if let Network::Unknown(_) = network {
+                panic!("{} not recognized", name);
+            }
+            assert_eq!(serde_json::to_string(&network).unwrap(), json);
+            assert_eq!(network.subdirectory(), *name);
+        }
+        let network: Network = serde_json::from_str("\"liquid\"").unwrap();
+        assert_eq!(network, Network::Unknown("liquid".to_owned()));
+        assert_eq!(serde_json::to_string(&network).unwrap(), "\"liquid\"");
+        assert_eq!(network.subdirectory(), "liquid");
+    }
+
+    #[test]
+    fn version_parse() {
+        let parse = |s: &str| s.parse::<Version>();
+        assert_eq!(parse("v23.08.1"), Ok(Version::new(23, 8, 1)));
+        assert_eq!(parse("v24.02"), Ok(Version::new(24, 2, 0)));
+        assert_eq!(parse("v24.02-modded"), Ok(Version::new(24, 2, 0)));
+        assert_eq!(parse("0.6.1rc1-123-gabcd"), Ok(Version::new(0, 6, 1)));
+        assert_eq!(parse("v0.12.0rc2"), Ok(Version::new(0, 12, 0)));
+        assert!(parse("v23").is_err());
+        assert!(parse("1.2.3.4").is_err());
+        assert!(parse("abcdef").is_err());
+        assert!(Version::new(23, 8, 1) > Version::new(0, 12, 1));
+        assert_eq!(Version::new(23, 8, 1).to_string(), "23.8.1");
+    }
+
+    #[test]
+    fn utxo_format() {
+        let txid = "f5dfb2ff1fd1a1a8c2e1fc3a8a5e3b1de7df6389a359312a1777cd4b5d5b6b07";
+        assert!(check_utxo(&format!("{}:0", txid)).is_ok());
+        assert!(check_utxo(&format!("{}:4294967295", txid)).is_ok());
+        assert!(check_utxo(txid).is_err());
+        assert!(check_utxo(&format!("{}:", txid)).is_err());
+        assert!(check_utxo(&format!("{}:-1", txid)).is_err());
+        assert!(check_utxo(&format!("{}:0", &txid[1..])).is_err());
+        assert!(check_utxo("zz:0").is_err());
+    }
+
+    #[test]
+    fn amount_forms() {
+        assert_eq!(amount_to_msat("1000"), Some(1_000_000));
+        assert_eq!(amount_to_msat("1000sat"), Some(1_000_000));
+        assert_eq!(amount_to_msat("1500msat"), Some(1500));
+        assert_eq!(amount_to_msat("0.001btc"), Some(100_000_000));
+        assert_eq!(amount_to_msat("2btc"), Some(200_000_000_000));
+        assert_eq!(amount_to_msat("0.00000000001btc"), Some(1));
+        assert_eq!(amount_to_msat("all"), None);
+        assert_eq!(amount_to_msat("sat"), None);
+        assert_eq!(amount_to_msat("-5sat"), None);
+        assert_eq!(amount_to_msat("1.5sat"), None);
+        assert_eq!(amount_to_msat("99999999999999999999"), None);
+    }
+
+    #[test]
+    fn net_address_forms() {
+        let from_str = |s: &str| serde_json::from_value::<NetAddress>(Value::from(s));
+        assert_eq!(
+            from_str("127.0.0.1:9735").unwrap(),
+            NetAddress::Ipv4 {
+                address: "127.0.0.1".to_owned(),
+                port: 9735
+            }
+        );
+        let v6 = from_str("[2001:db8::1]:9736").unwrap();
+        assert_eq!(
+            v6,
+            NetAddress::Ipv6 {
+                address: "2001:db8::1".to_owned(),
+                port: 9736
+            }
+        );
+        assert_eq!(v6.to_string(), "[2001:db8::1]:9736");
+        assert_eq!(
+            from_str("node.example.com:9735").unwrap(),
+            NetAddress::Dns {
+                address: "node.example.com".to_owned(),
+                port: 9735
+            }
+        );
+        for raw in ["127.0.0.1", "127.0.0.1:port", ":9735", "short.onion:9735"].iter() {
+            let odd = from_str(raw).unwrap();
+            assert_eq!(odd, NetAddress::Raw(raw.to_string()));
+            assert_eq!(serde_json::to_value(&odd).unwrap(), Value::from(*raw));
+            assert!(raw.parse::<NetAddress>().is_err());
+        }
+
+        // Tor, as a string and as an object
+        let onion = "vww6ybal4bd7szmgncyruucpgfkqahzddi37ktceo3ah7ngmcopnpyyd.onion";
+        let tor = from_str(&format!("{}:9735", onion)).unwrap();
+        assert_eq!(
+            tor,
+            NetAddress::Torv3 {
+                address: onion.to_owned(),
+                port: 9735
+            }
+        );
+        let obj = serde_json::json!({"type": "torv3", "address": onion, "port": 9735});
+        assert_eq!(serde_json::to_value(&tor).unwrap(), obj);
+        assert_eq!(serde_json::from_value::<NetAddress>(obj).unwrap(), tor);
+
+        let wss: NetAddress =
+            serde_json::from_str(r#"{"type": "websocket", "address": "127.0.0.1", "port": 9999}"#)
+                .unwrap();
+        assert_eq!(wss.to_string(), "127.0.0.1:9999");
+        assert!(matches!(wss, NetAddress::Wss { port: 9999, .. }));
+        let socket: NetAddress =
+            serde_json::from_str(r#"{"type": "local socket", "socket": "/tmp/sock"}"#).unwrap();
+        assert_eq!(socket.to_string(), "/tmp/sock");
+    }
+
+    #[test]
+    fn feature_bits() {
+        let node: FeatureBits = serde_json::from_str(r#""08a0000a8a5961""#).unwrap();
+        assert!(node.has(0) && !node.has(1));
+        assert!(node.has(23) && !node.has(22));
+        assert!(node.anchors());
+        assert!(node.basic_mpp());
+        assert!(node.zeroconf());
+        assert!(!node.dual_fund());
+        assert!(!node.onion_messages());
+        assert!(!node.has(400));
+        assert_eq!(node.to_string(), "08a0000a8a5961");
+        assert_eq!(serde_json::to_value(&node).unwrap(), "08a0000a8a5961");
+
+        let invoice: FeatureBits = "8000000002aaa2".parse().unwrap();
+        assert!(invoice.has(FeatureBits::VAR_ONION_OPTIN + 1));
+        assert!(invoice.payment_secret());
+        assert!(invoice.keysend());
+        assert!(!invoice.anchors());
+        assert!(!invoice.route_blinding());
+
+        let empty: FeatureBits = "".parse().unwrap();
+        assert!(empty.is_empty() && !empty.has(0));
+        assert!("abc".parse::<FeatureBits>().is_err());
+        assert!("zz".parse::<FeatureBits>().is_err());
+    }
+}
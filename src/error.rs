@@ -18,31 +18,79 @@
 //!
 
 use std::io;
+use std::path::PathBuf;
 use std::{error, fmt};
 
-use strason::{self, Json};
+use serde::de::DeserializeOwned;
+use serde_json::{self, Value};
 
-use Response;
+use crate::responses;
+use crate::Response;
 
 /// A library error
 #[derive(Debug)]
 pub enum Error {
     /// Json error
-    Json(strason::Error),
+    Json(serde_json::Error),
     /// IO Error
     Io(io::Error),
     /// Error response
     Rpc(RpcError),
     /// Response has neither error nor result
     NoErrorOrResult,
-    /// Response to a request did not have the expected nonce
-    NonceMismatch,
+    /// Response to a request did not have the expected id
+    IdMismatch {
+        /// Id of the request, or of all requests in a batch
+        expected: Value,
+        /// Id that was received, or of all responses to a batch
+        got: Value,
+    },
+    /// A response arrived while no request was outstanding
+    UnexpectedReply {
+        /// Id of the response
+        got: Value,
+    },
     /// Response to a request had a jsonrpc field other than "2.0"
     VersionMismatch,
+    /// Socket read or write timed out
+    Timeout,
+    /// A parameter was rejected before sending the request
+    InvalidInput(String),
+    /// A result could not be deserialized into the expected type
+    Deserialization {
+        /// Name of the type that was expected
+        expected_type: &'static str,
+        /// The JSON that was received
+        raw: String,
+        /// The underlying error
+        source: serde_json::Error,
+    },
+    /// There is no RPC socket at the given path
+    SocketNotFound(PathBuf),
+    /// A response was larger than the limit set with `set_max_response_bytes` on the client
+    ResponseTooLarge {
+        /// The limit, in bytes
+        limit: usize,
+    },
+    /// lightningd has no estimate for the requested feerate yet, e.g. because bitcoind is still
+    /// syncing
+    FeerateUnavailable,
+    /// A result lacked a field that was needed to continue, e.g. `created_index` for fetching
+    /// the next page of invoices
+    MissingField(&'static str),
 }
 
-impl From<strason::Error> for Error {
-    fn from(e: strason::Error) -> Error {
+/// Deserialize a received value, keeping the original JSON in the error for diagnostics
+pub(crate) fn from_value<T: DeserializeOwned>(value: &Value) -> Result<T, Error> {
+    T::deserialize(value).map_err(|source| Error::Deserialization {
+        expected_type: std::any::type_name::<T>(),
+        raw: value.to_string(),
+        source,
+    })
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Error {
         Error::Json(e)
     }
 }
@@ -63,8 +111,40 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Error::Json(ref e) => write!(f, "JSON decode error: {}", e),
+            Error::Io(ref e) => write!(f, "IO error response: {}", e),
             Error::Rpc(ref r) => write!(f, "RPC error response: {:?}", r),
-            _ => f.write_str(error::Error::description(self)),
+            Error::NoErrorOrResult => f.write_str("Malformed RPC response"),
+            Error::IdMismatch {
+                ref expected,
+                ref got,
+            } => write!(
+                f,
+                "Id of response did not match id of request: expected {}, got {}",
+                expected, got
+            ),
+            Error::UnexpectedReply { ref got } => {
+                write!(f, "Response with id {} while no request was pending", got)
+            }
+            Error::VersionMismatch => f.write_str("`jsonrpc` field set to non-\"2.0\""),
+            Error::Timeout => f.write_str("Timeout on RPC socket"),
+            Error::InvalidInput(ref s) => write!(f, "Invalid input: {}", s),
+            Error::Deserialization {
+                expected_type,
+                ref raw,
+                ref source,
+            } => write!(
+                f,
+                "Could not deserialize {} from {}: {}",
+                expected_type, raw, source
+            ),
+            Error::SocketNotFound(ref path) => {
+                write!(f, "RPC socket not found at {}", path.display())
+            }
+            Error::ResponseTooLarge { limit } => {
+                write!(f, "Response larger than the limit of {} bytes", limit)
+            }
+            Error::FeerateUnavailable => f.write_str("Feerate estimate not available"),
+            Error::MissingField(field) => write!(f, "Result has no `{}` field", field),
         }
     }
 }
@@ -76,14 +156,23 @@ impl error::Error for Error {
             Error::Io(_) => "IO error response",
             Error::Rpc(_) => "RPC error response",
             Error::NoErrorOrResult => "Malformed RPC response",
-            Error::NonceMismatch => "Nonce of response did not match nonce of request",
+            Error::IdMismatch { .. } => "Id of response did not match id of request",
+            Error::UnexpectedReply { .. } => "Response while no request was pending",
             Error::VersionMismatch => "`jsonrpc` field set to non-\"2.0\"",
+            Error::Timeout => "Timeout on RPC socket",
+            Error::InvalidInput(_) => "Invalid input",
+            Error::Deserialization { .. } => "Could not deserialize result",
+            Error::SocketNotFound(_) => "RPC socket not found",
+            Error::ResponseTooLarge { .. } => "Response too large",
+            Error::FeerateUnavailable => "Feerate estimate not available",
+            Error::MissingField(_) => "Result lacks a needed field",
         }
     }
 
-    fn cause(&self) -> Option<&error::Error> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
             Error::Json(ref e) => Some(e),
+            Error::Deserialization { ref source, .. } => Some(source),
             _ => None,
         }
     }
@@ -134,53 +223,236 @@ pub struct RpcError {
     /// A string describing the error
     pub message: String,
     /// Additional data specific to the error
-    pub data: Option<Json>,
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    /// Deserialize the error data into a structure, `None` if the error has no data.
+    ///
+    /// ```no_run
+    /// # use clightningrpc::error::{Error, PaymentError};
+    /// # let mut rpc = clightningrpc::LightningRPC::new("lightning-rpc");
+    /// if let Err(Error::Rpc(err)) = rpc.waitsendpay("00", None) {
+    ///     if let Some(Ok(pe)) = err.data_as::<PaymentError>() {
+    ///         println!("payment failed: {:?}", pe.failcodename);
+    ///     }
+    /// }
+    /// ```
+    pub fn data_as<T: DeserializeOwned>(&self) -> Option<Result<T, Error>> {
+        self.data.as_ref().map(from_value)
+    }
+
+    /// Whether lightningd does not know the method, e.g. because it is too old or the plugin
+    /// providing it is not loaded
+    pub fn is_method_not_found(&self) -> bool {
+        self.code == -32601
+    }
+
+    /// The kind of error, for the codes that lightningd documents
+    ///
+    /// ```no_run
+    /// # use clightningrpc::error::{Error, RpcErrorKind};
+    /// # let mut rpc = clightningrpc::LightningRPC::new("lightning-rpc");
+    /// match rpc.withdraw("bc1q...", "all", None, None, None) {
+    ///     Err(Error::Rpc(ref e)) if e.code_kind() == RpcErrorKind::InsufficientFunds => {
+    ///         println!("nothing to withdraw")
+    ///     }
+    ///     other => println!("{:?}", other),
+    /// }
+    /// ```
+    pub fn code_kind(&self) -> RpcErrorKind {
+        match self.code {
+            -32600 => RpcErrorKind::InvalidRequest,
+            -32601 => RpcErrorKind::MethodNotFound,
+            -32602 => RpcErrorKind::InvalidParams,
+            -1 => RpcErrorKind::Generic,
+            -3 => RpcErrorKind::PluginError,
+            -4 => RpcErrorKind::PluginTerminated,
+            -5 => RpcErrorKind::ShuttingDown,
+            200 => RpcErrorKind::PayInProgress,
+            201 => RpcErrorKind::PayRhashAlreadyUsed,
+            202 => RpcErrorKind::PayUnparseableOnion,
+            203 => RpcErrorKind::PayDestinationPermFail,
+            204 => RpcErrorKind::PayTryOtherRoute,
+            205 => RpcErrorKind::PayRouteNotFound,
+            206 => RpcErrorKind::PayRouteTooExpensive,
+            207 => RpcErrorKind::PayInvoiceExpired,
+            208 => RpcErrorKind::PayNoSuchPayment,
+            209 => RpcErrorKind::PayUnspecifiedError,
+            210 => RpcErrorKind::PayStoppedRetrying,
+            300 => RpcErrorKind::FundMaxExceeded,
+            301 => RpcErrorKind::InsufficientFunds,
+            302 => RpcErrorKind::OutputIsDust,
+            303 => RpcErrorKind::BroadcastFailed,
+            304 => RpcErrorKind::StillSyncing,
+            305 => RpcErrorKind::PeerNotConnected,
+            306 => RpcErrorKind::UnknownPeer,
+            400 => RpcErrorKind::NoKnownAddress,
+            401 => RpcErrorKind::AllAddressesFailed,
+            900 => RpcErrorKind::InvoiceLabelExists,
+            901 => RpcErrorKind::InvoicePreimageExists,
+            903 => RpcErrorKind::InvoiceExpiredDuringWait,
+            904 => RpcErrorKind::InvoiceWaitTimedOut,
+            905 => RpcErrorKind::InvoiceNotFound,
+            2000 => RpcErrorKind::WaitTimeout,
+            code => RpcErrorKind::Other(code),
+        }
+    }
+
+    /// Return the details of a failed payment attempt, which `sendpay` and `waitsendpay`
+    /// return as error data. `None` if the error has no data or it does not describe a payment
+    /// attempt.
+    pub fn sendpay_failure(&self) -> Option<responses::SendPayItem> {
+        self.data_as().and_then(Result::ok)
+    }
+}
+
+/// Known values of `RpcError::code`, see `RpcError::code_kind`
+///
+/// These follow `common/jsonrpc_errors.h` in the c-lightning source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RpcErrorKind {
+    /// The request is not valid JSON-RPC (-32600)
+    InvalidRequest,
+    /// Unknown command, e.g. from a plugin that is not loaded (-32601)
+    MethodNotFound,
+    /// Missing, unknown or malformed parameters (-32602)
+    InvalidParams,
+    /// Catch-all error without a more specific code (-1)
+    Generic,
+    /// A plugin failed to handle the command (-3)
+    PluginError,
+    /// The plugin handling the command terminated (-4)
+    PluginTerminated,
+    /// lightningd is shutting down (-5)
+    ShuttingDown,
+    /// A payment with this hash is already in progress (200)
+    PayInProgress,
+    /// The payment hash was already paid with a different amount or destination (201)
+    PayRhashAlreadyUsed,
+    /// The onion in the reply could not be parsed (202)
+    PayUnparseableOnion,
+    /// The destination failed the payment permanently (203)
+    PayDestinationPermFail,
+    /// A node along the route failed, another route may succeed (204)
+    PayTryOtherRoute,
+    /// No route to the destination was found (205)
+    PayRouteNotFound,
+    /// The cheapest route exceeds the fee or delay limits (206)
+    PayRouteTooExpensive,
+    /// The invoice expired (207)
+    PayInvoiceExpired,
+    /// No payment with this hash was attempted (208)
+    PayNoSuchPayment,
+    /// Unspecified payment error (209)
+    PayUnspecifiedError,
+    /// `pay` gave up retrying (210)
+    PayStoppedRetrying,
+    /// The amount exceeds the maximum channel size (300)
+    FundMaxExceeded,
+    /// Not enough funds in the wallet, e.g. for `fundchannel` or `withdraw` (301)
+    InsufficientFunds,
+    /// The output would be dust (302)
+    OutputIsDust,
+    /// Broadcasting the transaction failed (303)
+    BroadcastFailed,
+    /// bitcoind is still syncing (304)
+    StillSyncing,
+    /// The peer is not connected (305)
+    PeerNotConnected,
+    /// There is no peer with this id (306)
+    UnknownPeer,
+    /// No address is known for the node to connect to (400)
+    NoKnownAddress,
+    /// Connecting failed on all known addresses (401)
+    AllAddressesFailed,
+    /// An invoice with this label already exists (900)
+    InvoiceLabelExists,
+    /// An invoice with this preimage already exists (901)
+    InvoicePreimageExists,
+    /// The invoice expired while waiting for it (903)
+    InvoiceExpiredDuringWait,
+    /// Waiting for the invoice timed out (904)
+    InvoiceWaitTimedOut,
+    /// There is no invoice with this label (905)
+    InvoiceNotFound,
+    /// A wait command timed out, e.g. `waitblockheight` (2000)
+    WaitTimeout,
+    /// Any other code
+    Other(i32),
+}
+
+/// Error data of failed payments, as returned by `pay`, `sendpay` and `waitsendpay`
+///
+/// Which fields are present depends on the command and the kind of failure.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct PaymentError {
+    /// Id of the payment attempt
+    pub id: Option<u64>,
+    /// Hash of the payment
+    pub payment_hash: Option<String>,
+    /// Status of the payment attempt
+    pub status: Option<String>,
+    /// Index of the node in the route that reported the failure, 0 is ourselves
+    pub erring_index: Option<u64>,
+    /// BOLT #4 failure code
+    pub failcode: Option<u32>,
+    /// Name of the failure code, e.g. `WIRE_TEMPORARY_CHANNEL_FAILURE`
+    pub failcodename: Option<String>,
+    /// Node that reported the failure
+    pub erring_node: Option<String>,
+    /// Channel that the failure is attributed to
+    pub erring_channel: Option<String>,
+    /// Direction of the erring channel
+    pub erring_direction: Option<u8>,
+    /// The raw failure message, hex encoded
+    pub raw_message: Option<String>,
 }
 
 /// Create a standard error responses
-pub fn standard_error(code: StandardError, data: Option<Json>) -> RpcError {
+pub fn standard_error(code: StandardError, data: Option<Value>) -> RpcError {
     match code {
         StandardError::ParseError => RpcError {
             code: -32700,
             message: "Parse error".to_string(),
-            data: data,
+            data,
         },
         StandardError::InvalidRequest => RpcError {
             code: -32600,
             message: "Invalid Request".to_string(),
-            data: data,
+            data,
         },
         StandardError::MethodNotFound => RpcError {
             code: -32601,
             message: "Method not found".to_string(),
-            data: data,
+            data,
         },
         StandardError::InvalidParams => RpcError {
             code: -32602,
             message: "Invalid params".to_string(),
-            data: data,
+            data,
         },
         StandardError::InternalError => RpcError {
             code: -32603,
             message: "Internal error".to_string(),
-            data: data,
+            data,
         },
     }
 }
 
 /// Converts a Rust `Result` to a JSONRPC response object
-pub fn result_to_response(result: Result<Json, RpcError>, id: Json) -> Response {
+pub fn result_to_response(result: Result<Value, RpcError>, id: Value) -> Response {
     match result {
         Ok(data) => Response {
             result: Some(data),
             error: None,
-            id: id,
+            id,
             jsonrpc: Some(String::from("2.0")),
         },
         Err(err) => Response {
             result: None,
             error: Some(err),
-            id: id,
+            id,
             jsonrpc: Some(String::from("2.0")),
         },
     }
@@ -191,14 +463,15 @@ mod tests {
     use super::StandardError::{
         InternalError, InvalidParams, InvalidRequest, MethodNotFound, ParseError,
     };
-    use super::{result_to_response, standard_error};
+    use super::{result_to_response, standard_error, PaymentError, RpcError, RpcErrorKind};
+    use serde_json::Value;
 
     #[test]
     fn test_parse_error() {
         let resp = result_to_response(Err(standard_error(ParseError, None)), From::from(1));
         assert!(resp.result.is_none());
         assert!(resp.error.is_some());
-        assert_eq!(resp.id, From::from(1));
+        assert_eq!(resp.id, Value::from(1));
         assert_eq!(resp.error.unwrap().code, -32700);
     }
 
@@ -207,7 +480,7 @@ mod tests {
         let resp = result_to_response(Err(standard_error(InvalidRequest, None)), From::from(1));
         assert!(resp.result.is_none());
         assert!(resp.error.is_some());
-        assert_eq!(resp.id, From::from(1));
+        assert_eq!(resp.id, Value::from(1));
         assert_eq!(resp.error.unwrap().code, -32600);
     }
 
@@ -216,7 +489,7 @@ mod tests {
         let resp = result_to_response(Err(standard_error(MethodNotFound, None)), From::from(1));
         assert!(resp.result.is_none());
         assert!(resp.error.is_some());
-        assert_eq!(resp.id, From::from(1));
+        assert_eq!(resp.id, Value::from(1));
         assert_eq!(resp.error.unwrap().code, -32601);
     }
 
@@ -225,7 +498,7 @@ mod tests {
         let resp = result_to_response(Err(standard_error(InvalidParams, None)), From::from("123"));
         assert!(resp.result.is_none());
         assert!(resp.error.is_some());
-        assert_eq!(resp.id, From::from("123"));
+        assert_eq!(resp.id, Value::from("123"));
         assert_eq!(resp.error.unwrap().code, -32602);
     }
 
@@ -234,7 +507,121 @@ mod tests {
         let resp = result_to_response(Err(standard_error(InternalError, None)), From::from(-1));
         assert!(resp.result.is_none());
         assert!(resp.error.is_some());
-        assert_eq!(resp.id, From::from(-1));
+        assert_eq!(resp.id, Value::from(-1));
         assert_eq!(resp.error.unwrap().code, -32603);
     }
+
+    #[test]
+    fn test_sendpay_failure() {
+        let data = r#"{
+            "id": 3,
+            "payment_hash": "d6f5e0b3e5b4c1b2e2a1d2b4e3a6a7f1f2a4c3c5c6c7c8c9d1d2d3d4d5d6d7d8",
+            "destination": "02a5b1b1a1c1d1e1f1a2b2c2d2e2f2a3b3c3d3e3f3a4b4c4d4e4f4a5b5c5d5e5f5",
+            "amount_msat": "50000msat",
+            "amount_sent_msat": "50001msat",
+            "created_at": 1595416000,
+            "status": "failed",
+            "erring_index": 1,
+            "failcode": 16399,
+            "failcodename": "WIRE_INCORRECT_OR_UNKNOWN_PAYMENT_DETAILS",
+            "erring_node": "02a5b1b1a1c1d1e1f1a2b2c2d2e2f2a3b3c3d3e3f3a4b4c4d4e4f4a5b5c5d5e5f5",
+            "erring_channel": "103x1x0",
+            "erring_direction": 0
+        }"#;
+        let err = RpcError {
+            code: 204,
+            message: "failed: WIRE_INCORRECT_OR_UNKNOWN_PAYMENT_DETAILS".to_owned(),
+            data: Some(serde_json::from_str(data).unwrap()),
+        };
+        let failure = err.sendpay_failure().unwrap();
+        assert_eq!(failure.status, "failed");
+        assert_eq!(failure.erring_index, Some(1));
+        assert_eq!(failure.failcode, Some(16399));
+        assert_eq!(
+            failure.failcodename.as_ref().map(|s| &s[..]),
+            Some("WIRE_INCORRECT_OR_UNKNOWN_PAYMENT_DETAILS")
+        );
+
+        let no_data = RpcError {
+            code: -1,
+            message: "oops".to_owned(),
+            data: None,
+        };
+        assert!(no_data.sendpay_failure().is_none());
+    }
+
+    #[test]
+    fn test_data_as() {
+        let data = r#"{
+            "erring_index": 2,
+            "failcode": 4103,
+            "failcodename": "WIRE_TEMPORARY_CHANNEL_FAILURE",
+            "erring_node": "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59",
+            "erring_channel": "103x2x1",
+            "erring_direction": 1,
+            "raw_message": "1007008a01023fbbc0e8c0930d4b1d0bd4ae3c4b8d3c3e0e7f2a1b0c1d2e3f4a5b"
+        }"#;
+        let err = RpcError {
+            code: 204,
+            message: "failed: WIRE_TEMPORARY_CHANNEL_FAILURE".to_owned(),
+            data: Some(serde_json::from_str(data).unwrap()),
+        };
+        match err.data_as::<PaymentError>() {
+            Some(Ok(pe)) => {
+                assert_eq!(
+                    pe.failcodename.as_ref().map(|s| &s[..]),
+                    Some("WIRE_TEMPORARY_CHANNEL_FAILURE")
+                );
+                assert_eq!(pe.erring_index, Some(2));
+                assert!(pe.raw_message.is_some());
+                assert!(pe.payment_hash.is_none());
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        // Raw data stays available
+        assert!(err.data.is_some());
+        assert!(err.data_as::<Vec<String>>().unwrap().is_err());
+
+        let no_data = RpcError {
+            code: -1,
+            message: "oops".to_owned(),
+            data: None,
+        };
+        assert!(no_data.data_as::<PaymentError>().is_none());
+    }
+
+    #[test]
+    fn test_is_method_not_found() {
+        assert!(standard_error(MethodNotFound, None).is_method_not_found());
+        assert!(!standard_error(InvalidParams, None).is_method_not_found());
+        let err: RpcError = serde_json::from_str(
+            r#"{"code": -32601, "message": "Unknown command 'listclosedchannels'"}"#,
+        )
+        .unwrap();
+        assert!(err.is_method_not_found());
+    }
+
+    #[test]
+    fn code_kinds() {
+        let kind = |code| {
+            RpcError {
+                code,
+                message: String::new(),
+                data: None,
+            }
+            .code_kind()
+        };
+        assert_eq!(kind(-1), RpcErrorKind::Generic);
+        assert_eq!(kind(-32602), RpcErrorKind::InvalidParams);
+        assert_eq!(kind(205), RpcErrorKind::PayRouteNotFound);
+        assert_eq!(kind(301), RpcErrorKind::InsufficientFunds);
+        assert_eq!(kind(305), RpcErrorKind::PeerNotConnected);
+        assert_eq!(kind(900), RpcErrorKind::InvoiceLabelExists);
+        assert_eq!(kind(2000), RpcErrorKind::WaitTimeout);
+        assert_eq!(kind(1000), RpcErrorKind::Other(1000));
+        assert_eq!(
+            standard_error(MethodNotFound, None).code_kind(),
+            RpcErrorKind::MethodNotFound
+        );
+    }
 }
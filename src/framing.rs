@@ -0,0 +1,630 @@
+// Rust JSON-RPC Library
+// Written by
+//     Wladimir J. van der Laan <laanwj@gmail.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+//! Transport-independent JSON framing
+//!
+//! lightningd writes JSON objects back to back on the socket. This splits the incoming byte
+//! stream into individual messages, and contains the encoding and validation steps shared by
+//! the synchronous and asynchronous clients.
+
+use std::fmt;
+
+use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+use serde::ser::{self, Impossible, Serializer};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::error::Error;
+use crate::{Notification, Request, Response};
+
+/// Incremental splitter of a byte stream into top-level JSON values
+///
+/// This only tracks nesting of objects and arrays, taking string literals and escapes into
+/// account, and leaves actual parsing to the JSON library.
+#[derive(Debug, Default)]
+pub struct JsonFramer {
+    buf: Vec<u8>,
+    /// Number of bytes of `buf` that have been scanned
+    scanned: usize,
+    /// Offset in `buf` where the current value starts, if one has started
+    start: Option<usize>,
+    depth: usize,
+    in_string: bool,
+    escape: bool,
+}
+
+impl JsonFramer {
+    /// Create a new, empty framer
+    pub fn new() -> JsonFramer {
+        Default::default()
+    }
+
+    /// Append received bytes
+    pub fn push(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+
+    /// Number of bytes received that were not returned as a frame yet
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Return the next complete JSON value, if enough data has been received
+    pub fn next_frame(&mut self) -> Option<Vec<u8>> {
+        while self.scanned < self.buf.len() {
+            let ch = self.buf[self.scanned];
+            self.scanned += 1;
+            if self.start.is_none() {
+                if ch.is_ascii_whitespace() {
+                    continue;
+                }
+                self.start = Some(self.scanned - 1);
+            }
+            if self.in_string {
+                if self.escape {
+                    self.escape = false;
+                } else if ch == b'\\' {
+                    self.escape = true;
+                } else if ch == b'"' {
+                    self.in_string = false;
+                }
+                continue;
+            }
+            match ch {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => self.depth += 1,
+                b'}' | b']' => self.depth = self.depth.saturating_sub(1),
+                _ => {}
+            }
+            if self.depth == 0 {
+                let start = self.start.take().unwrap();
+                let frame = self.buf[start..self.scanned].to_vec();
+                self.buf.drain(..self.scanned);
+                self.scanned = 0;
+                return Some(frame);
+            }
+        }
+        None
+    }
+}
+
+/// A request that borrows its parts, serialized the same way as `Request`
+#[derive(Debug, Serialize)]
+pub struct RequestRef<'a> {
+    pub method: &'a str,
+    pub params: &'a Value,
+    pub id: &'a Value,
+    pub jsonrpc: Option<&'a str>,
+}
+
+impl<'a> From<&'a Request> for RequestRef<'a> {
+    fn from(request: &'a Request) -> RequestRef<'a> {
+        RequestRef {
+            method: &request.method,
+            params: &request.params,
+            id: &request.id,
+            jsonrpc: request.jsonrpc.as_deref(),
+        }
+    }
+}
+
+/// Serialize a request for sending into {buf}, replacing what it held, so that its allocation
+/// can be reused from one request to the next
+pub fn encode_request_into(buf: &mut Vec<u8>, request: &RequestRef) -> Result<(), Error> {
+    buf.clear();
+    serde_json::to_writer(&mut *buf, request)?;
+    Ok(())
+}
+
+/// A message read off the socket
+#[derive(Debug)]
+pub enum Incoming {
+    /// A response, or an array of responses to a batch
+    Reply(Value),
+    /// A message that the server sent without being asked
+    Notification(Notification),
+}
+
+fn parse(frame: &[u8]) -> Result<Value, Error> {
+    Ok(serde_json::from_slice(frame)?)
+}
+
+/// Parse a frame, telling notifications apart from replies by the absence of an `id` field
+pub fn demux(frame: &[u8]) -> Result<Incoming, Error> {
+    let json = parse(frame)?;
+    let is_notification = json.as_object().is_some_and(|obj| !obj.contains_key("id"));
+    if is_notification {
+        Ok(Incoming::Notification(serde_json::from_value(json)?))
+    } else {
+        Ok(Incoming::Reply(json))
+    }
+}
+
+/// Check a reply against the {id} of the request it answers
+pub fn reply_to_response(reply: Value, id: &Value) -> Result<Response, Error> {
+    let response: Response = serde_json::from_value(reply)?;
+    if response.jsonrpc.is_some() && response.jsonrpc != Some(From::from("2.0")) {
+        return Err(Error::VersionMismatch);
+    }
+    if response.id != *id {
+        return Err(Error::IdMismatch {
+            expected: id.clone(),
+            got: response.id,
+        });
+    }
+    Ok(response)
+}
+
+/// Log the method and id of an outgoing request
+pub fn log_request(method: &str, id: &Value) {
+    log_debug!("-> {} id={}", method, id);
+}
+
+/// Log the id and outcome of an incoming response
+pub fn log_response(response: &Response) {
+    match response.error {
+        Some(ref e) => log_debug!("<- id={} error {}: {}", response.id, e.code, e.message),
+        None => log_debug!("<- id={} result", response.id),
+    }
+}
+
+/// Serialize a batch of requests for sending, as a JSON array
+pub fn encode_batch(requests: &[Request]) -> Result<Vec<u8>, Error> {
+    Ok(serde_json::to_vec(requests)?)
+}
+
+/// Take a reply to a batch, an array of response objects, and put them in the order of the
+/// requests they answer, matching them up by `id`
+pub fn reply_to_batch(reply: Value, requests: &[Request]) -> Result<Vec<Response>, Error> {
+    let mut responses: Vec<Option<Response>> = serde_json::from_value::<Vec<Response>>(reply)?
+        .into_iter()
+        .map(Some)
+        .collect();
+    let mismatch = |responses: &[Option<Response>]| Error::IdMismatch {
+        expected: requests.iter().map(|r| r.id.clone()).collect(),
+        got: responses.iter().flatten().map(|r| r.id.clone()).collect(),
+    };
+    if responses.len() != requests.len() {
+        return Err(mismatch(&responses));
+    }
+    let mut rv = Vec::with_capacity(requests.len());
+    for request in requests {
+        let response = responses
+            .iter_mut()
+            .find(|r| r.as_ref().is_some_and(|r| r.id == request.id))
+            .and_then(Option::take);
+        let response = match response {
+            Some(response) => response,
+            None => return Err(mismatch(&responses)),
+        };
+        if response.jsonrpc.is_some() && response.jsonrpc != Some(From::from("2.0")) {
+            return Err(Error::VersionMismatch);
+        }
+        rv.push(response);
+    }
+    Ok(rv)
+}
+
+/// Filter out top-level parameters with value None, this is used for handling optional
+/// parameters correctly as c-lightning expects. Non-object (positional) parameters are passed
+/// through unchanged.
+pub fn filter_nones(params: Value) -> Value {
+    match params {
+        Value::Object(obj) => obj.into_iter().filter(|(_, v)| !v.is_null()).collect(),
+        other => other,
+    }
+}
+
+/// Values of the fields of a JSON object, in the order they appear in
+struct FieldValues(Vec<Value>);
+
+impl<'de> Deserialize<'de> for FieldValues {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<FieldValues, D::Error> {
+        struct FieldValuesVisitor;
+
+        impl<'de> Visitor<'de> for FieldValuesVisitor {
+            type Value = FieldValues;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<FieldValues, A::Error> {
+                let mut values = Vec::new();
+                while let Some((_, value)) = map.next_entry::<String, Value>()? {
+                    values.push(value);
+                }
+                Ok(FieldValues(values))
+            }
+        }
+
+        deserializer.deserialize_map(FieldValuesVisitor)
+    }
+}
+
+/// Serializer that only finds out whether a value is a structure
+///
+/// Structures list their fields in declaration order, maps such as `serde_json::Value` objects
+/// or `HashMap`s in an order that has nothing to do with the daemon's parameters.
+struct StructProbe;
+
+/// Error for anything other than a structure, never seen outside of `is_struct`
+fn not_struct<T>() -> Result<T, serde_json::Error> {
+    Err(ser::Error::custom("not a structure"))
+}
+
+impl Serializer for StructProbe {
+    type Ok = bool;
+    type Error = serde_json::Error;
+    type SerializeSeq = Impossible<bool, serde_json::Error>;
+    type SerializeTuple = Impossible<bool, serde_json::Error>;
+    type SerializeTupleStruct = Impossible<bool, serde_json::Error>;
+    type SerializeTupleVariant = Impossible<bool, serde_json::Error>;
+    type SerializeMap = Impossible<bool, serde_json::Error>;
+    type SerializeStruct = StructProbe;
+    type SerializeStructVariant = Impossible<bool, serde_json::Error>;
+
+    fn serialize_bool(self, _: bool) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_i8(self, _: i8) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_i16(self, _: i16) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_i32(self, _: i32) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_i64(self, _: i64) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_u8(self, _: u8) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_u16(self, _: u16) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_u32(self, _: u32) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_u64(self, _: u64) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_f32(self, _: f32) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_f64(self, _: f64) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_char(self, _: char) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_str(self, _: &str) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_bytes(self, _: &[u8]) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_none(self) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<bool, serde_json::Error> {
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_unit_struct(self, _: &'static str) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+    ) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<bool, serde_json::Error> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<bool, serde_json::Error> {
+        Ok(false)
+    }
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, serde_json::Error> {
+        not_struct()
+    }
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, serde_json::Error> {
+        not_struct()
+    }
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, serde_json::Error> {
+        not_struct()
+    }
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, serde_json::Error> {
+        not_struct()
+    }
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, serde_json::Error> {
+        not_struct()
+    }
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, serde_json::Error> {
+        Ok(StructProbe)
+    }
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, serde_json::Error> {
+        not_struct()
+    }
+}
+
+impl ser::SerializeStruct for StructProbe {
+    type Ok = bool;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), serde_json::Error> {
+        Ok(())
+    }
+    fn end(self) -> Result<bool, serde_json::Error> {
+        Ok(true)
+    }
+}
+
+/// Whether {params} serialize as a structure, as opposed to a map or anything else
+fn is_struct<P: Serialize + ?Sized>(params: &P) -> bool {
+    params.serialize(StructProbe).unwrap_or(false)
+}
+
+/// Serialize {params} as positional parameters
+///
+/// A structure becomes an array of its field values in declaration order, with trailing `null`s
+/// (unset optional parameters) left out. Maps, including `serde_json::Value` objects, have no
+/// meaningful order and stay named parameters. Anything else is passed through unchanged.
+pub fn positional_params<P: Serialize>(params: &P) -> Result<Value, Error> {
+    // `Value` sorts object keys, so go through the serialized text which keeps them in order
+    let raw = serde_json::to_vec(params)?;
+    if raw.first() != Some(&b'{') || !is_struct(params) {
+        return Ok(serde_json::from_slice(&raw)?);
+    }
+    let FieldValues(mut values) = serde_json::from_slice(&raw)?;
+    while values.last() == Some(&Value::Null) {
+        values.pop();
+    }
+    Ok(Value::Array(values))
+}
+
+/// Add {extra} to the named parameters in {params}
+///
+/// A key that is already set to something other than `null` is an error, unless {overwrite} is
+/// true, in which case the value from {extra} wins.
+pub fn merge_params(
+    params: Value,
+    extra: Map<String, Value>,
+    overwrite: bool,
+) -> Result<Value, Error> {
+    if extra.is_empty() {
+        return Ok(params);
+    }
+    let mut obj = match params {
+        Value::Object(obj) => obj,
+        Value::Null => Map::new(),
+        _ => {
+            return Err(Error::InvalidInput(
+                "extra parameters require named parameters".to_owned(),
+            ))
+        }
+    };
+    for (key, value) in extra {
+        let taken = obj.get(&key).is_some_and(|v| !v.is_null());
+        if taken && !overwrite {
+            return Err(Error::InvalidInput(format!(
+                "parameter {} is already set",
+                key
+            )));
+        }
+        obj.insert(key, value);
+    }
+    Ok(Value::Object(obj))
+}
+
+/// Build a request with the given id
+pub fn new_request(name: String, params: Value, id: Value) -> Request {
+    Request {
+        method: name,
+        params: filter_nones(params),
+        id,
+        jsonrpc: Some(String::from("2.0")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn filter_nones_keeps_positional() {
+        let named = filter_nones(json!({"a": null, "b": 3}));
+        assert_eq!(named, json!({"b": 3}));
+        let positional = json!(["x", null]);
+        assert_eq!(filter_nones(positional.clone()), positional);
+    }
+
+    #[test]
+    fn merge_params_precedence() {
+        let extra = |v: Value| v.as_object().unwrap().clone();
+        let params = json!({"bolt11": "lnbc1", "label": null});
+        let merged = merge_params(params.clone(), extra(json!({"label": "x"})), false).unwrap();
+        assert_eq!(merged, json!({"bolt11": "lnbc1", "label": "x"}));
+        match merge_params(params.clone(), extra(json!({"bolt11": "lnbc2"})), false) {
+            Err(Error::InvalidInput(msg)) => assert_eq!(msg, "parameter bolt11 is already set"),
+            other => panic!("expected invalid input, got {:?}", other),
+        }
+        let merged = merge_params(params, extra(json!({"bolt11": "lnbc2"})), true).unwrap();
+        assert_eq!(merged["bolt11"], "lnbc2");
+        assert!(merge_params(json!([1]), extra(json!({"a": 1})), true).is_err());
+        assert_eq!(
+            merge_params(json!([1]), Map::new(), false).unwrap(),
+            json!([1])
+        );
+    }
+
+    #[test]
+    fn positional_in_field_order() {
+        #[derive(Serialize)]
+        struct Params {
+            zeta: &'static str,
+            alpha: Option<u32>,
+            beta: Option<bool>,
+            gamma: Option<bool>,
+        }
+        let params = Params {
+            zeta: "z",
+            alpha: None,
+            beta: Some(true),
+            gamma: None,
+        };
+        assert_eq!(
+            positional_params(&params).unwrap(),
+            json!(["z", null, true])
+        );
+        assert_eq!(
+            positional_params(&Some(params)).unwrap(),
+            json!(["z", null, true])
+        );
+        assert_eq!(positional_params(&json!(["a"])).unwrap(), json!(["a"]));
+
+        // Maps have no field order, so they stay named
+        let named = json!({"bolt11": "lnbc1", "amount_msat": 5});
+        assert_eq!(positional_params(&named).unwrap(), named);
+        let mut map = std::collections::HashMap::new();
+        map.insert("bolt11", "lnbc1");
+        assert_eq!(positional_params(&map).unwrap(), json!({"bolt11": "lnbc1"}));
+    }
+
+    #[test]
+    fn framer_splits_messages() {
+        let mut framer = JsonFramer::new();
+        framer.push(b"  {\"a\": \"}{\\\"\", \"b\": [1, {}]}\n\n{\"c\"");
+        assert_eq!(
+            framer.next_frame().unwrap(),
+            b"{\"a\": \"}{\\\"\", \"b\": [1, {}]}".to_vec()
+        );
+        assert_eq!(framer.next_frame(), None);
+        framer.push(b": 1}");
+        assert_eq!(framer.next_frame().unwrap(), b"{\"c\": 1}".to_vec());
+        assert_eq!(framer.next_frame(), None);
+    }
+
+    #[test]
+    fn response_id_must_match() {
+        let request = new_request("a".to_owned(), json!({}), json!("x-1"));
+        assert!(reply_to_response(
+            parse(br#"{"id": "x-1", "result": true}"#).unwrap(),
+            &request.id
+        )
+        .is_ok());
+        match reply_to_response(
+            parse(br#"{"id": "x-0", "result": true}"#).unwrap(),
+            &request.id,
+        ) {
+            Err(Error::IdMismatch { expected, got }) => {
+                assert_eq!(expected, json!("x-1"));
+                assert_eq!(got, json!("x-0"));
+            }
+            other => panic!("expected id mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn batch_out_of_order() {
+        let empty = || json!({});
+        let requests = vec![
+            new_request("a".to_owned(), empty(), json!(1)),
+            new_request("b".to_owned(), empty(), json!(2)),
+        ];
+        let frame = br#"[{"id": 2, "result": "b"}, {"id": 1, "result": "a"}]"#;
+        let responses = reply_to_batch(parse(frame).unwrap(), &requests).unwrap();
+        assert_eq!(responses[0].result::<String>().unwrap(), "a");
+        assert_eq!(responses[1].result::<String>().unwrap(), "b");
+
+        let short = br#"[{"id": 2, "result": "b"}]"#;
+        assert!(reply_to_batch(parse(short).unwrap(), &requests).is_err());
+        let wrong = br#"[{"id": 2, "result": "b"}, {"id": 3, "result": "c"}]"#;
+        assert!(reply_to_batch(parse(wrong).unwrap(), &requests).is_err());
+    }
+
+    #[test]
+    fn demux_by_id() {
+        match demux(br#"{"jsonrpc": "2.0", "method": "log", "params": {"level": "info"}}"#) {
+            Ok(Incoming::Notification(n)) => {
+                assert_eq!(n.method, "log");
+                assert_eq!(n.params["level"], "info");
+            }
+            other => panic!("expected notification, got {:?}", other),
+        }
+        match demux(br#"{"id": 1, "result": {}}"#) {
+            Ok(Incoming::Reply(_)) => {}
+            other => panic!("expected reply, got {:?}", other),
+        }
+        match demux(br#"[{"id": 1, "result": {}}]"#) {
+            Ok(Incoming::Reply(_)) => {}
+            other => panic!("expected reply, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn request_ref_encodes_like_request() {
+        let request = new_request("getroute".to_owned(), json!({"id": "02aa"}), json!(7));
+        let mut buf = b"left over from the previous request".to_vec();
+        encode_request_into(&mut buf, &RequestRef::from(&request)).unwrap();
+        assert_eq!(buf, serde_json::to_vec(&request).unwrap());
+        let unversioned = Request {
+            jsonrpc: None,
+            ..request
+        };
+        encode_request_into(&mut buf, &RequestRef::from(&unversioned)).unwrap();
+        assert_eq!(buf, serde_json::to_vec(&unversioned).unwrap());
+    }
+}
@@ -34,20 +34,44 @@
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
-extern crate strason;
+extern crate serde_json;
+#[cfg(feature = "async")]
+extern crate tokio;
 
+#[macro_use]
+mod macros;
+
+#[cfg(feature = "async")]
+pub mod async_client;
+#[cfg(feature = "async")]
+pub mod async_lightningrpc;
 pub mod client;
 pub mod common;
 pub mod error;
+mod framing;
 pub mod lightningrpc;
+pub mod notifications;
+pub mod pool;
 pub mod requests;
 pub mod responses;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod transport;
+
+// Used by the `params!` macro
+#[doc(hidden)]
+pub mod __private {
+    pub use serde_json::{to_value, Error as JsonError, Map, Value};
+}
 
-use strason::Json;
+use serde_json::Value;
 // Re-export error type
 pub use error::Error;
 // Re-export high-level connection type
 pub use lightningrpc::LightningRPC;
+// Re-export high-level asynchronous connection type
+#[cfg(feature = "async")]
+pub use async_lightningrpc::AsyncLightningRPC;
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 /// A JSONRPC request object
@@ -55,9 +79,9 @@ pub struct Request {
     /// The name of the RPC call
     pub method: String,
     /// Parameters to the RPC call
-    pub params: Json,
+    pub params: Value,
     /// Identifier for this Request, which should appear in the response
-    pub id: Json,
+    pub id: Value,
     /// jsonrpc field, MUST be "2.0"
     pub jsonrpc: Option<String>,
 }
@@ -66,11 +90,22 @@ pub struct Request {
 /// A JSONRPC response object
 pub struct Response {
     /// A result if there is one, or null
-    pub result: Option<Json>,
+    pub result: Option<Value>,
     /// An error if there is one, or null
     pub error: Option<error::RpcError>,
     /// Identifier for this Request, which should match that of the request
-    pub id: Json,
+    pub id: Value,
+    /// jsonrpc field, MUST be "2.0"
+    pub jsonrpc: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+/// A JSONRPC notification, sent by the server without a request and without `id`
+pub struct Notification {
+    /// The name of the notification
+    pub method: String,
+    /// Parameters of the notification
+    pub params: Value,
     /// jsonrpc field, MUST be "2.0"
     pub jsonrpc: Option<String>,
 }
@@ -82,11 +117,22 @@ impl Response {
             return Err(Error::Rpc(e.clone()));
         }
         match self.result {
-            Some(ref res) => res.clone().into_deserialize().map_err(Error::Json),
+            Some(ref res) => error::from_value(res),
             None => Err(Error::NoErrorOrResult),
         }
     }
 
+    /// Borrow the result from a response as it was received, without deserializing it
+    ///
+    /// This is for tools that pass results on as JSON, such as proxies. Fails like `result` if
+    /// the response has an error or no result.
+    pub fn result_raw(&self) -> Result<&Value, Error> {
+        if let Some(ref e) = self.error {
+            return Err(Error::Rpc(e.clone()));
+        }
+        self.result.as_ref().ok_or(Error::NoErrorOrResult)
+    }
+
     /// Extract the result from a response, consuming the response
     pub fn into_result<T: serde::de::DeserializeOwned>(self) -> Result<T, Error> {
         if let Some(e) = self.error {
@@ -94,7 +140,7 @@ impl Response {
         }
 
         match self.result {
-            Some(ref res) => res.clone().into_deserialize().map_err(Error::Json),
+            Some(ref res) => error::from_value(res),
             None => Err(Error::NoErrorOrResult),
         }
     }
@@ -108,6 +154,17 @@ impl Response {
         }
     }
 
+    /// Like `check_error`, but also succeed if the RPC error has one of the {codes}
+    ///
+    /// Useful for idempotent calls where some errors mean the work was already done, such as
+    /// `invoice` failing with `InvoiceLabelExists` (900) when retried.
+    pub fn check_error_except(self, codes: &[i32]) -> Result<(), Error> {
+        match self.error {
+            Some(ref e) if codes.contains(&e.code) => Ok(()),
+            _ => self.check_error(),
+        }
+    }
+
     /// Returns whether or not the `result` field is empty
     pub fn is_none(&self) -> bool {
         self.result.is_none()
@@ -118,24 +175,26 @@ impl Response {
 mod tests {
     use super::error::RpcError;
     use super::{Request, Response};
-    use strason::Json;
+    use serde_json::Value;
 
     #[test]
     fn request_serialize_round_trip() {
         let original = Request {
             method: "test".to_owned(),
-            params: From::from(vec![
-                ("a".to_string(), From::from(())),
-                ("b".to_string(), From::from(false)),
-                ("c".to_string(), From::from(true)),
-                ("d".to_string(), From::from("test2")),
-            ]),
+            params: vec![
+                ("a".to_string(), Value::from(())),
+                ("b".to_string(), Value::from(false)),
+                ("c".to_string(), Value::from(true)),
+                ("d".to_string(), Value::from("test2")),
+            ]
+            .into_iter()
+            .collect(),
             id: From::from("69"),
             jsonrpc: Some(String::from("2.0")),
         };
 
-        let ser = Json::from_serialize(&original).unwrap();
-        let des = ser.into_deserialize().unwrap();
+        let ser = serde_json::to_value(&original).unwrap();
+        let des = serde_json::from_value(ser).unwrap();
 
         assert_eq!(original, des);
     }
@@ -149,7 +208,7 @@ mod tests {
         };
 
         let original = Response {
-            result: Some(From::<Vec<Json>>::from(vec![
+            result: Some(From::<Vec<Value>>::from(vec![
                 From::from(()),
                 From::from(false),
                 From::from(true),
@@ -160,8 +219,8 @@ mod tests {
             jsonrpc: Some(String::from("2.0")),
         };
 
-        let ser = Json::from_serialize(&original).unwrap();
-        let des = ser.into_deserialize().unwrap();
+        let ser = serde_json::to_value(&original).unwrap();
+        let des = serde_json::from_value(ser).unwrap();
 
         assert_eq!(original, des);
     }
@@ -190,7 +249,7 @@ mod tests {
     fn response_extract() {
         let obj = vec!["Mary", "had", "a", "little", "lamb"];
         let response = Response {
-            result: Some(Json::from_serialize(&obj).unwrap()),
+            result: Some(serde_json::to_value(&obj).unwrap()),
             error: None,
             id: From::from(()),
             jsonrpc: Some(String::from("2.0")),
@@ -201,4 +260,131 @@ mod tests {
         assert_eq!(obj, recovered1);
         assert_eq!(obj, recovered2);
     }
+
+    #[test]
+    fn response_result_raw() {
+        let ok = Response {
+            result: Some(serde_json::json!({"id": "02aa", "num_peers": 3})),
+            error: None,
+            id: From::from(1),
+            jsonrpc: Some(String::from("2.0")),
+        };
+        assert_eq!(ok.result_raw().unwrap()["num_peers"], 3);
+
+        let failed = Response {
+            result: None,
+            error: Some(RpcError {
+                code: -32601,
+                message: "Unknown command".to_owned(),
+                data: None,
+            }),
+            id: From::from(2),
+            jsonrpc: Some(String::from("2.0")),
+        };
+        match failed.result_raw() {
+            Err(super::Error::Rpc(e)) => assert_eq!(e.code, -32601),
+            other => panic!("expected rpc error, got {:?}", other),
+        }
+        let both = Response {
+            result: Some(From::from(true)),
+            ..failed
+        };
+        assert!(matches!(both.result_raw(), Err(super::Error::Rpc(_))));
+        let empty = Response {
+            result: None,
+            error: None,
+            id: From::from(3),
+            jsonrpc: None,
+        };
+        assert!(matches!(
+            empty.result_raw(),
+            Err(super::Error::NoErrorOrResult)
+        ));
+    }
+
+    #[test]
+    fn response_check_error_except() {
+        let failed = |code| Response {
+            result: None,
+            error: Some(RpcError {
+                code,
+                message: "Duplicate label".to_owned(),
+                data: None,
+            }),
+            id: From::from(1),
+            jsonrpc: Some(String::from("2.0")),
+        };
+        assert!(failed(900).check_error_except(&[900, 901]).is_ok());
+        match failed(-1).check_error_except(&[900, 901]) {
+            Err(super::Error::Rpc(e)) => assert_eq!(e.code, -1),
+            other => panic!("expected rpc error, got {:?}", other),
+        }
+        assert!(failed(900).check_error_except(&[]).is_err());
+        let ok = Response {
+            result: Some(From::from(true)),
+            error: None,
+            id: From::from(2),
+            jsonrpc: Some(String::from("2.0")),
+        };
+        assert!(ok.check_error_except(&[900]).is_ok());
+    }
+
+    #[test]
+    fn params_macro() {
+        let amount: Option<u64> = None;
+        let exclude = vec!["103x1x0/0".to_owned()];
+        let params = params! {
+            "id" => "02aa",
+            "amount_msat" => amount,
+            "riskfactor" => Some(10),
+            "exclude" => &exclude,
+            "route" => serde_json::json!([{"id": "02bb", "delay": 9}]),
+            String::from("maxfee") => super::common::MSat(1000),
+        }
+        .unwrap();
+        assert_eq!(
+            params,
+            serde_json::json!({
+                "id": "02aa",
+                "riskfactor": 10,
+                "exclude": ["103x1x0/0"],
+                "route": [{"id": "02bb", "delay": 9}],
+                "maxfee": 1000
+            })
+        );
+        assert_eq!(params! {}.unwrap(), serde_json::json!({}));
+        assert_eq!(
+            params! { "a" => None::<bool> }.unwrap(),
+            serde_json::json!({})
+        );
+
+        let mut by_pair = std::collections::HashMap::new();
+        by_pair.insert((1, 2), "not a string key");
+        match params! { "a" => 1, "b" => by_pair } {
+            Err(super::Error::Json(_)) => {}
+            other => panic!("expected json error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn response_extract_mismatch() {
+        let response = Response {
+            result: Some(serde_json::json!({"id": "02aa", "port": "x"})),
+            error: None,
+            id: From::from(1),
+            jsonrpc: Some(String::from("2.0")),
+        };
+        match response.result::<Vec<String>>() {
+            Err(super::Error::Deserialization {
+                expected_type, raw, ..
+            }) => {
+                assert!(expected_type.contains("Vec"));
+                assert_eq!(raw, r#"{"id":"02aa","port":"x"}"#);
+            }
+            other => panic!("expected deserialization error, got {:?}", other),
+        }
+        let err = response.into_result::<u64>().unwrap_err();
+        assert!(err.to_string().contains("u64"));
+        assert!(std::error::Error::source(&err).is_some());
+    }
 }
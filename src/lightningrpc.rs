@@ -1,38 +1,711 @@
 //! High-level interface to c-lightning RPC
-use std::path::Path;
+use std::collections::{BTreeMap, HashSet};
+use std::ffi::OsString;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use strason::Json;
+use serde_json::{Map, Value};
 
-use client;
-use common;
-use error::Error;
-use requests;
-use responses;
+use crate::client;
+use crate::common;
+use crate::error::{Error, RpcError, RpcErrorKind};
+use crate::framing;
+use crate::requests;
+use crate::responses;
+use crate::transport::RpcTransport;
+use crate::{Request, Response};
 
 /// Structure providing a high-level interface to the c-lightning daemon RPC
 pub struct LightningRPC {
     client: client::Client,
+    retry: Option<RetryPolicy>,
+    version: Option<common::Version>,
+    capabilities: Option<Capabilities>,
+    /// `client::Client::connection_count` when {version} and {capabilities} were fetched
+    cached_connection: u64,
+    param_style: ParamStyle,
+    on_complete: Option<Box<OnComplete>>,
 }
 
-/// Optional arguments for pay() request
+/// Hook called after each round trip, see `LightningRPC::set_on_complete`
+type OnComplete = dyn Fn(&str, Duration, bool) + Send + Sync;
+
+/// Path of the RPC socket according to the environment variables given by {var}, see
+/// `LightningRPC::from_env`
+fn env_socket_path<F: Fn(&str) -> Option<OsString>>(var: F) -> PathBuf {
+    if let Some(file) = var("LIGHTNING_RPC_FILE") {
+        return PathBuf::from(file);
+    }
+    let dir = var("LIGHTNING_DIR").map(PathBuf::from).unwrap_or_else(|| {
+        let home = var("HOME").map(PathBuf::from).unwrap_or_default();
+        home.join(".lightning")
+    });
+    let network = match var("LIGHTNING_NETWORK") {
+        Some(network) => common::Network::from(&*network.to_string_lossy()),
+        None => common::Network::Bitcoin,
+    };
+    dir.join(network.subdirectory()).join("lightning-rpc")
+}
+
+/// How parameters are encoded in requests, see `LightningRPC::set_param_style`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParamStyle {
+    /// An object of named parameters, parameters that are not set are left out
+    #[default]
+    Named,
+    /// An array of parameters, in the order of the fields of the request structure. Parameters
+    /// that are not set are passed as `null`, except at the end.
+    Positional,
+}
+
+/// Kind of transaction to estimate the fee of, see `LightningRPC::estimate_fee_for_weight`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeTarget {
+    /// Channel funding transaction
+    Opening,
+    /// Cooperative close transaction
+    MutualClose,
+    /// Commitment transaction broadcast to close a channel unilaterally
+    UnilateralClose,
+    /// Transaction claiming the outputs of a revoked commitment transaction
+    Penalty,
+}
+
+impl FeeTarget {
+    /// Feerate for this target in {rates}, if the estimate is available
+    pub(crate) fn feerate(self, rates: &responses::FeeRatesInner) -> Option<u64> {
+        match self {
+            FeeTarget::Opening => rates.opening,
+            FeeTarget::MutualClose => rates.mutual_close,
+            FeeTarget::UnilateralClose => rates.unilateral_close,
+            FeeTarget::Penalty => rates.penalty,
+        }
+    }
+}
+
+/// Fee in msat of a transaction of {weight} at the feerate for {target} in {rates}
+pub(crate) fn fee_for_weight(
+    rates: &responses::FeeRates,
+    weight: u64,
+    target: FeeTarget,
+) -> Result<u64, Error> {
+    // Satoshi per 1000 weight units is the same as msat per weight unit
+    let perkw = rates
+        .perkw
+        .as_ref()
+        .and_then(|rates| target.feerate(rates))
+        .filter(|&perkw| perkw > 0)
+        .ok_or(Error::FeerateUnavailable)?;
+    Ok(perkw.saturating_mul(weight))
+}
+
+/// Ids of the {channels} for which {predicate} holds, skipping channels whose peer is not in
+/// {peers}, which happens when the peer was forgotten in between
+pub(crate) fn matching_channels<F: Fn(&responses::Peer, &responses::Channel) -> bool>(
+    peers: &[responses::Peer],
+    channels: &[responses::PeerChannel],
+    predicate: F,
+) -> Vec<String> {
+    channels
+        .iter()
+        .filter(
+            |entry| match peers.iter().find(|peer| peer.id == entry.peer_id) {
+                Some(peer) => predicate(peer, &entry.channel),
+                None => false,
+            },
+        )
+        .map(|entry| entry.channel.channel_id.clone())
+        .collect()
+}
+
+/// Channel id and outcome of `close` for each channel closed by
+/// `LightningRPC::close_channels_where`
+pub type CloseResults = Vec<(String, Result<responses::Close, Error>)>;
+
+/// When and how often to retry calls that failed with an RPC error, see
+/// `LightningRPC::with_retry`
+///
+/// Only errors returned by lightningd are retried, and only those for which the predicate set with
+/// `retry_if` holds; by default none are. IO errors and timeouts are never retried, as the daemon
+/// may have acted on the request.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use clightningrpc::lightningrpc::RetryPolicy;
+/// // Retry failed payment attempts up to 5 times, for at most a minute
+/// let policy = RetryPolicy::new(5, Duration::from_millis(500))
+///     .retry_if(|code| code == 205 || code == 206)
+///     .deadline(Duration::from_secs(60));
+/// ```
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    backoff: Duration,
+    max_backoff: Duration,
+    deadline: Option<Duration>,
+    retry_if: Arc<dyn Fn(i32) -> bool + Send + Sync>,
+}
+
+impl RetryPolicy {
+    /// Make at most {max_attempts} attempts (including the first), waiting {backoff} before the
+    /// first retry and doubling the wait for each one after that.
+    pub fn new(max_attempts: u32, backoff: Duration) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            backoff,
+            max_backoff: Duration::from_secs(30),
+            deadline: None,
+            retry_if: Arc::new(|_| false),
+        }
+    }
+
+    /// Retry errors for which {predicate} holds on `RpcError::code`
+    pub fn retry_if<F: Fn(i32) -> bool + Send + Sync + 'static>(
+        mut self,
+        predicate: F,
+    ) -> RetryPolicy {
+        self.retry_if = Arc::new(predicate);
+        self
+    }
+
+    /// Never wait longer than {max_backoff} between attempts (default 30 seconds)
+    pub fn max_backoff(mut self, max_backoff: Duration) -> RetryPolicy {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Do not start a retry that would begin more than {deadline} after the first attempt
+    pub fn deadline(mut self, deadline: Duration) -> RetryPolicy {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// How long to wait before retrying after {error}, `None` if the error should be returned
+    fn delay(&self, error: &RpcError, attempt: u32, elapsed: Duration) -> Option<Duration> {
+        if attempt >= self.max_attempts || !(self.retry_if)(error.code) {
+            return None;
+        }
+        let factor = 1u32.checked_shl(attempt - 1).unwrap_or(u32::MAX);
+        let delay = self
+            .backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |d| d.min(self.max_backoff));
+        match self.deadline {
+            Some(deadline) if elapsed + delay > deadline => None,
+            _ => Some(delay),
+        }
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("backoff", &self.backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("deadline", &self.deadline)
+            .finish()
+    }
+}
+
+/// Commands the daemon provides, as listed by `help`, see `LightningRPC::capabilities`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Capabilities {
+    commands: HashSet<String>,
+}
+
+impl Capabilities {
+    /// Collect the command names from a 'help' response
+    ///
+    /// Older versions of lightningd list the usage with the command, e.g. "connect id [host]
+    /// [port]", so only the first word is taken.
+    pub fn from_help(help: &responses::Help) -> Capabilities {
+        let items = help.help.iter().flatten();
+        Capabilities {
+            commands: items
+                .filter_map(|item| item.command.split_whitespace().next())
+                .map(str::to_owned)
+                .collect(),
+        }
+    }
+
+    /// Whether the daemon has {command}, including commands added by plugins
+    pub fn supports(&self, command: &str) -> bool {
+        self.commands.contains(command)
+    }
+
+    /// All commands the daemon has, in no particular order
+    pub fn commands(&self) -> impl Iterator<Item = &str> {
+        self.commands.iter().map(String::as_str)
+    }
+}
+
+/// Optional arguments for pay() request, see also `PayBuilder`
 #[derive(Debug, Clone, Default)]
 pub struct PayOptions {
-    /// {msatoshi} (if and only if {bolt11} does not have amount)
-    pub msatoshi: Option<i64>,
+    /// {amount_msat} (if and only if {bolt11} does not have amount)
+    pub amount_msat: Option<u64>,
+    /// {label} an internal label for the payment
+    pub label: Option<String>,
+    /// {riskfactor} (default 10) how much to avoid routes that may lock funds for long
+    pub riskfactor: Option<f64>,
+    /// {maxfeepercent} (default 0.5) the maximum acceptable fee as a percentage (e.g. 0.5 => 0.5%)
+    pub maxfeepercent: Option<f64>,
+    /// {retry_for} (default 60) the integer number of seconds before we stop retrying
+    pub retry_for: Option<u64>,
+    /// {maxdelay} the maximum number of blocks we allow the funds to possibly get locked
+    pub maxdelay: Option<u64>,
+    /// {exemptfee} (default 5000 msat) disables the maxfeepercent check for fees below the threshold
+    pub exemptfee: Option<u64>,
+    /// {localinvreqid} the invoice request this pays, for bolt12 invoices we requested
+    pub localinvreqid: Option<String>,
+    /// {exclude} channels (as short channel id with direction, e.g. "103x1x0/1") and nodes to
+    /// route around
+    pub exclude: Option<Vec<String>>,
+    /// {maxfee} the maximum fee in msat, instead of {maxfeepercent} and {exemptfee}
+    pub maxfee: Option<u64>,
     /// {description} (required if {bolt11} uses description hash)
     pub description: Option<String>,
-    /// {riskfactor} (default 1.0)
-    pub riskfactor: Option<f64>,
+}
+
+/// Build the 'pay' request for {bolt11} with {options}
+pub(crate) fn pay_request(bolt11: &str, options: PayOptions) -> requests::Pay {
+    requests::Pay {
+        bolt11: bolt11.to_owned(),
+        amount_msat: options.amount_msat,
+        label: options.label,
+        riskfactor: options.riskfactor,
+        maxfeepercent: options.maxfeepercent,
+        retry_for: options.retry_for,
+        maxdelay: options.maxdelay,
+        exemptfee: options.exemptfee,
+        localinvreqid: options.localinvreqid,
+        exclude: options.exclude,
+        maxfee: options.maxfee,
+        description: options.description,
+    }
+}
+
+/// Optional arguments for keysend() request
+#[derive(Debug, Clone, Default)]
+pub struct KeySendOptions {
+    /// {label} an internal label for the payment
+    pub label: Option<String>,
     /// {maxfeepercent} (default 0.5) the maximum acceptable fee as a percentage (e.g. 0.5 => 0.5%)
     pub maxfeepercent: Option<f64>,
+    /// {retry_for} (default 60) the integer number of seconds before we stop retrying
+    pub retry_for: Option<u64>,
+    /// {maxdelay} the maximum number of blocks we allow the funds to possibly get locked
+    pub maxdelay: Option<u64>,
     /// {exemptfee} (default 5000 msat) disables the maxfeepercent check for fees below the threshold
-    pub exemptfee: Option<i64>,
+    pub exemptfee: Option<u64>,
+    /// {routehints} routes to reach a {destination} without public channels
+    pub routehints: Option<Vec<Vec<requests::HintHop>>>,
+    /// {extratlvs} extra records for the recipient, mapping odd TLV types to hex payloads, e.g.
+    /// 7629169 for podcast boosts
+    pub extratlvs: Option<BTreeMap<u64, String>>,
+}
+
+/// Optional arguments for renepay() request
+#[derive(Debug, Clone, Default)]
+pub struct RenePayOptions {
+    /// {maxfee} (default 5000 msat plus 1%) the maximum fee in msat
+    pub maxfee: Option<u64>,
+    /// {maxdelay} the maximum number of blocks we allow the funds to possibly get locked
+    pub maxdelay: Option<u64>,
     /// {retry_for} (default 60) the integer number of seconds before we stop retrying
-    pub retry_for: Option<i64>,
-    /// {maxdelay} (default 500) the maximum number of blocks we allow the funds to possibly get locked
-    pub maxdelay: Option<i64>,
+    pub retry_for: Option<u64>,
+    /// {description} of the invoice, needed for invoices that only commit to its hash
+    pub description: Option<String>,
+    /// {label} an internal label for the payment
+    pub label: Option<String>,
+}
+
+/// Optional arguments for sendonion() request
+#[derive(Debug, Clone, Default)]
+pub struct SendOnionOptions {
+    /// {label} an internal label for the payment
+    pub label: Option<String>,
+    /// {shared_secrets} as returned by `createonion`, needed to decode errors from the route
+    pub shared_secrets: Option<Vec<String>>,
+    /// {partid} for multi-part payments
+    pub partid: Option<u64>,
+    /// {bolt11} the invoice being paid, stored with the payment
+    pub bolt11: Option<String>,
+    /// {amount_msat} the amount the final hop receives, stored with the payment
+    pub amount_msat: Option<u64>,
+    /// {destination} the final hop, stored with the payment
+    pub destination: Option<String>,
+}
+
+/// Optional arguments for offer() request
+#[derive(Debug, Clone, Default)]
+pub struct OfferOptions {
+    /// {issuer} who is issuing the offer, e.g. a company name
+    pub issuer: Option<String>,
+    /// {label} an internal label for the offer
+    pub label: Option<String>,
+    /// {quantity_max} the maximum quantity that can be requested in one invoice, 0 for any
+    pub quantity_max: Option<u64>,
+    /// {absolute_expiry} time, in seconds since the epoch, after which the offer is invalid
+    pub absolute_expiry: Option<u64>,
+    /// {recurrence} how often the offer can be paid, e.g. "1month"
+    pub recurrence: Option<String>,
+    /// {recurrence_base} time, in seconds since the epoch, at which the first period starts
+    pub recurrence_base: Option<u64>,
+    /// {recurrence_paywindow} when payment for a period is accepted, e.g. "-1day+1day"
+    pub recurrence_paywindow: Option<String>,
+    /// {recurrence_limit} the number of the last period that can be paid
+    pub recurrence_limit: Option<u64>,
+    /// {single_use} whether the offer is disabled after one successful payment
+    pub single_use: Option<bool>,
+}
+
+/// Optional arguments for fetchinvoice() request
+#[derive(Debug, Clone, Default)]
+pub struct FetchInvoiceOptions {
+    /// {quantity} of items, for offers with a `quantity_max`
+    pub quantity: Option<u64>,
+    /// {recurrence_counter} the period to pay, starting at 0, for recurring offers
+    pub recurrence_counter: Option<u64>,
+    /// {recurrence_start} the first period to pay, for offers with a `recurrence_base`
+    pub recurrence_start: Option<u64>,
+    /// {recurrence_label} a label shared by all payments of a recurring offer
+    pub recurrence_label: Option<String>,
+    /// {timeout} (default 60) seconds to wait for the invoice
+    pub timeout: Option<u64>,
+    /// {payer_note} a note to the issuer of the offer
+    pub payer_note: Option<String>,
+}
+
+/// Connection info of peer {id} in {peers}, if it is connected
+pub(crate) fn connected_peer(peers: &responses::ListPeers, id: &str) -> Option<responses::Connect> {
+    peers
+        .peers
+        .iter()
+        .find(|peer| peer.id == id && peer.connected)
+        .map(|peer| responses::Connect {
+            id: peer.id.clone(),
+            features: peer.features.clone(),
+            direction: None,
+            address: None,
+        })
+}
+
+/// Treat 'connect' failing because peer {id} is already connected as success
+pub(crate) fn already_connected(
+    id: &str,
+    result: Result<responses::Connect, Error>,
+) -> Result<responses::Connect, Error> {
+    match result {
+        Err(Error::Rpc(ref e)) if e.message.to_lowercase().contains("already connected") => {
+            Ok(responses::Connect {
+                id: id.to_owned(),
+                features: None,
+                direction: None,
+                address: None,
+            })
+        }
+        other => other,
+    }
+}
+
+/// Treat the connection closing after 'stop' was written, but before its answer, as success
+///
+/// The clients report that, and only that, as `UnexpectedEof`. A failure to write, such as
+/// `BrokenPipe` because the daemon was already gone, means 'stop' was not delivered.
+pub(crate) fn stopped(result: Result<responses::Stop, Error>) -> Result<responses::Stop, Error> {
+    match result {
+        Err(Error::Io(ref e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+            Ok(responses::Stop("Shutdown complete".to_owned()))
+        }
+        other => other,
+    }
+}
+
+/// A batch of calls that is sent in one round trip, see `LightningRPC::batch`
+pub struct Batch<'a> {
+    rpc: &'a mut LightningRPC,
+    requests: Vec<Request>,
+}
+
+impl<'a> Batch<'a> {
+    /// Add a call to the batch.
+    pub fn call<P: Serialize>(&mut self, method: &str, params: P) -> Result<&mut Batch<'a>, Error> {
+        let params = self.rpc.encode_params(&params)?;
+        let request = self.rpc.client.build_request(method.to_string(), params);
+        self.requests.push(request);
+        Ok(self)
+    }
+
+    /// Send all calls, returning their responses in the order they were added. Use
+    /// `Response::into_result` to get at the typed result of each call.
+    pub fn send(self) -> Result<Vec<Response>, Error> {
+        self.rpc.client.send_batch(&self.requests)
+    }
+}
+
+/// Builder for a 'pay' call, see `LightningRPC::pay_builder`
+pub struct PayBuilder<'a> {
+    rpc: &'a mut LightningRPC,
+    bolt11: String,
+    options: PayOptions,
+    extra: Map<String, Value>,
+    overwrite: bool,
+}
+
+impl<'a> PayBuilder<'a> {
+    /// {amount_msat} (if and only if {bolt11} does not have amount)
+    pub fn amount_msat(mut self, amount_msat: u64) -> PayBuilder<'a> {
+        self.options.amount_msat = Some(amount_msat);
+        self
+    }
+
+    /// {label} an internal label for the payment
+    pub fn label(mut self, label: &str) -> PayBuilder<'a> {
+        self.options.label = Some(label.to_owned());
+        self
+    }
+
+    /// {riskfactor} (default 10) how much to avoid routes that may lock funds for long
+    pub fn riskfactor(mut self, riskfactor: f64) -> PayBuilder<'a> {
+        self.options.riskfactor = Some(riskfactor);
+        self
+    }
+
+    /// {maxfeepercent} (default 0.5) the maximum acceptable fee as a percentage
+    pub fn maxfeepercent(mut self, maxfeepercent: f64) -> PayBuilder<'a> {
+        self.options.maxfeepercent = Some(maxfeepercent);
+        self
+    }
+
+    /// {retry_for} (default 60) the number of seconds before we stop retrying
+    pub fn retry_for(mut self, retry_for: u64) -> PayBuilder<'a> {
+        self.options.retry_for = Some(retry_for);
+        self
+    }
+
+    /// {maxdelay} the maximum number of blocks we allow the funds to possibly get locked
+    pub fn maxdelay(mut self, maxdelay: u64) -> PayBuilder<'a> {
+        self.options.maxdelay = Some(maxdelay);
+        self
+    }
+
+    /// {exemptfee} (default 5000 msat) disables the maxfeepercent check for fees below this
+    pub fn exemptfee(mut self, exemptfee: u64) -> PayBuilder<'a> {
+        self.options.exemptfee = Some(exemptfee);
+        self
+    }
+
+    /// {localinvreqid} the invoice request this pays, for bolt12 invoices we requested
+    pub fn localinvreqid(mut self, localinvreqid: &str) -> PayBuilder<'a> {
+        self.options.localinvreqid = Some(localinvreqid.to_owned());
+        self
+    }
+
+    /// {exclude} channels (e.g. "103x1x0/1") and nodes to route around
+    pub fn exclude(mut self, exclude: Vec<String>) -> PayBuilder<'a> {
+        self.options.exclude = Some(exclude);
+        self
+    }
+
+    /// {maxfee} the maximum fee in msat, instead of {maxfeepercent} and {exemptfee}
+    pub fn maxfee(mut self, maxfee: u64) -> PayBuilder<'a> {
+        self.options.maxfee = Some(maxfee);
+        self
+    }
+
+    /// {description} (required if {bolt11} uses description hash)
+    pub fn description(mut self, description: &str) -> PayBuilder<'a> {
+        self.options.description = Some(description.to_owned());
+        self
+    }
+
+    /// {params} to add as is, for parameters not supported here yet, replacing any added before.
+    /// See `LightningRPC::call_with_extra` for the meaning of {overwrite}.
+    pub fn extra_params(mut self, params: Map<String, Value>, overwrite: bool) -> PayBuilder<'a> {
+        self.extra = params;
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Build the request `send` would make, without sending it. The id comes from {id_gen}.
+    pub fn build_request<G: client::IdGenerator + ?Sized>(
+        self,
+        id_gen: &mut G,
+    ) -> Result<Request, Error> {
+        self.rpc.build_request_with(
+            "pay",
+            pay_request(&self.bolt11, self.options),
+            self.extra,
+            self.overwrite,
+            id_gen,
+        )
+    }
+
+    /// Make the payment, this blocks until it succeeds or fails.
+    pub fn send(self) -> Result<responses::Pay, Error> {
+        self.rpc.call_with_extra(
+            "pay",
+            pay_request(&self.bolt11, self.options),
+            self.extra,
+            self.overwrite,
+        )
+    }
+}
+
+/// Builder for an 'invoice' call, see `LightningRPC::invoice_builder`
+pub struct InvoiceBuilder<'a> {
+    rpc: &'a mut LightningRPC,
+    request: requests::Invoice,
+    extra: Map<String, Value>,
+    overwrite: bool,
+}
+
+impl<'a> InvoiceBuilder<'a> {
+    /// {expiry} in seconds (default 1 week)
+    pub fn expiry(mut self, expiry: u64) -> InvoiceBuilder<'a> {
+        self.request.expiry = Some(expiry);
+        self
+    }
+
+    /// {fallbacks} on-chain addresses the payer can use instead
+    pub fn fallbacks(mut self, fallbacks: Vec<String>) -> InvoiceBuilder<'a> {
+        self.request.fallbacks = Some(fallbacks);
+        self
+    }
+
+    /// {preimage} to use instead of a random one, as 64 hex characters
+    pub fn preimage(mut self, preimage: &str) -> InvoiceBuilder<'a> {
+        self.request.preimage = Some(preimage.to_owned());
+        self
+    }
+
+    /// {exposeprivatechannels} whether to include route hints for private channels
+    pub fn exposeprivatechannels(mut self, expose: bool) -> InvoiceBuilder<'a> {
+        self.request.exposeprivatechannels = Some(expose);
+        self
+    }
+
+    /// {cltv} the final CLTV delta of the payment
+    pub fn cltv(mut self, cltv: u32) -> InvoiceBuilder<'a> {
+        self.request.cltv = Some(cltv);
+        self
+    }
+
+    /// {deschashonly} put only the hash of the description in the invoice
+    pub fn deschashonly(mut self, deschashonly: bool) -> InvoiceBuilder<'a> {
+        self.request.deschashonly = Some(deschashonly);
+        self
+    }
+
+    /// {params} to add as is, for parameters not supported here yet, replacing any added before.
+    /// See `LightningRPC::call_with_extra` for the meaning of {overwrite}.
+    pub fn extra_params(
+        mut self,
+        params: Map<String, Value>,
+        overwrite: bool,
+    ) -> InvoiceBuilder<'a> {
+        self.extra = params;
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Build the request `send` would make, without sending it. The id comes from {id_gen}.
+    pub fn build_request<G: client::IdGenerator + ?Sized>(
+        self,
+        id_gen: &mut G,
+    ) -> Result<Request, Error> {
+        self.check()?;
+        self.rpc
+            .build_request_with("invoice", self.request, self.extra, self.overwrite, id_gen)
+    }
+
+    /// Create the invoice.
+    pub fn send(self) -> Result<responses::Invoice, Error> {
+        self.check()?;
+        self.rpc
+            .call_with_extra("invoice", self.request, self.extra, self.overwrite)
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        if let Some(ref preimage) = self.request.preimage {
+            common::check_hex("preimage", preimage, 64)?;
+        }
+        Ok(())
+    }
+}
+
+/// Iterator over all invoices, fetched page by page, see `LightningRPC::invoices_paged`
+pub struct InvoicePages<'a> {
+    rpc: &'a mut LightningRPC,
+    batch_size: u64,
+    /// `created_index` to fetch the next page from, `None` after the last page
+    next_start: Option<u64>,
+    page: std::vec::IntoIter<responses::ListInvoice>,
+    /// Error to return once the current page is exhausted
+    error: Option<Error>,
+}
+
+impl<'a> InvoicePages<'a> {
+    /// Fetch the page starting at {start}
+    fn fetch(&mut self, start: u64) -> Result<Vec<responses::ListInvoice>, Error> {
+        let (limit, created) = (Some(self.batch_size), Some("created"));
+        let page = self
+            .rpc
+            .listinvoices(None, None, None, None, created, Some(start), limit);
+        let invoices = match page {
+            Ok(page) => page.invoices,
+            // Older daemons do not know the pagination parameters
+            Err(Error::Rpc(ref e))
+                if start == 0 && e.code_kind() == RpcErrorKind::InvalidParams =>
+            {
+                self.next_start = None;
+                return Ok(self
+                    .rpc
+                    .listinvoices(None, None, None, None, None, None, None)?
+                    .invoices);
+            }
+            Err(e) => return Err(e),
+        };
+        self.next_start = match invoices.last() {
+            Some(last) if invoices.len() as u64 == self.batch_size => match last.created_index {
+                Some(index) => Some(index + 1),
+                None => {
+                    self.error = Some(Error::MissingField("created_index"));
+                    None
+                }
+            },
+            _ => None,
+        };
+        Ok(invoices)
+    }
+}
+
+impl<'a> Iterator for InvoicePages<'a> {
+    type Item = Result<responses::ListInvoice, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(invoice) = self.page.next() {
+                return Some(Ok(invoice));
+            }
+            if let Some(e) = self.error.take() {
+                return Some(Err(e));
+            }
+            let start = self.next_start.take()?;
+            match self.fetch(start) {
+                Ok(invoices) => self.page = invoices.into_iter(),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
 }
 
 impl LightningRPC {
@@ -41,367 +714,3397 @@ impl LightningRPC {
     /// # Arguments
     ///
     /// * `sockpath` - Path of UNIX socket to connect to, by default lightningd will create a
-    /// socket named `.lightning/lightning-rpc` in the home directory of the user running
-    /// lightningd.
+    ///   socket named `.lightning/lightning-rpc` in the home directory of the user running
+    ///   lightningd.
     pub fn new<P: AsRef<Path>>(sockpath: P) -> LightningRPC {
         LightningRPC {
             client: client::Client::new(sockpath),
+            retry: None,
+            version: None,
+            capabilities: None,
+            cached_connection: 0,
+            param_style: ParamStyle::Named,
+            on_complete: None,
+        }
+    }
+
+    /// Create a new connection to a lightningd RPC socket that is reachable over TCP, for
+    /// example through `socat` or an SSH tunnel.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - Address to connect to, as "host:port"
+    pub fn from_tcp<A: Into<String>>(addr: A) -> LightningRPC {
+        LightningRPC {
+            client: client::Client::from_tcp(addr),
+            retry: None,
+            version: None,
+            capabilities: None,
+            cached_connection: 0,
+            param_style: ParamStyle::Named,
+            on_complete: None,
+        }
+    }
+
+    /// Create a new connection to the socket configured in the environment.
+    ///
+    /// The socket is `$LIGHTNING_RPC_FILE` if set. Otherwise it is `lightning-rpc` in the
+    /// subdirectory for `$LIGHTNING_NETWORK` (by default "bitcoin") of `$LIGHTNING_DIR`, which
+    /// defaults to `~/.lightning`, as lightningd does. Fails with `Error::SocketNotFound` if
+    /// nothing exists at that path.
+    pub fn from_env() -> Result<LightningRPC, Error> {
+        let path = env_socket_path(|name| std::env::var_os(name));
+        if path.exists() {
+            Ok(LightningRPC::new(path))
+        } else {
+            Err(Error::SocketNotFound(path))
+        }
+    }
+
+    /// Create a new connection over a custom transport, see `transport::RpcTransport`.
+    pub fn with_transport<T: RpcTransport + 'static>(transport: T) -> LightningRPC {
+        LightningRPC {
+            client: client::Client::with_transport(transport),
+            retry: None,
+            version: None,
+            capabilities: None,
+            cached_connection: 0,
+            param_style: ParamStyle::Named,
+            on_complete: None,
+        }
+    }
+
+    /// Retry calls that fail with an RPC error according to {policy}.
+    ///
+    /// Each retry re-sends the same request with a fresh id. This applies to all calls made
+    /// through `call` and the typed methods, but not to batches.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> LightningRPC {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Set the timeout for socket operations, `None` (the default) means block forever.
+    /// See `client::Client::set_timeout` for details.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.client.set_timeout(timeout);
+    }
+
+    /// Limit the size of a single response, see `client::Client::set_max_response_bytes`.
+    pub fn set_max_response_bytes(&mut self, limit: usize) {
+        self.client.set_max_response_bytes(limit);
+    }
+
+    /// Choose how parameters are encoded, by name (the default) or by position.
+    ///
+    /// lightningd accepts both. Positional encoding can be used to work around problems with
+    /// named parameters in some versions. It applies to all calls, including batches, and sends
+    /// the fields of a parameter structure in declaration order, so they must match the order
+    /// of the daemon's parameters, including the optional ones in between. Only some of the
+    /// structures in `requests` are tested against that order, so check it for the commands
+    /// used, and for custom structures passed to `call`. Maps and `serde_json::Value` objects
+    /// have no order of their own and are always sent by name.
+    pub fn set_param_style(&mut self, param_style: ParamStyle) {
+        self.param_style = param_style;
+    }
+
+    /// Call {f} after every round trip made by `call` and the typed methods, with the method
+    /// name, the time it took and whether lightningd answered without error.
+    ///
+    /// This is meant for collecting metrics. The time only covers sending the request and
+    /// reading the response, not decoding the result. Each retry is reported separately, batches
+    /// are not reported.
+    pub fn set_on_complete<F: Fn(&str, Duration, bool) + Send + Sync + 'static>(&mut self, f: F) {
+        self.on_complete = Some(Box::new(f));
+    }
+
+    /// Encode {params} in the chosen style
+    fn encode_params<P: Serialize>(&self, params: &P) -> Result<Value, Error> {
+        match self.param_style {
+            ParamStyle::Named => Ok(serde_json::to_value(params)?),
+            ParamStyle::Positional => framing::positional_params(params),
+        }
+    }
+
+    /// Build the request for {method} that `call_with_extra` would send, with an id from
+    /// {id_gen} instead of the client's id generator
+    fn build_request_with<P: Serialize, G: client::IdGenerator + ?Sized>(
+        &self,
+        method: &str,
+        params: P,
+        extra: Map<String, Value>,
+        overwrite: bool,
+        id_gen: &mut G,
+    ) -> Result<Request, Error> {
+        let params = framing::merge_params(self.encode_params(&params)?, extra, overwrite)?;
+        let id = id_gen.next_id(self.client.next_nonce());
+        Ok(framing::new_request(method.to_string(), params, id))
+    }
+
+    /// Enable or disable automatic reconnection, see `client::Client::set_auto_reconnect`.
+    pub fn set_auto_reconnect(&mut self, auto_reconnect: bool) {
+        self.client.set_auto_reconnect(auto_reconnect);
+    }
+
+    /// Path of the socket this connects to, `None` for TCP and custom transports that do not
+    /// use one, see `client::Client::socket_path`.
+    pub fn socket_path(&self) -> Option<&Path> {
+        self.client.socket_path()
+    }
+
+    /// Whether a connection is open, see `client::Client::is_connected`.
+    pub fn is_connected(&self) -> bool {
+        self.client.is_connected()
+    }
+
+    /// Re-open the connection to the daemon, for example after it was restarted.
+    ///
+    /// This forgets the version cached by `version` and the commands cached by `capabilities`,
+    /// as the daemon may have been upgraded. The same happens whenever the connection is opened
+    /// again automatically.
+    pub fn reconnect(&mut self) -> Result<(), Error> {
+        self.client.reconnect()
+    }
+
+    /// Forget the cached {version} and {capabilities} if they were fetched on an earlier
+    /// connection
+    fn check_cache(&mut self) {
+        let connection = self.client.connection_count();
+        if connection != self.cached_connection {
+            self.version = None;
+            self.capabilities = None;
+            self.cached_connection = connection;
+        }
+    }
+
+    /// Version of the daemon, from `getinfo`
+    ///
+    /// The version is only fetched the first time, or after reconnecting, so this is cheap enough
+    /// to check before every call that depends on it.
+    ///
+    /// ```no_run
+    /// # use clightningrpc::common::Version;
+    /// # let mut rpc = clightningrpc::LightningRPC::new("lightning-rpc");
+    /// if rpc.version()? >= Version::new(23, 8, 0) {
+    ///     // use a feature added in v23.08
+    /// }
+    /// # Ok::<(), clightningrpc::Error>(())
+    /// ```
+    pub fn version(&mut self) -> Result<common::Version, Error> {
+        self.check_cache();
+        if let Some(version) = self.version {
+            return Ok(version);
+        }
+        let info = self.getinfo()?;
+        let version = crate::error::from_value(&Value::from(info.version))?;
+        self.check_cache();
+        self.version = Some(version);
+        Ok(version)
+    }
+
+    /// Commands the daemon provides, from `help`
+    ///
+    /// Like `version`, this is only fetched the first time, or after reconnecting. Check it to
+    /// fall back on older daemons, or when a plugin is disabled, rather than handling
+    /// `RpcError::is_method_not_found` on each call:
+    ///
+    /// ```no_run
+    /// # let mut rpc = clightningrpc::LightningRPC::new("lightning-rpc");
+    /// if rpc.capabilities()?.supports("splice_init") {
+    ///     // splice into the channel
+    /// } else {
+    ///     // close it and open a new one
+    /// }
+    /// # Ok::<(), clightningrpc::Error>(())
+    /// ```
+    ///
+    /// `plugin_start`, `plugin_stop` and `plugin_rescan` forget the cached commands, but plugins
+    /// started or stopped by other clients or by lightningd itself, e.g. when one crashes, are
+    /// not noticed. Use `refresh_capabilities` where that matters.
+    pub fn capabilities(&mut self) -> Result<Capabilities, Error> {
+        self.check_cache();
+        if let Some(ref capabilities) = self.capabilities {
+            return Ok(capabilities.clone());
+        }
+        let capabilities = Capabilities::from_help(&self.help(None)?);
+        self.check_cache();
+        self.capabilities = Some(capabilities.clone());
+        Ok(capabilities)
+    }
+
+    /// Fetch the commands the daemon provides again, replacing those cached by `capabilities`
+    pub fn refresh_capabilities(&mut self) -> Result<Capabilities, Error> {
+        self.capabilities = None;
+        self.capabilities()
+    }
+
+    /// Replace the strategy used to generate request ids, see `client::IdGenerator`.
+    pub fn set_id_generator<G: client::IdGenerator + 'static>(&mut self, id_generator: G) {
+        self.client.set_id_generator(id_generator);
+    }
+
+    /// Generic call function for RPC calls.
+    ///
+    /// All of the typed methods below go through this. It can also be used directly to invoke
+    /// commands that have no wrapper (yet), such as ones provided by plugins, with custom request
+    /// and response structures. `params` is usually a structure serializing to a JSON object of
+    /// named parameters; fields that serialize to `null` are left out of the request.
+    pub fn call<P: Serialize, R: DeserializeOwned>(
+        &mut self,
+        method: &str,
+        params: P,
+    ) -> Result<R, Error> {
+        self.call_with_extra(method, params, Map::new(), false)
+    }
+
+    /// Like `call`, but add {extra} to the parameters as is.
+    ///
+    /// This is an escape hatch for optional parameters that lightningd added to a command before
+    /// this crate caught up:
+    ///
+    /// ```no_run
+    /// # use clightningrpc::{requests, responses, LightningRPC};
+    /// # let mut rpc = LightningRPC::new("lightning-rpc");
+    /// let mut extra = serde_json::Map::new();
+    /// extra.insert("new_flag".to_owned(), true.into());
+    /// let info: responses::GetInfo =
+    ///     rpc.call_with_extra("getinfo", requests::GetInfo {}, extra, false)?;
+    /// # Ok::<(), clightningrpc::Error>(())
+    /// ```
+    ///
+    /// Parameters in {params} that are `None` can be given in {extra}. One that is set in both
+    /// is an `Error::InvalidInput` and nothing is sent, unless {overwrite} is true; then the value
+    /// in {extra} takes precedence. {params} must serialize to an object if {extra} is not empty,
+    /// and `ParamStyle::Positional` cannot be combined with extra parameters.
+    pub fn call_with_extra<P: Serialize, R: DeserializeOwned>(
+        &mut self,
+        method: &str,
+        params: P,
+        extra: Map<String, Value>,
+        overwrite: bool,
+    ) -> Result<R, Error> {
+        let params = framing::merge_params(self.encode_params(&params)?, extra, overwrite)?;
+        let params = framing::filter_nones(params);
+        let start = Instant::now();
+        let mut attempt = 1;
+        loop {
+            let sent = Instant::now();
+            let response = self.client.send_call(method, &params);
+            if let Some(ref on_complete) = self.on_complete {
+                let success = matches!(response, Ok(ref res) if res.error.is_none());
+                on_complete(method, sent.elapsed(), success);
+            }
+            let result = response.and_then(|res| res.into_result::<R>());
+            let delay = match (&result, &self.retry) {
+                (Err(Error::Rpc(e)), Some(policy)) => policy.delay(e, attempt, start.elapsed()),
+                _ => None,
+            };
+            match delay {
+                Some(delay) => thread::sleep(delay),
+                None => return result,
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Start a batch of calls that will be sent to the daemon in one go.
+    ///
+    /// ```no_run
+    /// # use clightningrpc::{requests, responses, LightningRPC};
+    /// # let mut rpc = LightningRPC::new("lightning-rpc");
+    /// let mut batch = rpc.batch();
+    /// batch
+    ///     .call("getinfo", requests::GetInfo {})?
+    ///     .call("listfunds", requests::ListFunds { spent: None })?;
+    /// let mut responses = batch.send()?.into_iter();
+    /// let info: responses::GetInfo = responses.next().unwrap().into_result()?;
+    /// let funds: responses::ListFunds = responses.next().unwrap().into_result()?;
+    /// # Ok::<(), clightningrpc::Error>(())
+    /// ```
+    pub fn batch(&mut self) -> Batch<'_> {
+        Batch {
+            rpc: self,
+            requests: Vec::new(),
         }
     }
 
-    /// Generic call function for RPC calls.
-    fn call<T: Serialize, U: DeserializeOwned>(
-        &mut self,
-        method: &str,
-        input: T,
-    ) -> Result<U, Error> {
-        let params = Json::from_serialize(input)?;
-        let request = self.client.build_request(method.to_string(), params);
-        self.client
-            .send_request(&request)
-            .and_then(|res| res.into_result::<U>())
-    }
+    /// Show information about this node.
+    pub fn getinfo(&mut self) -> Result<responses::GetInfo, Error> {
+        self.call("getinfo", requests::GetInfo {})
+    }
+
+    /// Return feerate estimates, either satoshi-per-kw ({style} perkw) or satoshi-per-kb ({style}
+    /// perkb).
+    pub fn feerates(
+        &mut self,
+        style: requests::FeerateStyle,
+    ) -> Result<responses::FeeRates, Error> {
+        self.call("feerates", requests::FeeRates { style })
+    }
+
+    /// Estimate the fee in msat of a transaction of {weight} (in weight units, four times the
+    /// virtual size) for {target}, from the current `feerates` of lightningd.
+    ///
+    /// Fails with `Error::FeerateUnavailable` if lightningd has no estimate for {target}, as is
+    /// the case while bitcoind is starting up.
+    pub fn estimate_fee_for_weight(
+        &mut self,
+        weight: u64,
+        target: FeeTarget,
+    ) -> Result<u64, Error> {
+        let rates = self.feerates(requests::FeerateStyle::PerKw)?;
+        fee_for_weight(&rates, weight, target)
+    }
+
+    /// Show node {id} (or all, if no {id}), in our local network view.
+    ///
+    /// The whole list is returned in one response, which on mainnet is several megabytes;
+    /// lightningd does not paginate it, so pass {id} where possible.
+    pub fn listnodes(&mut self, id: Option<&str>) -> Result<responses::ListNodes, Error> {
+        self.call("listnodes", requests::ListNodes { id })
+    }
+
+    /// Show channel {short_channel_id} (or all known channels, if no {short_channel_id}),
+    /// optionally only those from node {source} or to node {destination}.
+    ///
+    /// Each channel is listed once for every direction that has been announced. As for
+    /// `listnodes`, the full graph is large and not paginated, so filter where possible.
+    pub fn listchannels(
+        &mut self,
+        short_channel_id: Option<&str>,
+        source: Option<&str>,
+        destination: Option<&str>,
+    ) -> Result<responses::ListChannels, Error> {
+        self.call(
+            "listchannels",
+            requests::ListChannels {
+                short_channel_id,
+                source,
+                destination,
+            },
+        )
+    }
+
+    /// List available commands, or give verbose help on one command.
+    pub fn help(&mut self, command: Option<String>) -> Result<responses::Help, Error> {
+        self.call("help", requests::Help { command })
+    }
+
+    /// Show logs, with optional log {level} (info|unusual|debug|io).
+    pub fn getlog(&mut self, level: Option<&str>) -> Result<responses::GetLog, Error> {
+        self.call("getlog", requests::GetLog { level })
+    }
+
+    /// List all configuration options, or with {config}, just that one.
+    ///
+    /// Because of the dynamic nature of the returned object, unlike the other methods, this
+    /// returns a map (from String to Value) instead of a structure, see `responses::ListConfigs`
+    /// for typed accessors of common options.
+    pub fn listconfigs(&mut self, config: Option<&str>) -> Result<responses::ListConfigs, Error> {
+        self.call("listconfigs", requests::ListConfigs { config })
+    }
+
+    /// Show current peers, or only peer {id}. If {level} is set (e.g. "debug"), include their
+    /// logs.
+    pub fn listpeers(
+        &mut self,
+        id: Option<&str>,
+        level: Option<&str>,
+    ) -> Result<responses::ListPeers, Error> {
+        self.call("listpeers", requests::ListPeers { id, level })
+    }
+
+    /// Show the channels with all peers, or only with peer {id}.
+    ///
+    /// lightningd v23.02 and newer list channels here instead of in `listpeers`; older versions
+    /// do not have the command and fail with an `Error::Rpc` for which
+    /// `RpcError::is_method_not_found` is true.
+    pub fn listpeerchannels(
+        &mut self,
+        id: Option<&str>,
+    ) -> Result<responses::ListPeerChannels, Error> {
+        self.call("listpeerchannels", requests::ListPeerChannels { id })
+    }
+
+    /// Show channels that were closed with {id} (or with any peer, if no {id}).
+    ///
+    /// The command is only available on newer versions of lightningd, older ones fail with an
+    /// `Error::Rpc` for which `RpcError::is_method_not_found` is true.
+    pub fn listclosedchannels(
+        &mut self,
+        id: Option<&str>,
+    ) -> Result<responses::ListClosedChannels, Error> {
+        self.call("listclosedchannels", requests::ListClosedChannels { id })
+    }
+
+    /// Show invoice {label} (or all, if no {label}), or the invoice for {invstring},
+    /// {payment_hash} or {offer_id}.
+    ///
+    /// Invoices can be fetched in pages of at most {limit}, starting at {start} in the order of
+    /// {index} ("created" or "updated"), compared with the `created_index` or `updated_index` of
+    /// the invoices. Pagination needs lightningd v23.08 or later, see `invoices_paged` for a
+    /// simpler way to go through all invoices.
+    #[allow(clippy::too_many_arguments)]
+    pub fn listinvoices(
+        &mut self,
+        label: Option<&str>,
+        invstring: Option<&str>,
+        payment_hash: Option<&str>,
+        offer_id: Option<&str>,
+        index: Option<&str>,
+        start: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<responses::ListInvoices, Error> {
+        self.call(
+            "listinvoices",
+            requests::ListInvoices {
+                label,
+                invstring,
+                payment_hash,
+                offer_id,
+                index,
+                start,
+                limit,
+            },
+        )
+    }
+
+    /// Iterate over all invoices, in order of creation, fetching {batch_size} of them at a time.
+    ///
+    /// ```no_run
+    /// # let mut rpc = clightningrpc::LightningRPC::new("lightning-rpc");
+    /// let mut unpaid = 0;
+    /// for invoice in rpc.invoices_paged(100) {
+    ///     if invoice?.status == "unpaid" {
+    ///         unpaid += 1;
+    ///     }
+    /// }
+    /// # Ok::<(), clightningrpc::Error>(())
+    /// ```
+    ///
+    /// A daemon that does not support pagination rejects the parameters, all invoices are then
+    /// fetched in one call instead. The iteration ends after the first error. If a full page
+    /// ends with an invoice without `created_index`, the next page cannot be requested and the
+    /// page is followed by `Error::MissingField`.
+    pub fn invoices_paged(&mut self, batch_size: u64) -> InvoicePages<'_> {
+        InvoicePages {
+            rpc: self,
+            batch_size: batch_size.max(1),
+            next_start: Some(0),
+            page: Vec::new().into_iter(),
+            error: None,
+        }
+    }
+
+    /// Create an invoice for {msatoshi} with {label} and {description} with
+    /// optional {expiry} seconds (default 1 week). Negative values fail with
+    /// `Error::InvalidInput`.
+    ///
+    /// See `invoice_builder` for the other options.
+    pub fn invoice(
+        &mut self,
+        msatoshi: i64,
+        label: String,
+        description: String,
+        expiry: Option<i64>,
+    ) -> Result<responses::Invoice, Error> {
+        let amount_msat = common::non_negative("msatoshi", msatoshi)?;
+        let expiry = expiry
+            .map(|e| common::non_negative("expiry", e))
+            .transpose()?;
+        let builder = self.invoice_builder(amount_msat, &label, &description);
+        match expiry {
+            Some(expiry) => builder.expiry(expiry),
+            None => builder,
+        }
+        .send()
+    }
+
+    /// Start building an invoice for {amount_msat} with {label} and {description}, the optional
+    /// parameters can be set on the returned builder before sending it.
+    ///
+    /// ```no_run
+    /// # let mut rpc = clightningrpc::LightningRPC::new("lightning-rpc");
+    /// let invoice = rpc
+    ///     .invoice_builder(10_000, "order-17", "two coffees")
+    ///     .expiry(3600)
+    ///     .exposeprivatechannels(true)
+    ///     .send()?;
+    /// println!("{}", invoice.bolt11);
+    /// # Ok::<(), clightningrpc::Error>(())
+    /// ```
+    pub fn invoice_builder(
+        &mut self,
+        amount_msat: u64,
+        label: &str,
+        description: &str,
+    ) -> InvoiceBuilder<'_> {
+        InvoiceBuilder {
+            rpc: self,
+            request: requests::Invoice {
+                amount_msat,
+                label: label.to_owned(),
+                description: description.to_owned(),
+                expiry: None,
+                fallbacks: None,
+                preimage: None,
+                exposeprivatechannels: None,
+                cltv: None,
+                deschashonly: None,
+            },
+            extra: Map::new(),
+            overwrite: false,
+        }
+    }
+
+    /// Create and sign an invoice from {bolt11} (as returned by `invoice` with `createonly`, or
+    /// built externally) under {label}, using the known {preimage} as the payment secret.
+    pub fn createinvoice(
+        &mut self,
+        label: &str,
+        bolt11: &str,
+        preimage: &str,
+    ) -> Result<responses::CreateInvoice, Error> {
+        common::check_hex("preimage", preimage, 64)?;
+        self.call(
+            "createinvoice",
+            requests::CreateInvoice {
+                invstring: bolt11,
+                label,
+                preimage,
+            },
+        )
+    }
+
+    /// Sign {invstring}, an unsigned or modified bolt11 invoice, with the node's key.
+    ///
+    /// Unlike `createinvoice` this only returns the signed invoice: it is not stored by the
+    /// node, which will not recognize payments to it unless they are handled elsewhere, e.g. by
+    /// a plugin that knows the preimage.
+    pub fn signinvoice(&mut self, invstring: &str) -> Result<responses::SignInvoice, Error> {
+        self.call("signinvoice", requests::SignInvoice { invstring })
+    }
+
+    /// Delete unpaid invoice {label} with {status}, returning the deleted invoice.
+    ///
+    /// lightningd refuses with an `Error::Rpc` if the invoice's current status is not {status}
+    /// ("paid", "expired" or "unpaid"), so that a concurrently paid invoice is not deleted.
+    pub fn delinvoice(
+        &mut self,
+        label: &str,
+        status: &str,
+    ) -> Result<responses::DelInvoice, Error> {
+        self.call("delinvoice", requests::DelInvoice { label, status })
+    }
+
+    /// Delete all expired invoices that expired as of given {maxexpirytime} (a UNIX epoch time),
+    /// or all expired invoices if not specified.
+    pub fn delexpiredinvoice(
+        &mut self,
+        maxexpirytime: Option<u64>,
+    ) -> Result<responses::DelExpiredInvoice, Error> {
+        self.call(
+            "delexpiredinvoice",
+            requests::DelExpiredInvoice { maxexpirytime },
+        )
+    }
+
+    /// Set up autoclean of expired invoices. Perform cleanup every {cycle_seconds} (default 3600),
+    /// or disable autoclean if 0. Clean up expired invoices that have expired for {expired_by}
+    /// seconds (default 86400).
+    pub fn autocleaninvoice(
+        &mut self,
+        cycle_seconds: Option<u64>,
+        expired_by: Option<u64>,
+    ) -> Result<responses::AutoCleanInvoice, Error> {
+        self.call(
+            "autocleaninvoice",
+            requests::AutoCleanInvoice {
+                cycle_seconds,
+                expired_by,
+            },
+        )
+    }
+
+    /// Wait for the next invoice to be paid, after {lastpay_index}.
+    /// (if supplied)
+    ///
+    /// This blocks until an invoice is paid, which may never happen. Either set a generous
+    /// timeout with `set_timeout` (the call fails with `Error::Timeout` when it expires) or
+    /// issue it from a dedicated `LightningRPC` instance.
+    pub fn waitanyinvoice(
+        &mut self,
+        lastpay_index: Option<u64>,
+    ) -> Result<responses::WaitAnyInvoice, Error> {
+        self.call("waitanyinvoice", requests::WaitAnyInvoice { lastpay_index })
+    }
+
+    /// Wait for an incoming payment matching the invoice with {label}.
+    ///
+    /// The same caveat about blocking as for `waitanyinvoice` applies.
+    pub fn waitinvoice(&mut self, label: &str) -> Result<responses::WaitInvoice, Error> {
+        self.call("waitinvoice", requests::WaitInvoice { label })
+    }
+
+    /// Wait until the blockchain has reached {blockheight}, or at most {timeout} seconds
+    /// (default 60).
+    ///
+    /// Returns immediately if the height was already reached. When the timeout fires first the
+    /// daemon answers with an `Error::Rpc` (code 2000). Like `waitanyinvoice` this blocks the
+    /// connection in the meantime; a socket timeout set with `set_timeout` that is shorter than
+    /// {timeout} makes the call fail with `Error::Timeout` instead, so keep it longer.
+    pub fn waitblockheight(
+        &mut self,
+        blockheight: u32,
+        timeout: Option<u32>,
+    ) -> Result<responses::WaitBlockHeight, Error> {
+        self.call(
+            "waitblockheight",
+            requests::WaitBlockHeight {
+                blockheight,
+                timeout,
+            },
+        )
+    }
+
+    /// Wait until the {indexname} index of {subsystem} reaches {nextvalue}, returning the
+    /// current indexes.
+    ///
+    /// Returns immediately if the index is already there. Waiting for one past the last value
+    /// seen, and then fetching what changed, follows a subsystem without polling:
+    ///
+    /// ```no_run
+    /// # use clightningrpc::common::{WaitIndex, WaitSubsystem};
+    /// # let mut rpc = clightningrpc::LightningRPC::new("lightning-rpc");
+    /// let mut next = 1;
+    /// loop {
+    ///     let changed = rpc.wait(WaitSubsystem::Invoices, WaitIndex::Updated, next)?;
+    ///     let updated = rpc.listinvoices(None, None, None, None, Some("updated"), Some(next), None)?;
+    ///     for invoice in updated.invoices {
+    ///         println!("{} is now {:?}", invoice.label, invoice.status);
+    ///     }
+    ///     next = changed.updated.unwrap_or(next) + 1;
+    /// }
+    /// # Ok::<(), clightningrpc::Error>(())
+    /// ```
+    ///
+    /// As with `waitblockheight`, the connection is blocked in the meantime and a socket
+    /// timeout set with `set_timeout` makes the call fail with `Error::Timeout`. Needs lightningd
+    /// v23.08 or later.
+    pub fn wait(
+        &mut self,
+        subsystem: common::WaitSubsystem,
+        indexname: common::WaitIndex,
+        nextvalue: u64,
+    ) -> Result<responses::Wait, Error> {
+        self.call(
+            "wait",
+            requests::Wait {
+                subsystem,
+                indexname,
+                nextvalue,
+            },
+        )
+    }
+
+    /// Pay {bolt11} with default options, see `pay_builder` to set them.
+    pub fn pay(&mut self, bolt11: &str) -> Result<responses::Pay, Error> {
+        self.pay_builder(bolt11).send()
+    }
+
+    /// Start building a payment of {bolt11}, only sent by `PayBuilder::send`.
+    ///
+    /// ```no_run
+    /// # let mut rpc = clightningrpc::LightningRPC::new("lightning-rpc");
+    /// let paid = rpc
+    ///     .pay_builder("lnbc1...")
+    ///     .label("order 42")
+    ///     .maxfee(10_000)
+    ///     .retry_for(120)
+    ///     .send()?;
+    /// println!("paid in {} parts", paid.parts);
+    /// # Ok::<(), clightningrpc::Error>(())
+    /// ```
+    pub fn pay_builder(&mut self, bolt11: &str) -> PayBuilder<'_> {
+        PayBuilder {
+            rpc: self,
+            bolt11: bolt11.to_owned(),
+            options: PayOptions::default(),
+            extra: Map::new(),
+            overwrite: false,
+        }
+    }
+
+    /// Send a spontaneous payment of {amount_msat} to {destination}, without an invoice.
+    ///
+    /// The payment is made by the daemon's `keysend` command, which takes care of constructing
+    /// the onion with the preimage in it.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - Node id of the recipient, which must support keysend.
+    /// * `amount_msat` - Amount to send, in millisatoshi.
+    /// * `options` - Options for this payment. Use `Default::default()` to not pass any options.
+    ///
+    /// Fails with `Error::InvalidInput` if {extratlvs} has an even type or a payload that is not
+    /// hex.
+    pub fn keysend(
+        &mut self,
+        destination: &str,
+        amount_msat: u64,
+        options: KeySendOptions,
+    ) -> Result<responses::KeySend, Error> {
+        if let Some(ref tlvs) = options.extratlvs {
+            common::check_extratlvs(tlvs)?;
+        }
+        self.call(
+            "keysend",
+            requests::KeySend {
+                destination,
+                amount_msat,
+                label: options.label,
+                maxfeepercent: options.maxfeepercent,
+                retry_for: options.retry_for,
+                maxdelay: options.maxdelay,
+                exemptfee: options.exemptfee,
+                routehints: options.routehints,
+                extratlvs: options.extratlvs,
+            },
+        )
+    }
+
+    /// Pay {invstring}, a bolt11 or bolt12 invoice, using the `renepay` plugin's min-cost-flow
+    /// pathfinding; {amount_msat} is needed if and only if the invoice has no amount.
+    ///
+    /// `renepay` is missing on older daemons (and when the plugin is disabled), in which case
+    /// this fails with an `Error::Rpc` for which `RpcError::is_method_not_found` is true:
+    ///
+    /// ```no_run
+    /// # let mut rpc = clightningrpc::LightningRPC::new("lightning-rpc");
+    /// let paid = match rpc.renepay("lnbc1...", None, Default::default()) {
+    ///     Err(clightningrpc::Error::Rpc(ref e)) if e.is_method_not_found() => rpc.pay("lnbc1..."),
+    ///     other => other,
+    /// }?;
+    /// println!("paid {}", paid.amount_sent_msat);
+    /// # Ok::<(), clightningrpc::Error>(())
+    /// ```
+    pub fn renepay(
+        &mut self,
+        invstring: &str,
+        amount_msat: Option<u64>,
+        options: RenePayOptions,
+    ) -> Result<responses::Pay, Error> {
+        self.call(
+            "renepay",
+            requests::RenePay {
+                invstring,
+                amount_msat,
+                maxfee: options.maxfee,
+                maxdelay: options.maxdelay,
+                retry_for: options.retry_for,
+                description: options.description,
+                label: options.label,
+            },
+        )
+    }
+
+    /// Show the status of `renepay` payments, of {invstring} only if given.
+    pub fn renepaystatus(
+        &mut self,
+        invstring: Option<&str>,
+    ) -> Result<responses::RenePayStatus, Error> {
+        self.call("renepaystatus", requests::RenePayStatus { invstring })
+    }
+
+    /// Create a BOLT12 offer to be paid {amount} ("any", msat, or a currency amount such as
+    /// "5USD") for {description}.
+    ///
+    /// Offers are experimental and need lightningd to run with `--experimental-offers`, without
+    /// it this fails with an `Error::Rpc` for which `RpcError::is_method_not_found` is true.
+    /// Creating an offer that already exists returns it with `created` false.
+    pub fn offer(
+        &mut self,
+        amount: &str,
+        description: &str,
+        options: OfferOptions,
+    ) -> Result<responses::Offer, Error> {
+        self.call(
+            "offer",
+            requests::Offer {
+                amount,
+                description,
+                issuer: options.issuer,
+                label: options.label,
+                quantity_max: options.quantity_max,
+                absolute_expiry: options.absolute_expiry,
+                recurrence: options.recurrence,
+                recurrence_base: options.recurrence_base,
+                recurrence_paywindow: options.recurrence_paywindow,
+                recurrence_limit: options.recurrence_limit,
+                single_use: options.single_use,
+            },
+        )
+    }
+
+    /// Request an invoice for BOLT12 {offer} from its issuer, for {amount_msat} if the offer
+    /// does not specify an amount.
+    ///
+    /// The invoice can be paid with `pay`. As with `offer`, this needs `--experimental-offers`.
+    pub fn fetchinvoice(
+        &mut self,
+        offer: &str,
+        amount_msat: Option<u64>,
+        options: FetchInvoiceOptions,
+    ) -> Result<responses::FetchInvoice, Error> {
+        self.call(
+            "fetchinvoice",
+            requests::FetchInvoice {
+                offer,
+                amount_msat,
+                quantity: options.quantity,
+                recurrence_counter: options.recurrence_counter,
+                recurrence_start: options.recurrence_start,
+                recurrence_label: options.recurrence_label,
+                timeout: options.timeout,
+                payer_note: options.payer_note,
+            },
+        )
+    }
+
+    /// Send along {route} in return for preimage of {payment_hash}, with optional {description}.
+    pub fn sendpay(
+        &mut self,
+        route: Vec<common::RouteItem>,
+        payment_hash: String,
+        description: Option<String>,
+        msatoshi: Option<i64>,
+    ) -> Result<responses::SendPay, Error> {
+        self.call(
+            "sendpay",
+            requests::SendPay {
+                route,
+                payment_hash,
+                description,
+                msatoshi,
+            },
+        )
+    }
+
+    /// Wait for payment attempt on {payment_hash} to succeed or fail, but only up to {timeout} seconds.
+    ///
+    /// A failed attempt is returned as `Error::Rpc`, use `RpcError::sendpay_failure` on it to get
+    /// at the details of the failure.
+    pub fn waitsendpay(
+        &mut self,
+        payment_hash: &str,
+        timeout: Option<u64>,
+    ) -> Result<responses::WaitSendPay, Error> {
+        self.call(
+            "waitsendpay",
+            requests::WaitSendPay {
+                payment_hash,
+                timeout,
+            },
+        )
+    }
+
+    /// Show outgoing payment attempts, optionally limited to {bolt11} or {payment_hash}.
+    pub fn listsendpays(
+        &mut self,
+        bolt11: Option<&str>,
+        payment_hash: Option<&str>,
+    ) -> Result<responses::ListSendPays, Error> {
+        self.call(
+            "listsendpays",
+            requests::ListSendPays {
+                bolt11,
+                payment_hash,
+            },
+        )
+    }
+
+    /// Delete the record of the payment attempt on {payment_hash} with {status} from
+    /// 'listsendpays', returning the deleted attempts.
+    ///
+    /// {status} must be the attempt's current status, "complete" or "failed", otherwise lightningd
+    /// refuses and the refusal is returned as `Error::Rpc`. Pending attempts cannot be deleted.
+    /// Without {partid} and {groupid} all parts of the payment are deleted, with both only the
+    /// part {partid} of attempt {groupid} of a multi-part payment.
+    pub fn delpay(
+        &mut self,
+        payment_hash: &str,
+        status: &str,
+        partid: Option<u64>,
+        groupid: Option<u64>,
+    ) -> Result<responses::DelPay, Error> {
+        self.call(
+            "delpay",
+            requests::DelPay {
+                payment_hash,
+                status,
+                partid,
+                groupid,
+            },
+        )
+    }
+
+    /// Show outgoing payments, with all attempts for the same payment combined, optionally
+    /// limited to {bolt11} or {payment_hash}, and to those with {status}.
+    pub fn listpays(
+        &mut self,
+        bolt11: Option<&str>,
+        payment_hash: Option<&str>,
+        status: Option<&str>,
+    ) -> Result<responses::ListPays, Error> {
+        self.call(
+            "listpays",
+            requests::ListPays {
+                bolt11,
+                payment_hash,
+                status,
+            },
+        )
+    }
+
+    /// Show outgoing payments.
+    ///
+    /// This was renamed to `listsendpays` in c-lightning 0.7, and is kept for older daemons.
+    pub fn listpayments(
+        &mut self,
+        bolt11: Option<String>,
+        payment_hash: Option<String>,
+    ) -> Result<responses::ListPayments, Error> {
+        self.call(
+            "listpayments",
+            requests::ListPayments {
+                bolt11,
+                payment_hash,
+            },
+        )
+    }
+
+    /// Decode {bolt11}, using {description} if necessary.
+    pub fn decodepay(
+        &mut self,
+        bolt11: &str,
+        description: Option<&str>,
+    ) -> Result<responses::DecodePay, Error> {
+        self.call(
+            "decodepay",
+            requests::DecodePay {
+                bolt11,
+                description,
+            },
+        )
+    }
+
+    /// Decode {string}, which may be a bolt11 invoice, a bolt12 offer, invoice request or
+    /// invoice, or a rune.
+    pub fn decode(&mut self, string: &str) -> Result<responses::Decode, Error> {
+        self.call("decode", requests::Decode { string })
+    }
+
+    /// Show route to {id} for {amount_msat}, using {riskfactor} and optional {cltv} (default 9).
+    /// If specified search from {fromid} otherwise use this node as source. Randomize the route
+    /// with up to {fuzzpercent} (0.0 -> 100.0, default 5.0). The channels in {exclude}, given as
+    /// "scid/direction", are not used, and the route is at most {maxhops} long.
+    #[allow(clippy::too_many_arguments)]
+    pub fn getroute(
+        &mut self,
+        id: &str,
+        amount_msat: u64,
+        riskfactor: f64,
+        cltv: Option<u64>,
+        fromid: Option<&str>,
+        fuzzpercent: Option<f64>,
+        exclude: &[String],
+        maxhops: Option<u64>,
+    ) -> Result<responses::GetRoute, Error> {
+        self.call(
+            "getroute",
+            requests::GetRoute {
+                id,
+                amount_msat,
+                riskfactor,
+                cltv,
+                fromid,
+                fuzzpercent,
+                exclude: if exclude.is_empty() {
+                    None
+                } else {
+                    Some(exclude.to_vec())
+                },
+                maxhops,
+            },
+        )
+    }
+
+    /// Create an onion for {hops}, bound to {assocdata} (usually the payment hash), optionally
+    /// using {session_key} instead of a random one.
+    ///
+    /// This is a low-level call: the caller is responsible for encoding each hop's payload
+    /// correctly, lightningd only wraps them.
+    pub fn createonion(
+        &mut self,
+        hops: &[requests::OnionHop],
+        assocdata: &str,
+        session_key: Option<&str>,
+    ) -> Result<responses::CreateOnion, Error> {
+        self.call(
+            "createonion",
+            requests::CreateOnion {
+                hops: hops.to_vec(),
+                assocdata,
+                session_key,
+            },
+        )
+    }
+
+    /// Send {onion} to {first_hop} in return for the preimage of {payment_hash}.
+    ///
+    /// As with `createonion`, nothing checks that the onion matches the payment; use `waitsendpay`
+    /// to learn the outcome.
+    pub fn sendonion(
+        &mut self,
+        onion: &str,
+        first_hop: requests::FirstHop,
+        payment_hash: &str,
+        options: SendOnionOptions,
+    ) -> Result<responses::SendOnion, Error> {
+        self.call(
+            "sendonion",
+            requests::SendOnion {
+                onion,
+                first_hop,
+                payment_hash,
+                label: options.label,
+                shared_secrets: options.shared_secrets,
+                partid: options.partid,
+                bolt11: options.bolt11,
+                amount_msat: options.amount_msat,
+                destination: options.destination,
+            },
+        )
+    }
+
+    /// Connect to {id} at {host} and optional {port} (default 9735). If {host} is not
+    /// given, {id} can also be of the form id@host or id@host:port.
+    pub fn connect(
+        &mut self,
+        id: &str,
+        host: Option<&str>,
+        port: Option<u16>,
+    ) -> Result<responses::Connect, Error> {
+        if port.is_some() && host.is_none() {
+            return Err(Error::InvalidInput("port requires host".to_owned()));
+        }
+        self.call("connect", requests::Connect { id, host, port })
+    }
+
+    /// Connect to {id} unless it is connected already, at {addr} ("host" or "host:port") if
+    /// given, like `connect` otherwise.
+    ///
+    /// If the peer was connected already, only `id` and `features` of the result are set. A
+    /// `connect` failing because the peer connected in the meantime is treated the same way,
+    /// with only `id` set.
+    pub fn ensure_connected(
+        &mut self,
+        id: &str,
+        addr: Option<&str>,
+    ) -> Result<responses::Connect, Error> {
+        common::check_pubkey("id", id)?;
+        if let Some(connect) = connected_peer(&self.listpeers(Some(id), None)?, id) {
+            return Ok(connect);
+        }
+        let target = match addr {
+            Some(addr) => format!("{}@{}", id, addr),
+            None => id.to_owned(),
+        };
+        already_connected(id, self.connect(&target, None, None))
+    }
+
+    /// Disconnect from peer with {id}. Unless {force} is set, this fails if there is an active
+    /// channel with the peer.
+    pub fn disconnect(&mut self, id: &str, force: bool) -> Result<responses::Disconnect, Error> {
+        self.call("disconnect", requests::Disconnect { id, force })
+    }
+
+    /// Fund channel with {id} using {amount} (e.g. "100000sat" or "all"), at optional
+    /// {feerate}, spending outputs with at least {minconf} confirmations (default 1). The
+    /// channel is announced to the network unless {announce} is false. {push_msat} is given to
+    /// the peer right away, and on a mutual close our funds go to {close_to} if the peer
+    /// supports it.
+    ///
+    /// To spend specific outputs, pass a `requests::FundChannel` with `utxos` to `call`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn fundchannel(
+        &mut self,
+        id: &str,
+        amount: &str,
+        feerate: Option<&str>,
+        announce: Option<bool>,
+        minconf: Option<u32>,
+        push_msat: Option<u64>,
+        close_to: Option<&str>,
+    ) -> Result<responses::FundChannel, Error> {
+        if let (Some(push), Some(total)) = (push_msat, common::amount_to_msat(amount)) {
+            if push > total {
+                return Err(Error::InvalidInput(
+                    "push_msat must not exceed amount".to_owned(),
+                ));
+            }
+        }
+        self.call(
+            "fundchannel",
+            requests::FundChannel {
+                id,
+                amount,
+                feerate,
+                announce,
+                minconf,
+                utxos: None,
+                push_msat,
+                close_to,
+            },
+        )
+    }
+
+    /// Fund channels to all {destinations} with a single transaction, at optional {feerate},
+    /// spending outputs with at least {minconf} confirmations (default 1).
+    ///
+    /// By default the whole operation fails if any destination fails. With {minchannels}, the
+    /// destinations that fail (to connect, or because the peer rejects the channel) are dropped
+    /// and listed in `failed`, and the others are funded as long as at least {minchannels}
+    /// remain.
+    ///
+    /// To spend specific outputs, pass a `requests::MultiFundChannel` with `utxos` to `call`.
+    pub fn multifundchannel(
+        &mut self,
+        destinations: &[requests::MultiFundDest],
+        feerate: Option<&str>,
+        minconf: Option<u32>,
+        minchannels: Option<u32>,
+    ) -> Result<responses::MultiFundChannel, Error> {
+        for dest in destinations {
+            if let (Some(push), Some(total)) =
+                (dest.push_msat, common::amount_to_msat(&dest.amount))
+            {
+                if push > total {
+                    return Err(Error::InvalidInput(format!(
+                        "push_msat must not exceed amount for {}",
+                        dest.id
+                    )));
+                }
+            }
+        }
+        self.call(
+            "multifundchannel",
+            requests::MultiFundChannel {
+                destinations: destinations.to_vec(),
+                feerate,
+                minconf,
+                utxos: None,
+                minchannels,
+            },
+        )
+    }
+
+    /// Start opening a channel with {id} for {amount} satoshis, to be funded by a transaction
+    /// built outside of the node.
+    ///
+    /// This is the first step of a stateful sequence on the daemon:
+    ///
+    /// 1. `fundchannel_start` returns the `funding_address` to pay to.
+    /// 2. The caller builds (but does not broadcast) a transaction paying exactly {amount} to
+    ///    that address.
+    /// 3. `fundchannel_complete` with the txid and output index of that transaction. Only when
+    ///    this returns with `commitments_secured` is it safe to broadcast the transaction.
+    ///
+    /// `fundchannel_cancel` aborts the sequence at any point before broadcasting.
+    pub fn fundchannel_start(
+        &mut self,
+        id: &str,
+        amount: u64,
+        feerate: Option<&str>,
+        announce: Option<bool>,
+        close_to: Option<&str>,
+        push_msat: Option<u64>,
+    ) -> Result<responses::FundChannelStart, Error> {
+        self.call(
+            "fundchannel_start",
+            requests::FundChannelStart {
+                id,
+                amount,
+                feerate,
+                announce,
+                close_to,
+                push_msat,
+            },
+        )
+    }
+
+    /// Complete a channel open with {id} started with `fundchannel_start`, given the {txid} and
+    /// {txout} of the (not yet broadcast) funding transaction.
+    pub fn fundchannel_complete(
+        &mut self,
+        id: &str,
+        txid: &str,
+        txout: u32,
+    ) -> Result<responses::FundChannelComplete, Error> {
+        self.call(
+            "fundchannel_complete",
+            requests::FundChannelComplete { id, txid, txout },
+        )
+    }
+
+    /// Cancel a channel open with {id} started with `fundchannel_start`. This must be done
+    /// before the funding transaction is broadcast.
+    pub fn fundchannel_cancel(&mut self, id: &str) -> Result<responses::FundChannelCancel, Error> {
+        self.call("fundchannel_cancel", requests::FundChannelCancel { id })
+    }
+
+    /// Start splicing {relative_amount} satoshis into the channel with {channel_id}, or out of it
+    /// if negative, at optional {feerate_per_kw}.
+    ///
+    /// A splice takes three steps, each passing the PSBT returned by the previous one on:
+    /// `splice_init` returns a PSBT to which the funding inputs (or, for a splice-out, the
+    /// destination outputs) are added, either up front through {initialpsbt} or afterwards.
+    /// `splice_update` is then called with the latest PSBT until it reports
+    /// `commitments_secured`, and finally the PSBT is signed (e.g. with `signpsbt`) and given to
+    /// `splice_signed`, which broadcasts the splice transaction. The node must run with
+    /// `--experimental-splicing`.
+    pub fn splice_init(
+        &mut self,
+        channel_id: &str,
+        relative_amount: i64,
+        initialpsbt: Option<&str>,
+        feerate_per_kw: Option<u32>,
+    ) -> Result<responses::SpliceInit, Error> {
+        common::check_hex("channel_id", channel_id, 64)?;
+        if let Some(psbt) = initialpsbt {
+            common::check_base64("initialpsbt", psbt)?;
+        }
+        self.call(
+            "splice_init",
+            requests::SpliceInit {
+                channel_id,
+                relative_amount,
+                initialpsbt,
+                feerate_per_kw,
+            },
+        )
+    }
+
+    /// Exchange the splice {psbt} for the channel with {channel_id} with the peer, returning the
+    /// PSBT with the peer's changes. Repeat with the returned PSBT until `commitments_secured`.
+    pub fn splice_update(
+        &mut self,
+        channel_id: &str,
+        psbt: &str,
+    ) -> Result<responses::SpliceUpdate, Error> {
+        common::check_hex("channel_id", channel_id, 64)?;
+        common::check_base64("psbt", psbt)?;
+        self.call("splice_update", requests::SpliceUpdate { channel_id, psbt })
+    }
+
+    /// Finish the splice of the channel with {channel_id} with the signed {psbt} from the last
+    /// `splice_update`, and broadcast the splice transaction.
+    pub fn splice_signed(
+        &mut self,
+        channel_id: &str,
+        psbt: &str,
+    ) -> Result<responses::SpliceSigned, Error> {
+        common::check_hex("channel_id", channel_id, 64)?;
+        common::check_base64("psbt", psbt)?;
+        self.call("splice_signed", requests::SpliceSigned { channel_id, psbt })
+    }
+
+    /// Close the channel with {id}, which is a peer id, channel id or short channel id.
+    ///
+    /// A mutual close is attempted first, after {unilateraltimeout} seconds (default 172800)
+    /// the channel is closed unilaterally, or never if it is 0. Our funds go to {destination}
+    /// if given, otherwise to the internal wallet. {fee_negotiation_step} sets how fast we move
+    /// towards the peer's fee proposal, e.g. "50%" (default) or "1" satoshi.
+    pub fn close(
+        &mut self,
+        id: &str,
+        unilateraltimeout: Option<u32>,
+        destination: Option<&str>,
+        fee_negotiation_step: Option<&str>,
+    ) -> Result<responses::Close, Error> {
+        self.call(
+            "close",
+            requests::Close {
+                id,
+                unilateraltimeout,
+                destination,
+                fee_negotiation_step,
+            },
+        )
+    }
+
+    /// Close every channel for which {predicate} holds, given the peer as listed by `listpeers`
+    /// and the channel as listed by `listpeerchannels`, with `close` and {unilateraltimeout}.
+    ///
+    /// Returns the channel id and the outcome of `close` for each matching channel. A failed
+    /// close is reported in its entry and does not stop the others; only a failure of
+    /// `listpeerchannels` or `listpeers` itself is returned as an error. That includes daemons
+    /// older than v23.02, which do not have `listpeerchannels`.
+    ///
+    /// ```no_run
+    /// # use clightningrpc::LightningRPC;
+    /// # let mut rpc = LightningRPC::new("lightning-rpc");
+    /// // Close channels to peers that are offline, forcing it after a minute
+    /// let results = rpc.close_channels_where(
+    ///     |peer, channel| !peer.connected && channel.state.is_active(),
+    ///     Some(60),
+    /// )?;
+    /// # Ok::<(), clightningrpc::Error>(())
+    /// ```
+    pub fn close_channels_where<F: Fn(&responses::Peer, &responses::Channel) -> bool>(
+        &mut self,
+        predicate: F,
+        unilateraltimeout: Option<u32>,
+    ) -> Result<CloseResults, Error> {
+        let channels = self.listpeerchannels(None)?.channels;
+        let peers = self.listpeers(None, None)?.peers;
+        let channels = matching_channels(&peers, &channels, predicate);
+        let mut results = Vec::with_capacity(channels.len());
+        for channel_id in channels {
+            let result = self.close(&channel_id, unilateraltimeout, None, None);
+            results.push((channel_id, result));
+        }
+        Ok(results)
+    }
+
+    /// Update the fees and HTLC limits of the channel(s) {id}, which is a peer id, channel id,
+    /// short channel id or "all". The base fee {feebase} is in msat and the proportional fee
+    /// {feeppm} in millionths, {htlcmin} and {htlcmax} are in msat. Values that are not given stay
+    /// as they are. The old values keep being accepted for {enforcedelay} seconds (default 600).
+    #[allow(clippy::too_many_arguments)]
+    pub fn setchannel(
+        &mut self,
+        id: &str,
+        feebase: Option<u64>,
+        feeppm: Option<u32>,
+        htlcmin: Option<u64>,
+        htlcmax: Option<u64>,
+        enforcedelay: Option<u32>,
+    ) -> Result<responses::SetChannel, Error> {
+        self.call(
+            "setchannel",
+            requests::SetChannel {
+                id,
+                feebase,
+                feeppm,
+                htlcmin,
+                htlcmax,
+                enforcedelay,
+            },
+        )
+    }
+
+    /// Send {id} a ping of length {len} (default 128) asking for {pongbytes} (default 128).
+    ///
+    /// {len} can be at most 65529, so that the ping fits in a message, and {pongbytes} at most
+    /// 65531, as peers do not answer requests for more.
+    pub fn ping(
+        &mut self,
+        id: &str,
+        len: Option<u16>,
+        pongbytes: Option<u16>,
+    ) -> Result<responses::Ping, Error> {
+        let len = len.unwrap_or(128);
+        let pongbytes = pongbytes.unwrap_or(128);
+        common::check_max("len", len, 65529)?;
+        common::check_max("pongbytes", pongbytes, 65531)?;
+        self.call("ping", requests::Ping { id, len, pongbytes })
+    }
+
+    /// List all forwarded payments and their information.
+    pub fn listforwards(&mut self) -> Result<responses::ListForwards, Error> {
+        self.call("listforwards", requests::ListForwards {})
+    }
+
+    /// List the HTLCs of all channels, or of the channel {id} (a channel id or short channel
+    /// id) only, both in flight and those kept from the past.
+    ///
+    /// HTLCs of closed channels are forgotten after some time.
+    pub fn listhtlcs(&mut self, id: Option<&str>) -> Result<responses::ListHtlcs, Error> {
+        self.call("listhtlcs", requests::ListHtlcs { id })
+    }
+
+    /// Show available funds from the internal wallet, including spent outputs if {spent}.
+    pub fn listfunds(&mut self, spent: Option<bool>) -> Result<responses::ListFunds, Error> {
+        self.call("listfunds", requests::ListFunds { spent })
+    }
+
+    /// Sum up the funds of the wallet and the channels from one `listfunds` call.
+    pub fn balance(&mut self) -> Result<responses::Balance, Error> {
+        Ok(self.listfunds(None)?.balance())
+    }
+
+    /// Show the transactions tracked by the internal wallet.
+    pub fn listtransactions(&mut self) -> Result<responses::ListTransactions, Error> {
+        self.call("listtransactions", requests::ListTransactions {})
+    }
+
+    /// Send {satoshi} (e.g. "10000sat", "1btc" or "all") to {destination} address via a Bitcoin
+    /// transaction, at optional {feerate}, spending outputs with at least {minconf}
+    /// confirmations or exactly the {utxos} given as "txid:vout".
+    pub fn withdraw(
+        &mut self,
+        destination: &str,
+        satoshi: &str,
+        feerate: Option<&str>,
+        minconf: Option<u32>,
+        utxos: Option<&[String]>,
+    ) -> Result<responses::Withdraw, Error> {
+        for utxo in utxos.unwrap_or_default() {
+            common::check_utxo(utxo)?;
+        }
+        self.call(
+            "withdraw",
+            requests::Withdraw {
+                destination,
+                satoshi,
+                feerate,
+                minconf,
+                utxos: utxos.map(<[String]>::to_vec),
+            },
+        )
+    }
+
+    /// Create a transaction paying {outputs}, a list of (address, amount) pairs where the amount
+    /// is e.g. "10000sat" or "all", at optional {feerate}, spending outputs with at least
+    /// {minconf} confirmations or exactly the {utxos} given as "txid:vout".
+    ///
+    /// The transaction is not broadcast, its inputs stay reserved until it is sent with `txsend`
+    /// or released with `txdiscard` (or lightningd restarts).
+    pub fn txprepare(
+        &mut self,
+        outputs: &[(String, String)],
+        feerate: Option<&str>,
+        minconf: Option<u32>,
+        utxos: Option<&[String]>,
+    ) -> Result<responses::TxPrepare, Error> {
+        for utxo in utxos.unwrap_or_default() {
+            common::check_utxo(utxo)?;
+        }
+        self.call(
+            "txprepare",
+            requests::TxPrepare {
+                outputs: outputs
+                    .iter()
+                    .map(|(address, amount)| {
+                        std::iter::once((address.clone(), amount.clone())).collect()
+                    })
+                    .collect(),
+                feerate,
+                minconf,
+                utxos: utxos.map(<[String]>::to_vec),
+            },
+        )
+    }
+
+    /// Sign and broadcast the transaction {txid} prepared with `txprepare`.
+    pub fn txsend(&mut self, txid: &str) -> Result<responses::TxSend, Error> {
+        self.call("txsend", requests::TxSend { txid })
+    }
+
+    /// Abandon the transaction {txid} prepared with `txprepare`, releasing its inputs.
+    pub fn txdiscard(&mut self, txid: &str) -> Result<responses::TxDiscard, Error> {
+        self.call("txdiscard", requests::TxDiscard { txid })
+    }
+
+    /// Select wallet inputs worth at least {satoshi} (or "all") plus the fees at {feerate} for a
+    /// transaction whose outputs and non-wallet inputs weigh {startweight}, and return them as a
+    /// PSBT without outputs.
+    ///
+    /// Inputs need {minconf} confirmations (1 by default) and are reserved for {reserve} blocks
+    /// (72 by default, 0 to not reserve). With {excess_as_change} the leftover amount is added as
+    /// a change output instead of being reported in `excess_msat`. This is the first step of
+    /// building a transaction, such as a dual-funded channel open, outside of lightningd: add the
+    /// outputs to the returned PSBT, then pass it to `signpsbt` and finally `sendpsbt`. Use
+    /// `unreserveinputs` to release the inputs if the transaction is abandoned.
+    ///
+    /// To set `locktime` or `min_witness_weight`, pass a `requests::FundPsbt` to `call`.
+    pub fn fundpsbt(
+        &mut self,
+        satoshi: &str,
+        feerate: &str,
+        startweight: u32,
+        minconf: Option<u32>,
+        reserve: Option<u32>,
+        excess_as_change: Option<bool>,
+    ) -> Result<responses::FundPsbt, Error> {
+        self.call(
+            "fundpsbt",
+            requests::FundPsbt {
+                satoshi,
+                feerate,
+                startweight,
+                minconf,
+                reserve,
+                locktime: None,
+                min_witness_weight: None,
+                excess_as_change,
+            },
+        )
+    }
+
+    /// Like `fundpsbt`, but spend exactly the {utxos} given as "txid:vout" instead of selecting
+    /// inputs from the wallet.
+    ///
+    /// To set `reservedok`, `locktime` or `min_witness_weight`, pass a `requests::UtxoPsbt` to
+    /// `call`.
+    pub fn utxopsbt(
+        &mut self,
+        satoshi: &str,
+        feerate: &str,
+        startweight: u32,
+        utxos: &[String],
+        reserve: Option<u32>,
+        excess_as_change: Option<bool>,
+    ) -> Result<responses::UtxoPsbt, Error> {
+        for utxo in utxos {
+            common::check_utxo(utxo)?;
+        }
+        self.call(
+            "utxopsbt",
+            requests::UtxoPsbt {
+                satoshi,
+                feerate,
+                startweight,
+                utxos: utxos.to_vec(),
+                reserve,
+                reservedok: None,
+                locktime: None,
+                min_witness_weight: None,
+                excess_as_change,
+            },
+        )
+    }
+
+    /// Sign the inputs of {psbt} that belong to our wallet, or only those at the indexes in
+    /// {signonly}.
+    pub fn signpsbt(
+        &mut self,
+        psbt: &str,
+        signonly: Option<&[u32]>,
+    ) -> Result<responses::SignPsbt, Error> {
+        common::check_base64("psbt", psbt)?;
+        self.call("signpsbt", requests::SignPsbt { psbt, signonly })
+    }
+
+    /// Finalize the fully signed {psbt} and broadcast the transaction.
+    pub fn sendpsbt(&mut self, psbt: &str) -> Result<responses::SendPsbt, Error> {
+        common::check_base64("psbt", psbt)?;
+        self.call("sendpsbt", requests::SendPsbt { psbt })
+    }
+
+    /// Convert {psbt} to PSBT {version} 0 or 2, for wallets that only handle one of them.
+    ///
+    /// lightningd produces version 2 PSBTs, while many wallets still expect version 0.
+    pub fn setpsbtversion(
+        &mut self,
+        psbt: &str,
+        version: u8,
+    ) -> Result<responses::SetPsbtVersion, Error> {
+        if version != 0 && version != 2 {
+            return Err(Error::InvalidInput(format!(
+                "psbt version must be 0 or 2, not {}",
+                version
+            )));
+        }
+        common::check_base64("psbt", psbt)?;
+        self.call("setpsbtversion", requests::SetPsbtVersion { psbt, version })
+    }
+
+    /// Reserve the wallet inputs of {psbt} so automatic coin selection skips them.
+    ///
+    /// The reservation lasts {reserve} blocks (72 by default) past the current height, or past
+    /// the existing reservation when an input is already reserved; with {exclusive} set to
+    /// false already reserved inputs are extended instead of rejected.
+    pub fn reserveinputs(
+        &mut self,
+        psbt: &str,
+        exclusive: Option<bool>,
+        reserve: Option<u32>,
+    ) -> Result<responses::ReserveInputs, Error> {
+        common::check_base64("psbt", psbt)?;
+        self.call(
+            "reserveinputs",
+            requests::ReserveInputs {
+                psbt,
+                exclusive,
+                reserve,
+            },
+        )
+    }
+
+    /// Shorten the reservation on the inputs of {psbt} by {reserve} blocks (72 by default),
+    /// releasing them once it reaches the current height.
+    pub fn unreserveinputs(
+        &mut self,
+        psbt: &str,
+        reserve: Option<u32>,
+    ) -> Result<responses::UnreserveInputs, Error> {
+        common::check_base64("psbt", psbt)?;
+        self.call(
+            "unreserveinputs",
+            requests::UnreserveInputs { psbt, reserve },
+        )
+    }
+
+    /// Get a new address of {addresstype} to fund a channel, both kinds if it is `All`.
+    pub fn newaddr(
+        &mut self,
+        addresstype: requests::AddressType,
+    ) -> Result<responses::NewAddr, Error> {
+        self.call("newaddr", requests::NewAddr { addresstype })
+    }
+
+    /// Create a digital signature of {message} using this node's secret key.
+    pub fn signmessage(&mut self, message: &str) -> Result<responses::SignMessage, Error> {
+        self.call("signmessage", requests::SignMessage { message })
+    }
+
+    /// Verify a digital signature {zbase} of {message} signed with {pubkey}.
+    ///
+    /// If {pubkey} is given, `verified` tells whether the signature was made by that key. If it
+    /// is omitted, the key is recovered from the signature and returned in `pubkey`, and
+    /// `verified` only tells whether it belongs to a node known to us from gossip; an
+    /// unverified result does not mean the signature itself is invalid.
+    pub fn checkmessage(
+        &mut self,
+        message: &str,
+        zbase: &str,
+        pubkey: Option<&str>,
+    ) -> Result<responses::CheckMessage, Error> {
+        self.call(
+            "checkmessage",
+            requests::CheckMessage {
+                message,
+                zbase,
+                pubkey,
+            },
+        )
+    }
+
+    /// Compute the ECDH shared secret of this node's secret key and {point}, a compressed
+    /// public key in hex.
+    ///
+    /// The secret is the SHA256 of the compressed point resulting from the multiplication, as
+    /// used for onion encryption and the noise handshake.
+    pub fn getsharedsecret(&mut self, point: &str) -> Result<responses::GetSharedSecret, Error> {
+        common::check_pubkey("point", point)?;
+        self.call("getsharedsecret", requests::GetSharedSecret { point })
+    }
+
+    /// List the plugins that lightningd runs.
+    pub fn plugin_list(&mut self) -> Result<responses::Plugins, Error> {
+        self.plugin(requests::PluginSubcommand::List, None)
+    }
+
+    /// Start the plugin at {path}, relative to the plugin directory if it is not absolute.
+    ///
+    /// On success this returns the updated list of plugins, including the new one.
+    pub fn plugin_start(&mut self, path: &str) -> Result<responses::Plugins, Error> {
+        self.plugin(requests::PluginSubcommand::Start, Some(path))
+    }
+
+    /// Stop the plugin {name}, the file name or path of a dynamic plugin.
+    pub fn plugin_stop(&mut self, name: &str) -> Result<responses::PluginStop, Error> {
+        self.plugin(requests::PluginSubcommand::Stop, Some(name))
+    }
+
+    /// Start the plugins in the plugin directories that are not running yet, returning the updated
+    /// list of plugins.
+    pub fn plugin_rescan(&mut self) -> Result<responses::Plugins, Error> {
+        self.plugin(requests::PluginSubcommand::Rescan, None)
+    }
+
+    fn plugin<R: DeserializeOwned>(
+        &mut self,
+        subcommand: requests::PluginSubcommand,
+        plugin: Option<&str>,
+    ) -> Result<R, Error> {
+        if subcommand != requests::PluginSubcommand::List {
+            // The plugins may add or remove commands
+            self.capabilities = None;
+        }
+        self.call("plugin", requests::Plugin { subcommand, plugin })
+    }
+
+    /// Check that {params} are valid for {command_to_check}, without running it.
+    ///
+    /// {params} are given as they would be to the command itself, named or positional, and are
+    /// sent that way whatever the `ParamStyle`. Invalid parameters are reported as an
+    /// `Error::Rpc`, usually with code -32602 (see `RpcError::code_kind`). Only the parameters
+    /// are checked, so for example a `pay` that passes may still fail for lack of a route.
+    pub fn check(
+        &mut self,
+        command_to_check: &str,
+        params: Value,
+    ) -> Result<responses::Check, Error> {
+        let named = match params {
+            Value::Array(mut params) => {
+                params.insert(0, Value::from(command_to_check));
+                return self.call("check", Value::Array(params));
+            }
+            params => {
+                let mut extra = Map::new();
+                extra.insert("command_to_check".to_owned(), command_to_check.into());
+                match framing::merge_params(params, extra, false)? {
+                    Value::Object(named) => named,
+                    _ => unreachable!("extra parameters are merged into an object"),
+                }
+            }
+        };
+        // Extra parameters are always sent by name, with `ParamStyle::Positional` the keys of
+        // {params} would otherwise be taken in sorted order
+        self.call_with_extra("check", Value::Null, named, false)
+    }
+
+    /// Store a value, given as {string} or {hex}, under {key} in the node's datastore.
+    ///
+    /// Keys are paths, such as `["myplugin", "config"]`, and a key cannot hold a value and have
+    /// children at the same time. {mode} says what to do if the key already exists, by default
+    /// this fails. With {generation} the update only happens if the entry was not modified since
+    /// it had that generation.
+    pub fn datastore(
+        &mut self,
+        key: &[String],
+        string: Option<&str>,
+        hex: Option<&str>,
+        mode: Option<requests::DataStoreMode>,
+        generation: Option<u64>,
+    ) -> Result<responses::DataStore, Error> {
+        if string.is_some() && hex.is_some() {
+            return Err(Error::InvalidInput(
+                "datastore takes either string or hex, not both".to_owned(),
+            ));
+        }
+        if let Some(hex) = hex {
+            common::check_hex("hex", hex, hex.len())?;
+        }
+        self.call(
+            "datastore",
+            requests::DataStore {
+                key: key.to_vec(),
+                string,
+                hex,
+                mode,
+                generation,
+            },
+        )
+    }
+
+    /// Delete {key} from the datastore, only if it still has {generation} if given.
+    pub fn deldatastore(
+        &mut self,
+        key: &[String],
+        generation: Option<u64>,
+    ) -> Result<responses::DelDataStore, Error> {
+        self.call(
+            "deldatastore",
+            requests::DelDataStore {
+                key: key.to_vec(),
+                generation,
+            },
+        )
+    }
+
+    /// List the datastore entries directly under {key}, or the top level entries if no {key}.
+    pub fn listdatastore(
+        &mut self,
+        key: Option<&[String]>,
+    ) -> Result<responses::ListDataStore, Error> {
+        self.call(
+            "listdatastore",
+            requests::ListDataStore {
+                key: key.map(<[String]>::to_vec),
+            },
+        )
+    }
+
+    /// Run the read-only SQL {query} over the node's state, e.g.
+    /// `"SELECT id, connected FROM peers"`.
+    ///
+    /// Tables mirror the list commands, so one query can combine what would take several of
+    /// them. The result does not name the columns; use `listsqlschemas` to find them and their
+    /// types. This needs the `sql` plugin, without it this fails with an `Error::Rpc` for which
+    /// `RpcError::is_method_not_found` is true.
+    pub fn sql(&mut self, query: &str) -> Result<responses::Sql, Error> {
+        self.call("sql", requests::Sql { query })
+    }
+
+    /// Describe the tables (of {table} only, if given) that `sql` can query.
+    pub fn listsqlschemas(
+        &mut self,
+        table: Option<&str>,
+    ) -> Result<responses::ListSqlSchemas, Error> {
+        self.call("listsqlschemas", requests::ListSqlSchemas { table })
+    }
+
+    /// List the balance of every account tracked by the bookkeeper plugin: the wallet, each
+    /// channel and funds sent to external addresses.
+    ///
+    /// The `bkpr-*` commands are provided by the bookkeeper plugin, which lightningd starts by
+    /// default; they fail with `RpcErrorKind::MethodNotFound` if it is disabled.
+    pub fn bkpr_listbalances(&mut self) -> Result<responses::BkprListBalances, Error> {
+        self.call("bkpr-listbalances", requests::BkprListBalances {})
+    }
+
+    /// List all events of {account} (or of all accounts) recorded by the bookkeeper plugin.
+    pub fn bkpr_listaccountevents(
+        &mut self,
+        account: Option<&str>,
+    ) -> Result<responses::BkprListAccountEvents, Error> {
+        self.call(
+            "bkpr-listaccountevents",
+            requests::BkprListAccountEvents { account },
+        )
+    }
+
+    /// List the income and expense events between the UNIX timestamps {start_time} and
+    /// {end_time}, for example for an accounting export.
+    ///
+    /// Unless {consolidate_fees} is false, the onchain fees of a transaction are reported as one
+    /// event rather than one per account.
+    pub fn bkpr_listincome(
+        &mut self,
+        consolidate_fees: Option<bool>,
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+    ) -> Result<responses::ListIncome, Error> {
+        self.call(
+            "bkpr-listincome",
+            requests::BkprListIncome {
+                consolidate_fees,
+                start_time,
+                end_time,
+            },
+        )
+    }
+
+    /// Send the custom message {msg} to the connected peer {node_id}. {msg} is the hex encoded
+    /// message, starting with its two byte type, which must be odd so that peers that do not
+    /// understand it ignore it.
+    ///
+    /// Custom messages sent by peers are not delivered over the RPC socket, lightningd passes
+    /// them to plugins through the `custommsg` hook.
+    pub fn sendcustommsg(
+        &mut self,
+        node_id: &str,
+        msg: &str,
+    ) -> Result<responses::SendCustomMsg, Error> {
+        common::check_pubkey("node_id", node_id)?;
+        common::check_custommsg(msg)?;
+        self.call("sendcustommsg", requests::SendCustomMsg { node_id, msg })
+    }
+
+    /// Inject the gossip {message}, hex encoded and starting with its two byte type, such as a
+    /// `channel_announcement`, `node_announcement` or `channel_update`.
+    ///
+    /// lightningd checks the message as if a peer had sent it, so invalid signatures or
+    /// unknown channels are reported as `Error::Rpc`.
+    pub fn addgossip(&mut self, message: &str) -> Result<responses::AddGossip, Error> {
+        common::check_hex_bytes("message", message, 2)?;
+        self.call("addgossip", requests::AddGossip { message })
+    }
+
+    /// Get the static channel backups of all channels, to store somewhere safe.
+    ///
+    /// This only reads them and can be run periodically. The returned `scb` list can be passed
+    /// as is to `recoverchannel`.
+    pub fn staticbackup(&mut self) -> Result<responses::StaticBackup, Error> {
+        self.call("staticbackup", requests::StaticBackup {})
+    }
+
+    /// Recover the channels in {scb}, a list of static channel backups in hex as found in the
+    /// `emergency.recover` file or returned by `staticbackup`.
+    ///
+    /// This is a last resort after losing the node's database. For each backup lightningd
+    /// creates a stub channel and asks the peer to force close it, which only works if the peer
+    /// is online and cooperates; our funds are returned onchain once the peer's commitment
+    /// transaction confirms. Do not use it while the original node or a copy of its database
+    /// may still be running: the peer could then see an old state and take all funds of the
+    /// channel as a penalty.
+    pub fn recoverchannel(&mut self, scb: &[String]) -> Result<responses::RecoverChannel, Error> {
+        self.call(
+            "recoverchannel",
+            requests::RecoverChannel { scb: scb.to_vec() },
+        )
+    }
+
+    /// Recover the channels in the node's `emergency.recover` file, like `recoverchannel`.
+    ///
+    /// The same caveats apply: every channel is force closed by its peer and this must never be
+    /// done while another instance of the node may be running.
+    pub fn emergencyrecover(&mut self) -> Result<responses::EmergencyRecover, Error> {
+        self.call("emergencyrecover", requests::EmergencyRecover {})
+    }
+
+    /// Shut down the lightningd process.
+    ///
+    /// lightningd may close the socket before its answer arrives, this is reported as success
+    /// with the message "Shutdown complete". Failing to send the request, for example because
+    /// lightningd is not running, is still an error.
+    pub fn stop(&mut self) -> Result<responses::Stop, Error> {
+        stopped(self.call("stop", requests::Stop {}))
+    }
+}
+
+/// Developer commands, only available if lightningd was built with developer mode and the `dev`
+/// feature is enabled.
+///
+/// These are unstable, lightningd may change or remove them in any release, and are meant for
+/// testing against regtest nodes only. Several of them can lose funds when used on a real node.
+#[cfg(feature = "dev")]
+impl LightningRPC {
+    /// Synchronize the state of the wallet outputs with bitcoind.
+    pub fn dev_rescan_outputs(&mut self) -> Result<responses::DevRescanOutputs, Error> {
+        self.call("dev-rescan-outputs", requests::DevRescanOutputs {})
+    }
+
+    /// Forget the channel with peer {id}, selected by {short_channel_id} or {channel_id} if there
+    /// are several, without closing it. Unless {force} is set, this fails if the funding output
+    /// is still unspent.
+    pub fn dev_forget_channel(
+        &mut self,
+        id: &str,
+        short_channel_id: Option<&str>,
+        channel_id: Option<&str>,
+        force: Option<bool>,
+    ) -> Result<responses::DevForgetChannel, Error> {
+        self.call(
+            "dev-forget-channel",
+            requests::DevForgetChannel {
+                id,
+                short_channel_id,
+                channel_id,
+                force,
+            },
+        )
+    }
+
+    /// Sign and return the latest commitment transaction of the channel with peer {id}, without
+    /// broadcasting it. Broadcasting it afterwards will make the peer penalize us if the channel
+    /// state moved on.
+    pub fn dev_sign_last_tx(&mut self, id: &str) -> Result<responses::DevSignLastTx, Error> {
+        self.call("dev-sign-last-tx", requests::DevSignLastTx { id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::result_to_response;
+    use crate::testing::MockTransport;
+    use serde_json::json;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixListener;
+
+    #[test]
+    fn invoice_builder_checks_preimage() {
+        // Never connects, the socket does not exist
+        let mut rpc = LightningRPC::new("/nonexistent/lightning-rpc");
+        match rpc
+            .invoice_builder(1000, "label", "description")
+            .expiry(60)
+            .preimage("not hex")
+            .send()
+        {
+            Err(Error::InvalidInput(_)) => {}
+            other => panic!("expected invalid input, got {:?}", other),
+        }
+        match rpc.invoice_builder(1000, "label", "description").send() {
+            Err(Error::Io(_)) => {}
+            other => panic!("expected io error, got {:?}", other),
+        }
+    }
+
+    /// Answer the first {failures} calls of "pay" with an RPC error with code 205, and the next
+    /// one with `true`
+    fn flaky_rpc(failures: usize, policy: RetryPolicy) -> (LightningRPC, MockTransport) {
+        let mock = MockTransport::new();
+        for _ in 0..failures {
+            mock.push_error(
+                "pay",
+                RpcError {
+                    code: 205,
+                    message: "Could not find a route".to_owned(),
+                    data: None,
+                },
+            );
+        }
+        mock.push_result("pay", true).unwrap();
+        let rpc = LightningRPC::with_transport(mock.clone()).with_retry(policy);
+        (rpc, mock)
+    }
+
+    #[test]
+    fn retry_until_success() {
+        let policy = RetryPolicy::new(3, Duration::from_millis(1)).retry_if(|code| code == 205);
+        let (mut rpc, mock) = flaky_rpc(2, policy);
+        let result: bool = rpc.call("pay", json!({"bolt11": "lnbc1"})).unwrap();
+        assert!(result);
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|r| r.method == "pay"));
+        assert!(requests.iter().all(|r| r.params == requests[0].params));
+        assert_ne!(requests[0].id, requests[1].id);
+        assert_ne!(requests[1].id, requests[2].id);
+    }
+
+    #[test]
+    fn retry_gives_up() {
+        // Attempts exhausted
+        let policy = RetryPolicy::new(3, Duration::from_millis(1)).retry_if(|code| code == 205);
+        let (mut rpc, mock) = flaky_rpc(5, policy);
+        match rpc.call::<_, bool>("pay", json!({})) {
+            Err(Error::Rpc(e)) => assert_eq!(e.code, 205),
+            other => panic!("expected rpc error, got {:?}", other),
+        }
+        assert_eq!(mock.requests().len(), 3);
+
+        // Not retryable, the default
+        let (mut rpc, mock) = flaky_rpc(1, RetryPolicy::new(3, Duration::from_millis(1)));
+        assert!(rpc.call::<_, bool>("pay", json!({})).is_err());
+        assert_eq!(mock.requests().len(), 1);
+
+        // The next retry would start after the deadline
+        let policy = RetryPolicy::new(10, Duration::from_millis(20))
+            .retry_if(|_| true)
+            .deadline(Duration::from_millis(50));
+        let (mut rpc, mock) = flaky_rpc(10, policy);
+        let start = Instant::now();
+        assert!(rpc.call::<_, bool>("pay", json!({})).is_err());
+        assert!(start.elapsed() < Duration::from_millis(50));
+        // Waits of 20ms and 40ms would add up past the deadline
+        assert_eq!(mock.requests().len(), 2);
+    }
+
+    #[test]
+    fn retry_backoff() {
+        let policy = RetryPolicy::new(10, Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(300))
+            .deadline(Duration::from_secs(1))
+            .retry_if(|_| true);
+        let error = RpcError {
+            code: -1,
+            message: "error".to_owned(),
+            data: None,
+        };
+        let delay = |attempt, elapsed| policy.delay(&error, attempt, elapsed);
+        assert_eq!(
+            delay(1, Duration::from_millis(0)),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            delay(2, Duration::from_millis(0)),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(
+            delay(3, Duration::from_millis(0)),
+            Some(Duration::from_millis(300))
+        );
+        assert_eq!(delay(40, Duration::from_millis(0)), None);
+        assert_eq!(
+            delay(9, Duration::from_millis(0)),
+            Some(Duration::from_millis(300))
+        );
+        assert_eq!(delay(3, Duration::from_millis(800)), None);
+    }
+
+    #[test]
+    fn stop_closing_socket() {
+        let path = std::env::temp_dir().join(format!("clightningrpc-stop-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = thread::spawn(move || {
+            // Answer the first stop, close the connection without answering the second
+            for answered in [true, false].iter() {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0u8; 1024];
+                let n = stream.read(&mut buf).unwrap();
+                let request: Request = serde_json::from_slice(&buf[..n]).unwrap();
+                assert_eq!(request.method, "stop");
+                if *answered {
+                    let response = result_to_response(Ok(Value::from("Shutting down")), request.id);
+                    stream
+                        .write_all(&serde_json::to_vec(&response).unwrap())
+                        .unwrap();
+                }
+            }
+        });
+
+        let mut rpc = LightningRPC::new(&path);
+        assert_eq!(rpc.stop().unwrap().0, "Shutting down");
+        rpc.reconnect().unwrap();
+        assert_eq!(rpc.stop().unwrap().0, "Shutdown complete");
+        server.join().unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+
+    /// A transport to a daemon that is already gone
+    struct GoneTransport;
+
+    impl Read for GoneTransport {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Ok(0)
+        }
+    }
+
+    impl Write for GoneTransport {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl RpcTransport for GoneTransport {
+        fn connect(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn disconnect(&mut self) {}
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        fn set_timeout(&mut self, _timeout: Option<Duration>) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn stop_not_delivered() {
+        let mut rpc = LightningRPC::with_transport(GoneTransport);
+        match rpc.stop() {
+            Err(Error::Io(e)) => assert_eq!(e.kind(), io::ErrorKind::BrokenPipe),
+            other => panic!("expected broken pipe, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn cache_cleared_on_reconnect() {
+        let mock = MockTransport::new();
+        mock.push_result(
+            "help",
+            json!({"help": [{"command": "pay", "description": ""}]}),
+        )
+        .unwrap();
+        mock.push_result(
+            "help",
+            json!({"help": [{"command": "renepay", "description": ""}]}),
+        )
+        .unwrap();
+        mock.push_result("echo", true).unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        assert!(rpc.capabilities().unwrap().supports("pay"));
+        assert!(rpc.capabilities().unwrap().supports("pay"));
+        assert_eq!(mock.requests().len(), 1);
+
+        // The connection is lost, the next call opens a new one
+        RpcTransport::disconnect(&mut mock.clone());
+        rpc.call::<_, bool>("echo", json!({})).unwrap();
+        assert!(rpc.capabilities().unwrap().supports("renepay"));
+        assert_eq!(mock.requests().len(), 3);
+    }
+
+    #[test]
+    fn fundchannel_checks_push_msat() {
+        let mut rpc = LightningRPC::new("/nonexistent/lightning-rpc");
+        match rpc.fundchannel("02aa", "1000sat", None, None, None, Some(1_000_001), None) {
+            Err(Error::InvalidInput(_)) => {}
+            other => panic!("expected invalid input, got {:?}", other),
+        }
+        // Within the amount, or not comparable, so sent to the daemon
+        for amount in ["1000sat", "all"].iter() {
+            match rpc.fundchannel("02aa", amount, None, None, None, Some(1_000_000), None) {
+                Err(Error::Io(_)) => {}
+                other => panic!("expected io error, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn fundchannel_positional() {
+        let mock = MockTransport::new();
+        mock.push_result(
+            "fundchannel",
+            json!({"tx": "0200", "txid": "ab", "channel_id": "cd", "outnum": 0}),
+        )
+        .unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        rpc.set_param_style(ParamStyle::Positional);
+        rpc.fundchannel("02aa", "100000sat", None, None, None, Some(1000), None)
+            .unwrap();
+        // push_msat comes after utxos in the daemon's order
+        assert_eq!(
+            mock.requests()[0].params,
+            json!(["02aa", "100000sat", null, null, null, null, 1000])
+        );
+    }
+
+    #[test]
+    fn pay_builder_params() {
+        let mock = MockTransport::new();
+        let paid = json!({
+            "payment_preimage": "00",
+            "payment_hash": "11",
+            "created_at": 1661371840.5,
+            "parts": 2,
+            "amount_msat": 1000,
+            "amount_sent_msat": 1001,
+            "status": "complete"
+        });
+        mock.push_result("pay", &paid).unwrap();
+        mock.push_result("pay", &paid).unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        let pay = rpc
+            .pay_builder("lnbc1")
+            .amount_msat(1000)
+            .maxfee(10)
+            .exclude(vec!["103x1x0/1".to_owned()])
+            .send()
+            .unwrap();
+        assert_eq!(pay.parts, 2);
+        rpc.pay("lnbc2").unwrap();
 
-    /// Show information about this node.
-    pub fn getinfo(&mut self) -> Result<responses::GetInfo, Error> {
-        self.call("getinfo", requests::GetInfo {})
+        let requests = mock.requests();
+        assert_eq!(
+            requests[0].params,
+            json!({"bolt11": "lnbc1", "amount_msat": 1000, "maxfee": 10, "exclude": ["103x1x0/1"]})
+        );
+        assert_eq!(requests[1].params, json!({"bolt11": "lnbc2"}));
     }
 
-    /// Return feerate estimates, either satoshi-per-kw ({style} perkw) or satoshi-per-kb ({style}
-    /// perkb).
-    pub fn feerates(&mut self, style: String) -> Result<responses::FeeRates, Error> {
-        self.call(
-            "feerates",
-            requests::FeeRates {
-                style: style,
+    #[test]
+    fn waitblockheight() {
+        let mock = MockTransport::new();
+        mock.push_result("waitblockheight", json!({"blockheight": 110}))
+            .unwrap();
+        // What lightningd answers when the timeout fires first
+        mock.push_error(
+            "waitblockheight",
+            RpcError {
+                code: 2000,
+                message: "Timed out.".to_owned(),
+                data: None,
             },
-        )
+        );
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        assert_eq!(rpc.waitblockheight(110, None).unwrap().blockheight, 110);
+        match rpc.waitblockheight(120, Some(1)) {
+            Err(Error::Rpc(e)) => assert_eq!(e.code, 2000),
+            other => panic!("expected rpc error, got {:?}", other),
+        }
+        assert_eq!(
+            mock.requests()[1].params,
+            json!({"blockheight": 120, "timeout": 1})
+        );
     }
 
-    /// Show node {id} (or all, if no {id}), in our local network view.
-    pub fn listnodes(&mut self, id: Option<String>) -> Result<responses::ListNodes, Error> {
-        self.call("listnodes", requests::ListNodes { id })
+    #[test]
+    fn getsharedsecret_round_trip() {
+        // BOLT #8 act one: the responder's shared secret with the initiator's ephemeral key
+        let point = "036360e856310ce5d294e8be33fc807077dc56ac80d95d9cd4ddbd21325eff73f7";
+        let secret = "1e2fb3c8fe8fb9f262f649f64d26ecf0f2c0a805a767cf02dc2d77a6ef1fdcc3";
+        let mock = MockTransport::new();
+        mock.push_result("getsharedsecret", json!({ "shared_secret": secret }))
+            .unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        assert_eq!(rpc.getsharedsecret(point).unwrap().shared_secret, secret);
+        assert_eq!(mock.requests()[0].params, json!({ "point": point }));
+
+        match rpc.getsharedsecret(&point[2..]) {
+            Err(Error::InvalidInput(_)) => {}
+            other => panic!("expected invalid input, got {:?}", other),
+        }
+        assert_eq!(mock.requests().len(), 1);
     }
 
-    /// Show channel {short_channel_id} (or all known channels, if no {short_channel_id}).
-    pub fn listchannels(
-        &mut self,
-        short_channel_id: Option<String>,
-    ) -> Result<responses::ListChannels, Error> {
-        self.call("listchannels", requests::ListChannels { short_channel_id })
+    #[test]
+    fn extra_params() {
+        let mock = MockTransport::new();
+        mock.push_result("getinfo", json!({})).unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        let extra = |v: Value| v.as_object().unwrap().clone();
+
+        let _: Value = rpc
+            .call_with_extra(
+                "getinfo",
+                requests::GetInfo {},
+                extra(json!({"new": 1})),
+                false,
+            )
+            .unwrap();
+        // Clashes with a typed parameter, refused without sending anything
+        match rpc
+            .pay_builder("lnbc1")
+            .extra_params(extra(json!({"bolt11": "lnbc2"})), false)
+            .send()
+        {
+            Err(Error::InvalidInput(_)) => {}
+            other => panic!("expected invalid input, got {:?}", other),
+        }
+        let _ = rpc
+            .pay_builder("lnbc1")
+            .maxfee(10)
+            .extra_params(extra(json!({"maxfee": 20, "new_flag": true})), true)
+            .send();
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].params, json!({"new": 1}));
+        assert_eq!(
+            requests[1].params,
+            json!({"bolt11": "lnbc1", "maxfee": 20, "new_flag": true})
+        );
     }
 
-    /// List available commands, or give verbose help on one command.
-    pub fn help(&mut self, command: Option<String>) -> Result<responses::Help, Error> {
-        self.call("help", requests::Help { command })
+    #[test]
+    fn param_styles() {
+        let closed = json!({"type": "mutual", "tx": "02", "txid": "aa"});
+        let mock = MockTransport::new();
+        for _ in 0..3 {
+            mock.push_result("close", &closed).unwrap();
+        }
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        rpc.close("02aa", None, Some("bc1q"), None).unwrap();
+        rpc.set_param_style(ParamStyle::Positional);
+        rpc.close("02aa", None, Some("bc1q"), None).unwrap();
+        rpc.close("02aa", Some(60), None, None).unwrap();
+
+        let requests = mock.requests();
+        assert_eq!(
+            requests[0].params,
+            json!({"id": "02aa", "destination": "bc1q"})
+        );
+        assert_eq!(requests[1].params, json!(["02aa", null, "bc1q"]));
+        assert_eq!(requests[2].params, json!(["02aa", 60]));
+
+        // A JSON object has its keys sorted, so it is sent by name
+        mock.push_result("echo", true).unwrap();
+        let params = json!({"bolt11": "lnbc1", "amount_msat": 5});
+        assert!(rpc.call::<_, bool>("echo", &params).unwrap());
+        assert_eq!(mock.requests()[3].params, params);
     }
 
-    /// Show logs, with optional log {level} (info|unusual|debug|io).
-    pub fn getlog(&mut self, level: Option<String>) -> Result<responses::GetLog, Error> {
-        self.call("getlog", requests::GetLog { level })
+    fn invoices(indexes: std::ops::RangeInclusive<u64>) -> Value {
+        let invoices: Vec<Value> = indexes
+            .map(|i| {
+                json!({
+                    "label": format!("inv{}", i),
+                    "bolt11": "lnbcrt1",
+                    "payment_hash": "00",
+                    "amount_msat": 1000,
+                    "status": "unpaid",
+                    "expires_at": 1700000000,
+                    "created_index": i
+                })
+            })
+            .collect();
+        json!({ "invoices": invoices })
     }
 
-    /// List all configuration options, or with [config], just that one.
-    /// Because of the dynamic nature of the returned object, unlike the other methods, this
-    /// returns a HashMap (from String to Json) instead of a structure.
-    pub fn listconfigs(&mut self, config: Option<String>) -> Result<responses::ListConfigs, Error> {
-        self.call("listconfigs", requests::ListConfigs { config })
+    #[test]
+    fn listinvoices_flat() {
+        let mock = MockTransport::new();
+        mock.push_result("listinvoices", invoices(3..=3)).unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        let list = rpc
+            .listinvoices(Some("inv3"), None, None, None, None, None, None)
+            .unwrap();
+        assert_eq!(list.invoices[0].amount_msat, Some(common::MSat(1000)));
+        assert_eq!(list.invoices[0].created_index, Some(3));
+        assert_eq!(mock.requests()[0].params, json!({"label": "inv3"}));
     }
 
-    /// Show current peers, if {level} is set, include {log}s.
-    pub fn listpeers(
-        &mut self,
-        id: Option<String>,
-        level: Option<String>,
-    ) -> Result<responses::ListPeers, Error> {
-        self.call("listpeers", requests::ListPeers { id, level })
+    #[test]
+    fn invoices_paged() {
+        let mock = MockTransport::new();
+        mock.push_result("listinvoices", invoices(1..=2)).unwrap();
+        mock.push_result("listinvoices", invoices(3..=4)).unwrap();
+        mock.push_result("listinvoices", invoices(6..=6)).unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        let labels: Vec<String> = rpc
+            .invoices_paged(2)
+            .map(|invoice| invoice.unwrap().label)
+            .collect();
+        assert_eq!(labels, ["inv1", "inv2", "inv3", "inv4", "inv6"]);
+        let starts: Vec<Value> = mock
+            .requests()
+            .iter()
+            .map(|r| r.params["start"].clone())
+            .collect();
+        assert_eq!(starts, [json!(0), json!(3), json!(5)]);
+        assert_eq!(
+            mock.requests()[0].params,
+            json!({"index": "created", "start": 0, "limit": 2})
+        );
     }
 
-    /// Show invoice {label} (or all, if no {label)).
-    pub fn listinvoices(
-        &mut self,
-        label: Option<String>,
-    ) -> Result<responses::ListInvoices, Error> {
-        self.call("listinvoices", requests::ListInvoices { label })
+    #[test]
+    fn invoices_paged_without_index() {
+        let mock = MockTransport::new();
+        let mut page = invoices(1..=2);
+        page["invoices"][1]
+            .as_object_mut()
+            .unwrap()
+            .remove("created_index");
+        mock.push_result("listinvoices", page).unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        let results: Vec<_> = rpc.invoices_paged(2).collect();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[1].as_ref().unwrap().label, "inv2");
+        match results[2] {
+            Err(Error::MissingField("created_index")) => {}
+            ref other => panic!("expected missing created_index, got {:?}", other),
+        }
+        assert_eq!(mock.requests().len(), 1);
     }
 
-    /// Create an invoice for {msatoshi} with {label} and {description} with
-    /// optional {expiry} seconds (default 1 hour).
-    pub fn invoice(
-        &mut self,
-        msatoshi: i64,
-        label: String,
-        description: String,
-        expiry: Option<i64>,
-    ) -> Result<responses::Invoice, Error> {
-        self.call(
-            "invoice",
-            requests::Invoice {
-                msatoshi,
-                label,
-                description,
-                expiry,
+    #[test]
+    fn invoices_paged_unsupported() {
+        let mock = MockTransport::new();
+        mock.push_error(
+            "listinvoices",
+            RpcError {
+                code: -32602,
+                message: "unknown parameter: index".to_owned(),
+                data: None,
             },
-        )
-    }
+        );
+        mock.push_result("listinvoices", invoices(1..=3)).unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        let all: Result<Vec<_>, Error> = rpc.invoices_paged(2).collect();
+        assert_eq!(all.unwrap().len(), 3);
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].params, json!({}));
 
-    /// Create an invoice for {msatoshi} with {label} and {description} with
-    /// optional {expiry} seconds (default 1 hour).
-    pub fn delinvoice(
-        &mut self,
-        label: String,
-        status: String,
-    ) -> Result<responses::DelInvoice, Error> {
-        self.call("delinvoice", requests::DelInvoice { label, status })
+        // Other errors end the iteration
+        let mut pages = rpc.invoices_paged(2);
+        assert!(pages.next().unwrap().is_err());
+        assert!(pages.next().is_none());
     }
 
-    /// Delete all expired invoices that expired as of given {maxexpirytime} (a UNIX epoch time),
-    /// or all expired invoices if not specified.
-    pub fn delexpiredinvoice(
-        &mut self,
-        maxexpirytime: Option<i64>,
-    ) -> Result<responses::DelExpiredInvoice, Error> {
-        self.call(
-            "delexpiredinvoice",
-            requests::DelExpiredInvoice { maxexpirytime },
+    #[test]
+    fn plugin_subcommands() {
+        let mock = MockTransport::new();
+        let plugins = json!({"command": "list", "plugins": []});
+        mock.push_result("plugin", &plugins).unwrap();
+        mock.push_result("plugin", &plugins).unwrap();
+        mock.push_result(
+            "plugin",
+            json!({"command": "stop", "result": "Successfully stopped helloworld.py."}),
         )
+        .unwrap();
+        mock.push_result("plugin", &plugins).unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        rpc.plugin_list().unwrap();
+        rpc.plugin_start("helloworld.py").unwrap();
+        assert!(rpc
+            .plugin_stop("helloworld.py")
+            .unwrap()
+            .result
+            .contains("stopped"));
+        rpc.plugin_rescan().unwrap();
+
+        let params: Vec<Value> = mock.requests().into_iter().map(|r| r.params).collect();
+        assert_eq!(
+            params,
+            [
+                json!({"subcommand": "list"}),
+                json!({"subcommand": "start", "plugin": "helloworld.py"}),
+                json!({"subcommand": "stop", "plugin": "helloworld.py"}),
+                json!({"subcommand": "rescan"}),
+            ]
+        );
     }
 
-    /// Set up autoclean of expired invoices. Perform cleanup every {cycle_seconds} (default 3600),
-    /// or disable autoclean if 0. Clean up expired invoices that have expired for {expired_by}
-    /// seconds (default 86400).
-    pub fn autocleaninvoice(
-        &mut self,
-        cycle_seconds: Option<i64>,
-        expired_by: Option<i64>,
-    ) -> Result<responses::AutoCleanInvoice, Error> {
-        self.call(
-            "autocleaninvoice",
-            requests::AutoCleanInvoice {
-                cycle_seconds,
-                expired_by,
+    #[test]
+    fn check_params() {
+        let mock = MockTransport::new();
+        mock.push_result("check", json!({"command_to_check": "pay"}))
+            .unwrap();
+        mock.push_error(
+            "check",
+            RpcError {
+                code: -32602,
+                message: "missing required parameter: bolt11".to_owned(),
+                data: None,
             },
-        )
+        );
+        mock.push_result("check", json!({"command_to_check": "connect"}))
+            .unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        let checked = rpc.check("pay", json!({"bolt11": "lnbc1"})).unwrap();
+        assert_eq!(checked.command_to_check, "pay");
+        match rpc.check("pay", json!({})) {
+            Err(Error::Rpc(e)) => assert_eq!(e.code_kind(), RpcErrorKind::InvalidParams),
+            other => panic!("expected rpc error, got {:?}", other),
+        }
+        rpc.check("connect", json!(["02aa@127.0.0.1"])).unwrap();
+
+        let params: Vec<Value> = mock.requests().into_iter().map(|r| r.params).collect();
+        assert_eq!(
+            params,
+            [
+                json!({"command_to_check": "pay", "bolt11": "lnbc1"}),
+                json!({"command_to_check": "pay"}),
+                json!(["connect", "02aa@127.0.0.1"]),
+            ]
+        );
+
+        // Named params stay named with positional encoding, array ones stay positional
+        mock.push_result("check", json!({"command_to_check": "pay"}))
+            .unwrap();
+        mock.push_result("check", json!({"command_to_check": "connect"}))
+            .unwrap();
+        rpc.set_param_style(ParamStyle::Positional);
+        rpc.check("pay", json!({"bolt11": "lnbc1"})).unwrap();
+        rpc.check("connect", json!(["02aa@127.0.0.1"])).unwrap();
+        let requests = mock.requests();
+        assert_eq!(
+            requests[3].params,
+            json!({"command_to_check": "pay", "bolt11": "lnbc1"})
+        );
+        assert_eq!(requests[4].params, json!(["connect", "02aa@127.0.0.1"]));
     }
 
-    /// Wait for the next invoice to be paid, after {lastpay_index}.
-    /// (if supplied)
-    pub fn waitanyinvoice(
-        &mut self,
-        lastpay_index: Option<i64>,
-    ) -> Result<responses::WaitAnyInvoice, Error> {
-        self.call("waitanyinvoice", requests::WaitAnyInvoice { lastpay_index })
+    #[test]
+    fn psbt_params() {
+        let psbt = "cHNidP8BAHEC";
+        let mock = MockTransport::new();
+        mock.push_result("signpsbt", json!({ "signed_psbt": psbt }))
+            .unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        rpc.signpsbt(psbt, Some(&[0, 2])).unwrap();
+        assert_eq!(
+            mock.requests()[0].params,
+            json!({"psbt": psbt, "signonly": [0, 2]})
+        );
+        for invalid in ["", "not base64!"].iter() {
+            match rpc.sendpsbt(invalid) {
+                Err(Error::InvalidInput(_)) => {}
+                other => panic!("expected invalid input, got {:?}", other),
+            }
+        }
+        assert_eq!(mock.requests().len(), 1);
     }
 
-    /// Wait for an incoming payment matching the invoice with {label}.
-    pub fn waitinvoice(&mut self, label: String) -> Result<responses::WaitInvoice, Error> {
-        self.call("waitinvoice", requests::WaitInvoice { label })
+    #[test]
+    fn fundpsbt_positional() {
+        let funded = json!({
+            "psbt": "cHNidP8BAHEC",
+            "feerate_per_kw": 253,
+            "estimated_final_weight": 612,
+            "excess_msat": 0,
+            "change_outnum": 1
+        });
+        let mock = MockTransport::new();
+        mock.push_result("fundpsbt", &funded).unwrap();
+        mock.push_result("utxopsbt", &funded).unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        rpc.set_param_style(ParamStyle::Positional);
+        rpc.fundpsbt("all", "normal", 0, None, None, Some(true))
+            .unwrap();
+        let utxos = [format!("{}:1", "ab".repeat(32))];
+        rpc.utxopsbt("all", "normal", 0, &utxos, Some(0), Some(true))
+            .unwrap();
+        // excess_as_change comes after locktime and min_witness_weight, and for utxopsbt
+        // reservedok
+        let requests = mock.requests();
+        assert_eq!(
+            requests[0].params,
+            json!(["all", "normal", 0, null, null, null, null, true])
+        );
+        assert_eq!(
+            requests[1].params,
+            json!(["all", "normal", 0, utxos, 0, null, null, null, true])
+        );
     }
 
-    /// Send a lightning payment.
-    ///
-    /// # Arguments
-    ///
-    /// * `bolt11` - A string that holds the payment information in bolt11 format.
-    /// * `options` - Options for this payment. Use `Default::default()` to not pass any options.
-    pub fn pay(&mut self, bolt11: String, options: PayOptions) -> Result<responses::Pay, Error> {
-        self.call(
-            "pay",
-            requests::Pay {
-                bolt11: bolt11,
-                msatoshi: options.msatoshi,
-                description: options.description,
-                riskfactor: options.riskfactor,
-                maxfeepercent: options.maxfeepercent,
-                exemptfee: options.exemptfee,
-                retry_for: options.retry_for,
-                maxdelay: options.maxdelay,
-            },
+    #[cfg(feature = "dev")]
+    #[test]
+    fn dev_commands() {
+        let mock = MockTransport::new();
+        mock.push_result("dev-rescan-outputs", json!({ "outputs": [] }))
+            .unwrap();
+        mock.push_result(
+            "dev-forget-channel",
+            json!({
+                "forced": true,
+                "funding_unspent": true,
+                "funding_txid": "05985cb4ec99fd3a8a5e46e35eb6cede8d13fd5c9b2206350cedf7f0b6ac92e1"
+            }),
         )
+        .unwrap();
+        mock.push_result("dev-sign-last-tx", json!({ "tx": "02000000000101" }))
+            .unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        let peer = format!("02{}", "ab".repeat(32));
+        assert!(rpc.dev_rescan_outputs().unwrap().outputs.is_empty());
+        assert!(
+            rpc.dev_forget_channel(&peer, None, None, Some(true))
+                .unwrap()
+                .forced
+        );
+        assert_eq!(rpc.dev_sign_last_tx(&peer).unwrap().tx, "02000000000101");
+        let requests = mock.requests();
+        assert_eq!(requests[0].method, "dev-rescan-outputs");
+        assert_eq!(requests[1].params, json!({"id": peer, "force": true}));
+        assert_eq!(requests[2].params, json!({ "id": peer }));
     }
 
-    /// Send along {route} in return for preimage of {payment_hash}, with optional {description}.
-    pub fn sendpay(
-        &mut self,
-        route: Vec<common::RouteItem>,
-        payment_hash: String,
-        description: Option<String>,
-        msatoshi: Option<i64>,
-    ) -> Result<responses::SendPay, Error> {
-        self.call(
-            "sendpay",
-            requests::SendPay {
-                route,
-                payment_hash,
-                description,
-                msatoshi,
+    #[test]
+    fn on_complete_hook() {
+        let mock = MockTransport::new();
+        mock.push_result("getinfo", json!({})).unwrap();
+        mock.push_error(
+            "pay",
+            RpcError {
+                code: -1,
+                message: "failed".to_owned(),
+                data: None,
             },
+        );
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut rpc = LightningRPC::with_transport(mock);
+        let seen = calls.clone();
+        rpc.set_on_complete(move |method, elapsed, success| {
+            seen.lock()
+                .unwrap()
+                .push((method.to_owned(), elapsed, success));
+        });
+        // The round trip succeeds even if the result does not decode
+        assert!(rpc.getinfo().is_err());
+        assert!(rpc.call::<_, Value>("pay", json!({})).is_err());
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!((calls[0].0.as_str(), calls[0].2), ("getinfo", true));
+        assert_eq!((calls[1].0.as_str(), calls[1].2), ("pay", false));
+        for (_, elapsed, _) in calls.iter() {
+            assert!(*elapsed > Duration::from_nanos(0) && *elapsed < Duration::from_secs(5));
+        }
+    }
+
+    #[test]
+    fn datastore_params() {
+        let mock = MockTransport::new();
+        mock.push_result(
+            "datastore",
+            json!({"key": ["a", "b"], "generation": 1, "hex": "00ff"}),
+        )
+        .unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        let key = vec!["a".to_owned(), "b".to_owned()];
+        rpc.datastore(
+            &key,
+            None,
+            Some("00ff"),
+            Some(requests::DataStoreMode::CreateOrAppend),
+            Some(0),
         )
+        .unwrap();
+        assert_eq!(
+            mock.requests()[0].params,
+            json!({"key": ["a", "b"], "hex": "00ff", "mode": "create-or-append", "generation": 0})
+        );
+        for (string, hex) in [(Some("x"), Some("00")), (None, Some("0g"))].iter() {
+            match rpc.datastore(&key, *string, *hex, None, None) {
+                Err(Error::InvalidInput(_)) => {}
+                other => panic!("expected invalid input, got {:?}", other),
+            }
+        }
+        assert_eq!(mock.requests().len(), 1);
     }
 
-    /// Wait for payment attempt on {payment_hash} to succeed or fail, but only up to {timeout} seconds.
-    pub fn waitsendpay(
-        &mut self,
-        payment_hash: String,
-        timeout: i64,
-    ) -> Result<responses::WaitSendPay, Error> {
-        self.call(
-            "waitsendpay",
-            requests::WaitSendPay {
-                payment_hash,
-                timeout,
-            },
+    #[test]
+    fn socket_path_from_env() {
+        let path = |vars: &[(&str, &str)]| {
+            let vars: std::collections::HashMap<_, _> = vars.iter().cloned().collect();
+            env_socket_path(|name| vars.get(name).map(OsString::from))
+        };
+        assert_eq!(
+            path(&[("HOME", "/home/satoshi")]),
+            Path::new("/home/satoshi/.lightning/bitcoin/lightning-rpc")
+        );
+        assert_eq!(
+            path(&[("HOME", "/home/satoshi"), ("LIGHTNING_NETWORK", "regtest")]),
+            Path::new("/home/satoshi/.lightning/regtest/lightning-rpc")
+        );
+        assert_eq!(
+            path(&[("HOME", "/home/satoshi"), ("LIGHTNING_DIR", "/var/lib/cln")]),
+            Path::new("/var/lib/cln/bitcoin/lightning-rpc")
+        );
+        assert_eq!(
+            path(&[
+                ("LIGHTNING_DIR", "/var/lib/cln"),
+                ("LIGHTNING_RPC_FILE", "/run/cln.sock")
+            ]),
+            Path::new("/run/cln.sock")
+        );
+
+        // Only this test touches these variables
+        let dir = std::env::temp_dir().join(format!("clightningrpc-env-{}", std::process::id()));
+        let socket = dir.join("testnet").join("lightning-rpc");
+        fs::create_dir_all(socket.parent().unwrap()).unwrap();
+        std::env::remove_var("LIGHTNING_RPC_FILE");
+        std::env::set_var("LIGHTNING_DIR", &dir);
+        std::env::set_var("LIGHTNING_NETWORK", "signet");
+        match LightningRPC::from_env() {
+            Err(Error::SocketNotFound(path)) => {
+                assert_eq!(path, dir.join("signet").join("lightning-rpc"))
+            }
+            Err(e) => panic!("expected socket not found, got {:?}", e),
+            Ok(_) => panic!("expected socket not found"),
+        }
+        std::env::set_var("LIGHTNING_NETWORK", "testnet");
+        let listener = UnixListener::bind(&socket).unwrap();
+        let rpc = LightningRPC::from_env();
+        std::env::remove_var("LIGHTNING_DIR");
+        std::env::remove_var("LIGHTNING_NETWORK");
+        drop(listener);
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(rpc.is_ok());
+    }
+
+    #[test]
+    fn estimate_fee() {
+        let mock = MockTransport::new();
+        let feerates = json!({
+            "perkw": {
+                "opening": 2500,
+                "mutual_close": 1250,
+                "unilateral_close": 11000,
+                "penalty": 7500,
+                "min_acceptable": 253,
+                "max_acceptable": 100000
+            }
+        });
+        for _ in 0..3 {
+            mock.push_result("feerates", feerates.clone()).unwrap();
+        }
+        mock.push_result(
+            "feerates",
+            json!({"perkw": {"min_acceptable": 253, "max_acceptable": 4294967295u64}}),
         )
+        .unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        // 2500 sat per 1000 weight units is 1125 sat for 450 weight units
+        assert_eq!(
+            rpc.estimate_fee_for_weight(450, FeeTarget::Opening)
+                .unwrap(),
+            1_125_000
+        );
+        assert_eq!(
+            rpc.estimate_fee_for_weight(724, FeeTarget::UnilateralClose)
+                .unwrap(),
+            7_964_000
+        );
+        assert_eq!(
+            rpc.estimate_fee_for_weight(0, FeeTarget::Penalty).unwrap(),
+            0
+        );
+        match rpc.estimate_fee_for_weight(450, FeeTarget::MutualClose) {
+            Err(Error::FeerateUnavailable) => {}
+            other => panic!("expected unavailable feerate, got {:?}", other),
+        }
+        assert_eq!(mock.requests()[0].params, json!({"style": "perkw"}));
     }
 
-    /// Show outgoing payments.
-    pub fn listpayments(
-        &mut self,
-        bolt11: Option<String>,
-        payment_hash: Option<String>,
-    ) -> Result<responses::ListPayments, Error> {
-        self.call(
-            "listpayments",
-            requests::ListPayments {
-                bolt11,
-                payment_hash,
+    #[test]
+    fn close_channels_where_reports_each() {
+        let channel = |peer: &str, id: &str, state: &str| {
+            json!({
+                "peer_id": peer,
+                "peer_connected": peer == "02bb",
+                "state": state,
+                "channel_id": id,
+                "funding_txid": "b3a1f9e7d5c3b1a9f7e5d3c1b9a7f5e3d1c9b7a5f3e1d9c7b5a3f1e9d7c5b3a1",
+                "to_us_msat": 1000,
+                "total_msat": 2000,
+                "their_reserve_msat": 0,
+                "our_reserve_msat": 0,
+                "spendable_msat": 1000,
+                "receivable_msat": 1000
+            })
+        };
+        let mock = MockTransport::new();
+        mock.push_result(
+            "listpeerchannels",
+            json!({"channels": [
+                channel("02aa", "c1", "CHANNELD_NORMAL"),
+                channel("02aa", "c2", "ONCHAIN"),
+                channel("02bb", "c3", "CHANNELD_NORMAL"),
+                channel("02cc", "c4", "CHANNELD_NORMAL"),
+            ]}),
+        )
+        .unwrap();
+        mock.push_result(
+            "listpeers",
+            json!({"peers": [
+                {"id": "02aa", "connected": false},
+                {"id": "02bb", "connected": true},
+                {"id": "02cc", "connected": false},
+            ]}),
+        )
+        .unwrap();
+        mock.push_error(
+            "close",
+            RpcError {
+                code: -1,
+                message: "Peer not connected".to_owned(),
+                data: None,
             },
+        );
+        mock.push_result(
+            "close",
+            json!({"type": "unilateral", "tx": "0200", "txid": "ab"}),
         )
+        .unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        let results = rpc
+            .close_channels_where(
+                |peer, channel| !peer.connected && channel.state.is_active(),
+                Some(1),
+            )
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, "c1");
+        match results[0].1 {
+            Err(Error::Rpc(ref e)) => assert_eq!(e.code, -1),
+            ref other => panic!("expected rpc error, got {:?}", other),
+        }
+        assert_eq!(results[1].0, "c4");
+        assert_eq!(
+            results[1].1.as_ref().unwrap().type_,
+            responses::CloseType::Unilateral
+        );
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(
+            requests[2].params,
+            json!({"id": "c1", "unilateraltimeout": 1})
+        );
+        assert_eq!(
+            requests[3].params,
+            json!({"id": "c4", "unilateraltimeout": 1})
+        );
+
+        // Daemons older than v23.02, where this cannot find the channels
+        mock.push_error(
+            "listpeerchannels",
+            RpcError {
+                code: -32601,
+                message: "Unknown command 'listpeerchannels'".to_owned(),
+                data: None,
+            },
+        );
+        match rpc.close_channels_where(|_, _| true, None) {
+            Err(Error::Rpc(ref e)) => assert!(e.is_method_not_found()),
+            other => panic!("expected rpc error, got {:?}", other),
+        }
+        assert_eq!(mock.requests().len(), 5);
     }
 
-    /// Decode {bolt11}, using {description} if necessary.
-    pub fn decodepay(
-        &mut self,
-        bolt11: String,
-        description: Option<String>,
-    ) -> Result<responses::DecodePay, Error> {
-        self.call(
-            "decodepay",
-            requests::DecodePay {
-                bolt11,
-                description,
+    #[test]
+    fn multifundchannel_params() {
+        let mock = MockTransport::new();
+        mock.push_result(
+            "multifundchannel",
+            json!({"tx": "0200", "txid": "ab", "channel_ids": []}),
+        )
+        .unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        let destinations = vec![
+            requests::MultiFundDest {
+                id: "02aa@127.0.0.1:9735".to_owned(),
+                amount: "100000sat".to_owned(),
+                push_msat: Some(1000),
+                ..Default::default()
+            },
+            requests::MultiFundDest {
+                id: "02bb".to_owned(),
+                amount: "all".to_owned(),
+                announce: Some(false),
+                ..Default::default()
             },
+        ];
+        rpc.multifundchannel(&destinations, None, None, Some(1))
+            .unwrap();
+        assert_eq!(
+            mock.requests()[0].params,
+            json!({
+                "destinations": [
+                    {"id": "02aa@127.0.0.1:9735", "amount": "100000sat", "push_msat": 1000},
+                    {"id": "02bb", "amount": "all", "announce": false}
+                ],
+                "minchannels": 1
+            })
+        );
+        let too_much = vec![requests::MultiFundDest {
+            id: "02aa".to_owned(),
+            amount: "1sat".to_owned(),
+            push_msat: Some(1001),
+            ..Default::default()
+        }];
+        match rpc.multifundchannel(&too_much, None, None, None) {
+            Err(Error::InvalidInput(_)) => {}
+            other => panic!("expected invalid input, got {:?}", other),
+        }
+
+        // minchannels comes after utxos in the daemon's order
+        mock.push_result(
+            "multifundchannel",
+            json!({"tx": "0200", "txid": "ab", "channel_ids": []}),
         )
+        .unwrap();
+        rpc.set_param_style(ParamStyle::Positional);
+        rpc.multifundchannel(&destinations[1..], None, None, Some(1))
+            .unwrap();
+        assert_eq!(
+            mock.requests()[1].params,
+            json!([[{"id": "02bb", "amount": "all", "announce": false}], null, null, null, 1])
+        );
     }
 
-    /// Show route to {id} for {msatoshi}, using {riskfactor} and optional {cltv} (default 9). If
-    /// specified search from {fromid} otherwise use this node as source. Randomize the route with
-    /// up to {fuzzpercent} (0.0 -> 100.0, default 5.0) using {seed} as an arbitrary-size string
-    /// seed.
-    pub fn getroute(
-        &mut self,
-        id: String,
-        msatoshi: i64,
-        riskfactor: f64,
-        cltv: Option<i64>,
-        fromid: Option<String>,
-        fuzzpercent: Option<f64>,
-        seed: Option<String>,
-    ) -> Result<responses::GetRoute, Error> {
-        self.call(
-            "getroute",
-            requests::GetRoute {
-                id,
-                msatoshi,
-                riskfactor,
-                cltv,
-                fromid,
-                fuzzpercent,
-                seed,
+    #[test]
+    fn splice_params() {
+        let channel_id = "e1c92acb0f7fedc0350622b9e5fd13d8dece6be5ae6e858a3afd99ecb45c9805";
+        let mock = MockTransport::new();
+        mock.push_result("splice_init", json!({"psbt": "cHNidP8BAgQC"}))
+            .unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        let init = rpc
+            .splice_init(channel_id, -100_000, None, Some(253))
+            .unwrap();
+        assert_eq!(
+            mock.requests()[0].params,
+            json!({"channel_id": channel_id, "relative_amount": -100_000, "feerate_per_kw": 253})
+        );
+        assert_eq!(init.psbt, "cHNidP8BAgQC");
+        match rpc.splice_update("03aa", &init.psbt) {
+            Err(Error::InvalidInput(_)) => {}
+            other => panic!("expected invalid input, got {:?}", other),
+        }
+        match rpc.splice_signed(channel_id, "not base64!") {
+            Err(Error::InvalidInput(_)) => {}
+            other => panic!("expected invalid input, got {:?}", other),
+        }
+        assert_eq!(mock.requests().len(), 1);
+    }
+
+    #[test]
+    fn renepay_round_trip() {
+        let paid = json!({
+            "payment_preimage": "7b0ef6f6b2d3ea5ff1ea2bd3d5bd04e5a4eedb4b3b5bb0dd1b43e3f1d0c3b7c2",
+            "payment_hash": "f56e0ba0e495ae7b4d6fa4c0cc9b2ffe3e1220a18a6bd4eaee2a2c1355bfb220",
+            "destination": "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59",
+            "created_at": 1690000000.25,
+            "parts": 1,
+            "amount_msat": 10000000,
+            "amount_sent_msat": 10000101,
+            "status": "complete"
+        });
+        let mock = MockTransport::new();
+        mock.push_result("renepay", paid.clone()).unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        let options = RenePayOptions {
+            maxfee: Some(2000),
+            ..Default::default()
+        };
+        let pay = rpc.renepay("lnbcrt1", Some(10_000_000), options).unwrap();
+        assert_eq!(
+            mock.requests()[0].params,
+            json!({"invstring": "lnbcrt1", "amount_msat": 10_000_000, "maxfee": 2000})
+        );
+        assert_eq!(serde_json::to_value(&pay).unwrap()["parts"], paid["parts"]);
+
+        mock.push_error(
+            "renepay",
+            RpcError {
+                code: -32601,
+                message: "Unknown command 'renepay'".to_owned(),
+                data: None,
             },
-        )
+        );
+        match rpc.renepay("lnbcrt1", None, Default::default()) {
+            Err(Error::Rpc(ref e)) => assert!(e.is_method_not_found()),
+            other => panic!("expected method not found, got {:?}", other),
+        }
     }
 
-    /// Connect to {id} at {host} (which can end in ':port' if not default). {id} can also be of
-    /// the form id@host.
-    pub fn connect(
-        &mut self,
-        id: String,
-        host: Option<String>,
-    ) -> Result<responses::Connect, Error> {
-        self.call("connect", requests::Connect { id, host })
+    #[test]
+    fn addgossip_checks_hex() {
+        let mock = MockTransport::new();
+        mock.push_result("addgossip", json!({})).unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        for invalid in ["", "01", "0102x3", "01020"].iter() {
+            match rpc.addgossip(invalid) {
+                Err(Error::InvalidInput(_)) => {}
+                other => panic!("expected invalid input, got {:?}", other),
+            }
+        }
+        assert!(mock.requests().is_empty());
+        rpc.addgossip("0102aabb").unwrap();
+        assert_eq!(mock.requests()[0].params, json!({"message": "0102aabb"}));
     }
 
-    /// Disconnect from peer with {peer_id}.
-    pub fn disconnect(&mut self, id: String) -> Result<responses::Disconnect, Error> {
-        self.call("disconnect", requests::Disconnect { id })
+    #[test]
+    fn builders_build_request() {
+        struct Fixed;
+        impl client::IdGenerator for Fixed {
+            fn next_id(&mut self, nonce: u64) -> Value {
+                Value::from(format!("tool:{}", nonce))
+            }
+        }
+        // Nothing listens here, building a request must not connect
+        let mut rpc = LightningRPC::new("/nonexistent/lightning-rpc");
+        let pay = rpc
+            .pay_builder("lnbcrt1")
+            .label("order 42")
+            .maxfee(1000)
+            .build_request(&mut Fixed)
+            .unwrap();
+        assert_eq!(
+            serde_json::to_string(&pay).unwrap(),
+            r#"{"method":"pay","params":{"bolt11":"lnbcrt1","label":"order 42","maxfee":1000},"id":"tool:1","jsonrpc":"2.0"}"#
+        );
+
+        let mut extra = Map::new();
+        extra.insert("new_flag".to_owned(), json!(true));
+        let invoice = rpc
+            .invoice_builder(1000, "label", "coffee")
+            .expiry(3600)
+            .extra_params(extra, false)
+            .build_request(&mut client::CounterIdGenerator)
+            .unwrap();
+        assert_eq!(
+            serde_json::to_string(&invoice).unwrap(),
+            r#"{"method":"invoice","params":{"amount_msat":1000,"description":"coffee","expiry":3600,"label":"label","new_flag":true},"id":2,"jsonrpc":"2.0"}"#
+        );
+
+        assert!(rpc
+            .invoice_builder(1000, "label", "coffee")
+            .preimage("00")
+            .build_request(&mut Fixed)
+            .is_err());
+        assert!(!rpc.is_connected());
     }
 
-    /// Fund channel with {id} using {satoshi} (or 'all') satoshis, at optional {feerate}.
-    pub fn fundchannel(
-        &mut self,
-        id: String,
-        satoshi: i64,
-        feerate: Option<i64>,
-    ) -> Result<responses::FundChannel, Error> {
-        self.call(
-            "fundchannel",
-            requests::FundChannel {
-                id,
-                satoshi,
-                feerate,
-            },
+    #[test]
+    fn ensure_connected() {
+        let id = "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59";
+        let mock = MockTransport::new();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        mock.push_result(
+            "listpeers",
+            json!({"peers": [{"id": id, "connected": true, "features": "08a0000a8a5961"}]}),
+        )
+        .unwrap();
+        let connect = rpc.ensure_connected(id, Some("127.0.0.1:9735")).unwrap();
+        assert_eq!(connect.id, id);
+        assert!(connect.features.unwrap().anchors());
+        assert_eq!(mock.requests().len(), 1);
+
+        // Connected by the peer between the check and 'connect'
+        mock.push_result(
+            "listpeers",
+            json!({"peers": [{"id": id, "connected": false}]}),
         )
+        .unwrap();
+        mock.push_error(
+            "connect",
+            RpcError {
+                code: -1,
+                message: "Peer already connected".to_owned(),
+                data: None,
+            },
+        );
+        let connect = rpc.ensure_connected(id, Some("127.0.0.1:9735")).unwrap();
+        assert_eq!(connect.id, id);
+        let requests = mock.requests();
+        assert_eq!(requests[2].method, "connect");
+        assert_eq!(
+            requests[2].params,
+            json!({"id": format!("{}@127.0.0.1:9735", id)})
+        );
+
+        mock.push_result("listpeers", json!({"peers": []})).unwrap();
+        mock.push_error(
+            "connect",
+            RpcError {
+                code: 401,
+                message: "All addresses failed".to_owned(),
+                data: None,
+            },
+        );
+        match rpc.ensure_connected(id, None) {
+            Err(Error::Rpc(ref e)) => assert_eq!(e.code, 401),
+            other => panic!("expected rpc error, got {:?}", other),
+        }
     }
 
-    /// Close the channel with {id} (either peer ID, channel ID, or short channel ID). If {force}
-    /// (default false) is true, force a unilateral close after {timeout} seconds (default 30),
-    /// otherwise just schedule a mutual close later and fail after timing out.
-    pub fn close(
-        &mut self,
-        id: String,
-        force: Option<bool>,
-        timeout: Option<i64>,
-    ) -> Result<responses::Close, Error> {
-        self.call("close", requests::Close { id, force, timeout })
+    #[test]
+    fn setpsbtversion_params() {
+        let psbt = "cHNidP8BAHEC";
+        let mock = MockTransport::new();
+        mock.push_result("setpsbtversion", json!({ "psbt": psbt }))
+            .unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        rpc.setpsbtversion(psbt, 0).unwrap();
+        assert_eq!(
+            mock.requests()[0].params,
+            json!({"psbt": psbt, "version": 0})
+        );
+        for version in [1, 3].iter() {
+            match rpc.setpsbtversion(psbt, *version) {
+                Err(Error::InvalidInput(_)) => {}
+                other => panic!("expected invalid input, got {:?}", other),
+            }
+        }
+        assert_eq!(mock.requests().len(), 1);
     }
 
-    /// Send {peerid} a ping of length {len} (default 128) asking for {pongbytes} (default 128).
-    pub fn ping(
-        &mut self,
-        peerid: String,
-        len: Option<i64>,
-        pongbytes: Option<i64>,
-    ) -> Result<responses::Ping, Error> {
-        self.call(
-            "ping",
-            requests::Ping {
-                peerid,
-                len,
-                pongbytes,
-            },
+    #[test]
+    fn delpay_round_trip() {
+        let hash = "f56e0ba0e495ae7b4d6fa4c0cc9b2ffe3e1220a18a6bd4eaee2a2c1355bfb220";
+        let mock = MockTransport::new();
+        mock.push_result(
+            "delpay",
+            json!({
+                "payments": [{
+                    "id": 4,
+                    "groupid": 1,
+                    "partid": 2,
+                    "payment_hash": hash,
+                    "destination": "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59",
+                    "amount_msat": 250000000,
+                    "amount_sent_msat": 250002750,
+                    "created_at": 1700000000,
+                    "status": "failed"
+                }]
+            }),
         )
-    }
+        .unwrap();
+        mock.push_error(
+            "delpay",
+            RpcError {
+                code: 211,
+                message:
+                    "Payment with hash f56e...b220 has complete status but failed status expected"
+                        .to_owned(),
+                data: None,
+            },
+        );
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        let deleted = rpc.delpay(hash, "failed", Some(2), Some(1)).unwrap();
+        assert_eq!(deleted.payments.len(), 1);
+        assert_eq!(deleted.payments[0].partid, Some(2));
+        assert_eq!(
+            deleted.payments[0].amount_sent_msat,
+            common::MSat(250_002_750)
+        );
+        assert_eq!(
+            mock.requests()[0].params,
+            json!({"payment_hash": hash, "status": "failed", "partid": 2, "groupid": 1})
+        );
 
-    /// Show available funds from the internal wallet.
-    pub fn listfunds(&mut self) -> Result<responses::ListFunds, Error> {
-        self.call("listfunds", requests::ListFunds {})
+        match rpc.delpay(hash, "failed", None, None) {
+            Err(Error::Rpc(e)) => assert_eq!(e.code, 211),
+            other => panic!("expected rpc error, got {:?}", other),
+        }
+        assert_eq!(
+            mock.requests()[1].params,
+            json!({"payment_hash": hash, "status": "failed"})
+        );
     }
 
-    /// Send to {destination} address {satoshi} (or 'all') amount via Bitcoin transaction, at optional {feerate}.
-    pub fn withdraw(
-        &mut self,
-        destination: String,
-        satoshi: i64,
-        feerate: Option<i64>,
-    ) -> Result<responses::Withdraw, Error> {
-        self.call(
-            "withdraw",
-            requests::Withdraw {
+    #[test]
+    fn keysend_extratlvs() {
+        let destination = "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59";
+        let sent = json!({
+            "payment_hash": "f56e0ba0e495ae7b4d6fa4c0cc9b2ffe3e1220a18a6bd4eaee2a2c1355bfb220",
+            "payment_preimage": "0000000000000000000000000000000000000000000000000000000000000000",
+            "destination": destination,
+            "created_at": 1700000000.5,
+            "parts": 1,
+            "amount_msat": 10000,
+            "amount_sent_msat": 10001,
+            "status": "complete"
+        });
+        let mock = MockTransport::new();
+        mock.push_result("keysend", &sent).unwrap();
+        mock.push_result("keysend", &sent).unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        let mut extratlvs = BTreeMap::new();
+        extratlvs.insert(7629169, "7b7d".to_owned());
+        extratlvs.insert(7629175, "68656c6c6f".to_owned());
+        let options = KeySendOptions {
+            extratlvs: Some(extratlvs),
+            ..Default::default()
+        };
+        rpc.keysend(destination, 10000, options).unwrap();
+        assert_eq!(
+            mock.requests()[0].params,
+            json!({
+                "destination": destination,
+                "amount_msat": 10000,
+                "extratlvs": {"7629169": "7b7d", "7629175": "68656c6c6f"}
+            })
+        );
+
+        for (tlv_type, payload) in [(7629168, "7b7d"), (7629169, "7b7"), (7629169, "zz")].iter() {
+            let mut extratlvs = BTreeMap::new();
+            extratlvs.insert(*tlv_type, payload.to_string());
+            let options = KeySendOptions {
+                extratlvs: Some(extratlvs),
+                ..Default::default()
+            };
+            match rpc.keysend(destination, 10000, options) {
+                Err(Error::InvalidInput(_)) => {}
+                other => panic!("expected invalid input, got {:?}", other),
+            }
+        }
+        assert_eq!(mock.requests().len(), 1);
+
+        // The map goes after routehints by position, like the object when sent by name
+        rpc.set_param_style(ParamStyle::Positional);
+        let mut extratlvs = BTreeMap::new();
+        extratlvs.insert(7629169, "7b7d".to_owned());
+        let options = KeySendOptions {
+            label: Some("boost".to_owned()),
+            extratlvs: Some(extratlvs),
+            ..Default::default()
+        };
+        rpc.keysend(destination, 10000, options).unwrap();
+        assert_eq!(
+            mock.requests()[1].params,
+            json!([
                 destination,
-                satoshi,
-                feerate,
-            },
+                10000,
+                "boost",
+                null,
+                null,
+                null,
+                null,
+                null,
+                {"7629169": "7b7d"}
+            ])
+        );
+    }
+
+    #[test]
+    fn capabilities_from_help() {
+        let mock = MockTransport::new();
+        mock.push_result(
+            "help",
+            json!({
+                "help": [
+                    {
+                        "command": "connect id [host] [port]",
+                        "category": "network",
+                        "description": "Connect to {id} at {host} (which can end in ':port' if not default)."
+                    },
+                    {
+                        "command": "splice_init",
+                        "category": "channels",
+                        "description": "Init a channel splice"
+                    },
+                    {
+                        "command": "pay",
+                        "category": "payment",
+                        "description": "Send payment specified by {bolt11}"
+                    }
+                ],
+                "format-hint": "simple"
+            }),
         )
+        .unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        let capabilities = rpc.capabilities().unwrap();
+        assert!(capabilities.supports("connect"));
+        assert!(capabilities.supports("splice_init"));
+        assert!(!capabilities.supports("renepay"));
+        assert_eq!(capabilities.commands().count(), 3);
+        assert_eq!(mock.requests()[0].params, json!({}));
+
+        // Cached, so no second 'help'
+        assert_eq!(rpc.capabilities().unwrap(), capabilities);
+        assert_eq!(mock.requests().len(), 1);
+
+        let renepay = json!({
+            "command": "renepay invstring",
+            "category": "payment",
+            "description": "Send payment specified by {invstring}"
+        });
+        mock.push_result("help", json!({ "help": [renepay] }))
+            .unwrap();
+        assert!(rpc.refresh_capabilities().unwrap().supports("renepay"));
+        assert!(rpc.capabilities().unwrap().supports("renepay"));
+        assert_eq!(mock.requests().len(), 2);
+
+        // Stopping a plugin forgets the cache too
+        let stopped = json!({"command": "stop", "result": "Successfully stopped renepay."});
+        mock.push_result("plugin", stopped).unwrap();
+        mock.push_result("help", json!({"help": []})).unwrap();
+        rpc.plugin_stop("renepay").unwrap();
+        assert!(!rpc.capabilities().unwrap().supports("renepay"));
+        assert_eq!(mock.requests()[3].method, "help");
     }
 
-    /// Get a new {bech32, p2sh-segwit} address to fund a channel (default is bech32).
-    pub fn newaddr(&mut self, addresstype: Option<String>) -> Result<responses::NewAddr, Error> {
-        self.call("newaddr", requests::NewAddr { addresstype })
+    #[test]
+    fn invoice_rejects_negative() {
+        let mock = MockTransport::new();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        for (msatoshi, expiry) in [(-1, None), (1000, Some(-3600))].iter() {
+            match rpc.invoice(*msatoshi, "l".to_owned(), "d".to_owned(), *expiry) {
+                Err(Error::InvalidInput(_)) => {}
+                other => panic!("expected invalid input, got {:?}", other),
+            }
+        }
+        assert!(mock.requests().is_empty());
     }
 
-    /// Shut down the lightningd process.
-    pub fn stop(&mut self) -> Result<responses::Stop, Error> {
-        self.call("stop", requests::Stop {})
+    #[test]
+    fn txprepare_outputs() {
+        let mock = MockTransport::new();
+        let prepared =
+            json!({"psbt": "cHNidP8BAF4CAAAA", "unsigned_tx": "0200000001", "txid": "ab12"});
+        mock.push_result("txprepare", prepared).unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        let outputs = [
+            ("bcrt1qxyz".to_owned(), "10000sat".to_owned()),
+            ("bcrt1qabc".to_owned(), "all".to_owned()),
+        ];
+        let tx = rpc.txprepare(&outputs, Some("normal"), None, None).unwrap();
+        assert_eq!(tx.txid, "ab12");
+        assert_eq!(
+            mock.requests()[0].params,
+            json!({
+                "outputs": [{"bcrt1qxyz": "10000sat"}, {"bcrt1qabc": "all"}],
+                "feerate": "normal"
+            })
+        );
+        for invalid in ["ab12", "ab12:x"].iter() {
+            match rpc.txprepare(&outputs, None, None, Some(&[invalid.to_string()])) {
+                Err(Error::InvalidInput(_)) => {}
+                other => panic!("expected invalid input, got {:?}", other),
+            }
+        }
+        assert_eq!(mock.requests().len(), 1);
     }
 }
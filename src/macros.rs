@@ -0,0 +1,152 @@
+// Rust JSON-RPC Library
+// Written by
+//     Wladimir J. van der Laan <laanwj@gmail.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+//! Internal macros, and the exported `params!`
+
+/// Define an enum for a string field with a known set of values. Values that this version of
+/// the crate does not know about are kept in an `Unknown` variant instead of failing
+/// deserialization, as lightningd adds new ones from time to time.
+macro_rules! string_enum {
+    (
+        $(#[$attr:meta])*
+        pub enum $name:ident {
+            $($(#[$vattr:meta])* $variant:ident => $value:literal,)*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$vattr])* $variant,)*
+            /// A value not known to this version of the crate
+            Unknown(String),
+        }
+
+        impl $name {
+            /// Return the string representation used by lightningd
+            pub fn as_str(&self) -> &str {
+                match *self {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(ref s) => s,
+                }
+            }
+        }
+
+        impl<'a> From<&'a str> for $name {
+            fn from(s: &'a str) -> $name {
+                match s {
+                    $($value => $name::$variant,)*
+                    _ => $name::Unknown(s.to_owned()),
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl ::serde::Serialize for $name {
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> ::serde::Deserialize<'de> for $name {
+            fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<$name, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                Ok($name::from(&s[..]))
+            }
+        }
+    };
+}
+
+/// Log at debug level through the `log` crate, if the `logging` feature is enabled. Without it
+/// the arguments are still type checked, but never evaluated.
+#[cfg(feature = "logging")]
+macro_rules! log_debug {
+    ($($arg:tt)*) => { log::debug!($($arg)*) };
+}
+
+#[cfg(not(feature = "logging"))]
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+/// Log at trace level through the `log` crate, if the `logging` feature is enabled, see
+/// `log_debug!`
+#[cfg(feature = "logging")]
+macro_rules! log_trace {
+    ($($arg:tt)*) => { log::trace!($($arg)*) };
+}
+
+#[cfg(not(feature = "logging"))]
+macro_rules! log_trace {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+/// Build named parameters for `LightningRPC::call` from `key => value` pairs
+///
+/// Values can be anything that implements `Serialize`. Keys whose value serializes to `null`,
+/// such as `None`, are left out, so optional parameters can be passed as they are. Evaluates to
+/// a `Result<serde_json::Value, Error>`, failing with `Error::Json` if a value cannot be
+/// converted to JSON, such as a map with keys that are not strings.
+///
+/// ```
+/// # use clightningrpc::params;
+/// let label: Option<&str> = None;
+/// let params = params! {
+///     "id" => "02aa",
+///     "msatoshi" => 1000,
+///     "label" => label,
+///     "exclude" => vec!["103x1x0/0"],
+/// }?;
+/// assert_eq!(
+///     params,
+///     serde_json::json!({"id": "02aa", "msatoshi": 1000, "exclude": ["103x1x0/0"]})
+/// );
+/// # Ok::<(), clightningrpc::Error>(())
+/// ```
+#[macro_export]
+macro_rules! params {
+    ($($key:expr => $value:expr),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut params = $crate::__private::Map::new();
+        #[allow(unused_mut)]
+        let mut error: ::std::option::Option<$crate::__private::JsonError> = None;
+        $(
+            if error.is_none() {
+                match $crate::__private::to_value($value) {
+                    Ok(value) => {
+                        if !value.is_null() {
+                            params.insert(::std::string::String::from($key), value);
+                        }
+                    }
+                    Err(e) => error = Some(e),
+                }
+            }
+        )*
+        match error {
+            Some(e) => Err($crate::Error::from(e)),
+            None => Ok($crate::__private::Value::Object(params)),
+        }
+    }};
+}
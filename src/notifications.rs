@@ -0,0 +1,292 @@
+// Rust JSON-RPC Library
+// Written by
+//     Wladimir J. van der Laan <laanwj@gmail.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+//! Typed notifications
+//!
+//! `Notification::typed` turns notifications into a `TypedNotification` for the kinds this crate
+//! knows about.
+//!
+//! lightningd only sends RPC clients `message` and `progress` notifications, and only for
+//! commands sent on the same connection after notifications were enabled on it with
+//! `notifications {"enable": true}`. As calls block until their response, the notifications are
+//! queued meanwhile and can be read from `client::Client::queued_notifications` afterwards:
+//!
+//! ```no_run
+//! use clightningrpc::client;
+//! use clightningrpc::notifications::TypedNotification;
+//! use serde_json::json;
+//!
+//! let mut client = client::Client::new("lightning-rpc");
+//! let enable = client.build_request("notifications".to_owned(), json!({"enable": true}));
+//! client.send_request(&enable)?.check_error()?;
+//! // A plugin command that reports how far it got
+//! let request = client.build_request("myplugin-import".to_owned(), json!({}));
+//! let response = client.send_request(&request)?;
+//! for notification in client.queued_notifications() {
+//!     match notification.typed()? {
+//!         TypedNotification::Progress(p) if p.id == request.id => {
+//!             println!("{} of {}", p.num + 1, p.total)
+//!         }
+//!         TypedNotification::Message(m) => println!("{}: {}", m.level, m.message),
+//!         _ => {}
+//!     }
+//! }
+//! response.check_error()?;
+//! # Ok::<(), clightningrpc::Error>(())
+//! ```
+//!
+//! Other topics, such as `pay_part_start` and `pay_part_end` for the parts of a payment the pay
+//! plugin tries, are only sent to plugins that subscribe to them. A plugin can decode the
+//! notifications it reads from lightningd in the same way.
+#![allow(missing_docs)]
+
+use crate::common;
+use crate::error::{self, Error};
+use crate::Notification;
+use serde_json::Value;
+
+/// 'message' notification, sent for a command issued on the same connection
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Message {
+    /// Id of the request this is about
+    pub id: Value,
+    /// "debug", "info", "warn" or "error"
+    pub level: String,
+    pub message: String,
+}
+
+/// Sub-structure for the stage of a 'progress' notification
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProgressStage {
+    pub num: u64,
+    pub total: u64,
+}
+
+/// 'progress' notification, sent for a command issued on the same connection
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Progress {
+    /// Id of the request this is about
+    pub id: Value,
+    /// Steps done, counting from 0, of {total}
+    pub num: u64,
+    pub total: u64,
+    /// Set for commands that go through several stages, each with its own steps
+    pub stage: Option<ProgressStage>,
+}
+
+/// Sub-structure for hops in 'pay_part_start'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PayPartHop {
+    pub next_node: String,
+    pub short_channel_id: common::ShortChannelId,
+    pub direction: u8,
+    /// Amount entering the channel, including the fees of the following hops
+    pub channel_in_msat: common::MSat,
+    /// Amount leaving the channel
+    pub channel_out_msat: common::MSat,
+}
+
+/// 'pay_part_start' notification, sent to subscribed plugins when the pay plugin tries a part of a payment
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PayPartStart {
+    pub payment_hash: String,
+    /// Identifies the payment attempt, together with {payment_hash}
+    pub groupid: u64,
+    pub partid: u64,
+    pub total_payment_msat: common::MSat,
+    /// Amount sent in this part
+    pub attempt_msat: common::MSat,
+    pub hops: Vec<PayPartHop>,
+}
+
+string_enum! {
+    /// Outcome of a part in 'pay_part_end'
+    pub enum PayPartStatus {
+        /// The part reached the destination
+        Success => "success",
+        /// The part failed, the pay plugin may try another route
+        Failure => "failure",
+    }
+}
+
+/// 'pay_part_end' notification, sent to subscribed plugins when a part of a payment succeeded or failed
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PayPartEnd {
+    pub status: PayPartStatus,
+    /// Seconds since the part was started
+    pub duration: f64,
+    pub payment_hash: String,
+    pub groupid: u64,
+    pub partid: u64,
+    /// The fields below are only set on failure
+    pub failed_msg: Option<String>,
+    pub failed_node_id: Option<String>,
+    pub failed_short_channel_id: Option<common::ShortChannelId>,
+    pub failed_direction: Option<u8>,
+    pub error_code: Option<i32>,
+    pub error_message: Option<String>,
+}
+
+/// A notification decoded by `Notification::typed`
+#[derive(Debug, Clone)]
+pub enum TypedNotification {
+    Message(Message),
+    Progress(Progress),
+    PayPartStart(PayPartStart),
+    PayPartEnd(PayPartEnd),
+    /// A notification of a kind not known to this crate, as received
+    Other(Notification),
+}
+
+impl Notification {
+    /// Decode the notification if it is of a kind known to this crate
+    ///
+    /// Plugins may send the payload as is or wrapped in an object with the notification's name
+    /// as only key, both are accepted. Fails with `Error::Deserialization` if a known
+    /// notification does not have the expected fields.
+    pub fn typed(self) -> Result<TypedNotification, Error> {
+        let payload = || match self.params.get(&self.method) {
+            Some(inner) if self.params.as_object().is_some_and(|obj| obj.len() == 1) => inner,
+            _ => &self.params,
+        };
+        match &self.method[..] {
+            "message" => error::from_value(payload()).map(TypedNotification::Message),
+            "progress" => error::from_value(payload()).map(TypedNotification::Progress),
+            "pay_part_start" => error::from_value(payload()).map(TypedNotification::PayPartStart),
+            "pay_part_end" => error::from_value(payload()).map(TypedNotification::PayPartEnd),
+            _ => Ok(TypedNotification::Other(self)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pay_part_notifications() {
+        let start: Notification = serde_json::from_str(
+            r#"{
+                "jsonrpc": "2.0",
+                "method": "pay_part_start",
+                "params": {
+                    "pay_part_start": {
+                        "payment_hash": "f56e0ba0e495ae7b4d6fa4c0cc9b2ffe3e1220a18a6bd4eaee2a2c1355bfb220",
+                        "groupid": 1,
+                        "partid": 2,
+                        "total_payment_msat": 500000000,
+                        "attempt_msat": 250000000,
+                        "hops": [
+                            {
+                                "next_node": "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59",
+                                "short_channel_id": "103x1x0",
+                                "direction": 1,
+                                "channel_in_msat": 250002750,
+                                "channel_out_msat": 250000000
+                            }
+                        ]
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        match start.typed().unwrap() {
+            TypedNotification::PayPartStart(part) => {
+                assert_eq!((part.groupid, part.partid), (1, 2));
+                assert_eq!(part.attempt_msat, common::MSat(250_000_000));
+                assert_eq!(part.hops[0].short_channel_id.to_string(), "103x1x0");
+                assert_eq!(part.hops[0].channel_in_msat, common::MSat(250_002_750));
+            }
+            other => panic!("expected pay_part_start, got {:?}", other),
+        }
+
+        // Unwrapped payload
+        let end: Notification = serde_json::from_str(
+            r#"{
+                "jsonrpc": "2.0",
+                "method": "pay_part_end",
+                "params": {
+                    "status": "failure",
+                    "duration": 0.371,
+                    "payment_hash": "f56e0ba0e495ae7b4d6fa4c0cc9b2ffe3e1220a18a6bd4eaee2a2c1355bfb220",
+                    "groupid": 1,
+                    "partid": 2,
+                    "failed_msg": "1007",
+                    "failed_node_id": "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59",
+                    "failed_short_channel_id": "103x1x0",
+                    "failed_direction": 1,
+                    "error_code": 204,
+                    "error_message": "WIRE_TEMPORARY_CHANNEL_FAILURE"
+                }
+            }"#,
+        )
+        .unwrap();
+        match end.typed().unwrap() {
+            TypedNotification::PayPartEnd(part) => {
+                assert_eq!(part.status, PayPartStatus::Failure);
+                assert_eq!(part.error_code, Some(204));
+                assert_eq!(part.failed_short_channel_id.unwrap().block(), 103);
+            }
+            other => panic!("expected pay_part_end, got {:?}", other),
+        }
+
+        let progress: Notification = serde_json::from_str(
+            r#"{
+                "jsonrpc": "2.0",
+                "method": "progress",
+                "params": {"id": 7, "num": 2, "total": 10, "stage": {"num": 0, "total": 2}}
+            }"#,
+        )
+        .unwrap();
+        match progress.typed().unwrap() {
+            TypedNotification::Progress(p) => {
+                assert_eq!((p.id, p.num, p.total), (Value::from(7), 2, 10));
+                assert_eq!(p.stage.unwrap().total, 2);
+            }
+            other => panic!("expected progress, got {:?}", other),
+        }
+        let message = Notification {
+            method: "message".to_owned(),
+            params: serde_json::json!({"id": "cli:pay#1", "level": "info", "message": "trying"}),
+            jsonrpc: Some("2.0".to_owned()),
+        };
+        match message.typed().unwrap() {
+            TypedNotification::Message(m) => {
+                assert_eq!((&m.level[..], &m.message[..]), ("info", "trying"))
+            }
+            other => panic!("expected message, got {:?}", other),
+        }
+
+        let log = Notification {
+            method: "log".to_owned(),
+            params: serde_json::json!({"level": "info", "message": "hello"}),
+            jsonrpc: Some("2.0".to_owned()),
+        };
+        match log.clone().typed().unwrap() {
+            TypedNotification::Other(other) => assert_eq!(other, log),
+            other => panic!("expected other notification, got {:?}", other),
+        }
+
+        let broken = Notification {
+            method: "pay_part_end".to_owned(),
+            params: serde_json::json!({"status": "success"}),
+            jsonrpc: None,
+        };
+        match broken.typed() {
+            Err(Error::Deserialization { expected_type, .. }) => {
+                assert!(expected_type.ends_with("PayPartEnd"))
+            }
+            other => panic!("expected deserialization error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,264 @@
+// Rust JSON-RPC Library
+// Written by
+//     Wladimir J. van der Laan <laanwj@gmail.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+//! A pool of connections for use from multiple threads
+//!
+//! A `LightningRPC` holds a single connection and is used through `&mut self`. Instead of
+//! sharing one behind a `Mutex`, threads can share a `LightningRPCPool`, which hands out one of
+//! up to `size` connections to the same socket for the duration of a call.
+//!
+//! ```no_run
+//! use clightningrpc::pool::LightningRPCPool;
+//! use std::thread;
+//!
+//! let pool = LightningRPCPool::new("lightning-rpc", 4);
+//! let handles: Vec<_> = (0..8)
+//!     .map(|_| {
+//!         let pool = pool.clone();
+//!         thread::spawn(move || pool.get().getinfo().map(|info| info.blockheight))
+//!     })
+//!     .collect();
+//! for handle in handles {
+//!     println!("{:?}", handle.join().unwrap());
+//! }
+//! ```
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::lightningrpc::LightningRPC;
+
+struct State {
+    /// Connections that are not in use
+    idle: Vec<LightningRPC>,
+    /// Number of connections, in use or idle
+    open: usize,
+}
+
+struct Shared {
+    sockpath: PathBuf,
+    size: usize,
+    state: Mutex<State>,
+    returned: Condvar,
+}
+
+/// A pool of connections to a lightningd RPC socket
+///
+/// Clones share the same connections. Connections are opened when needed, up to the size of the
+/// pool, after which `get` waits for one to be returned.
+#[derive(Clone)]
+pub struct LightningRPCPool {
+    shared: Arc<Shared>,
+}
+
+impl LightningRPCPool {
+    /// Create a pool of at most {size} connections, at least 1, to the UNIX socket {sockpath}.
+    ///
+    /// No connection is opened until the first call.
+    pub fn new<P: AsRef<Path>>(sockpath: P, size: usize) -> LightningRPCPool {
+        LightningRPCPool {
+            shared: Arc::new(Shared {
+                sockpath: sockpath.as_ref().to_path_buf(),
+                size: size.max(1),
+                state: Mutex::new(State {
+                    idle: Vec::new(),
+                    open: 0,
+                }),
+                returned: Condvar::new(),
+            }),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.shared.state.lock().unwrap()
+    }
+
+    /// Borrow a connection, waiting for one to become available if all are in use.
+    ///
+    /// The connection goes back to the pool when the guard is dropped, unless it was dropped
+    /// after an error, for example because the daemon restarted; a new one is opened in its place
+    /// when needed.
+    pub fn get(&self) -> PooledRPC<'_> {
+        let mut state = self.state();
+        loop {
+            if let Some(rpc) = state.idle.pop() {
+                return PooledRPC {
+                    pool: self,
+                    rpc: Some(rpc),
+                };
+            }
+            if state.open < self.shared.size {
+                state.open += 1;
+                return PooledRPC {
+                    pool: self,
+                    rpc: Some(LightningRPC::new(&self.shared.sockpath)),
+                };
+            }
+            state = self.shared.returned.wait(state).unwrap();
+        }
+    }
+
+    /// Make a call on one of the connections, see `LightningRPC::call`.
+    pub fn call<P: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> Result<R, Error> {
+        self.get().call(method, params)
+    }
+
+    fn put_back(&self, rpc: LightningRPC) {
+        let mut state = self.state();
+        if rpc.is_connected() {
+            state.idle.push(rpc);
+        } else {
+            state.open -= 1;
+        }
+        self.shared.returned.notify_one();
+    }
+}
+
+impl fmt::Debug for LightningRPCPool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LightningRPCPool")
+            .field("sockpath", &self.shared.sockpath)
+            .field("size", &self.shared.size)
+            .finish()
+    }
+}
+
+/// A connection borrowed from a `LightningRPCPool`, see `LightningRPCPool::get`
+pub struct PooledRPC<'a> {
+    pool: &'a LightningRPCPool,
+    /// Always `Some` until dropped
+    rpc: Option<LightningRPC>,
+}
+
+impl<'a> Deref for PooledRPC<'a> {
+    type Target = LightningRPC;
+
+    fn deref(&self) -> &LightningRPC {
+        self.rpc.as_ref().unwrap()
+    }
+}
+
+impl<'a> DerefMut for PooledRPC<'a> {
+    fn deref_mut(&mut self) -> &mut LightningRPC {
+        self.rpc.as_mut().unwrap()
+    }
+}
+
+impl<'a> Drop for PooledRPC<'a> {
+    fn drop(&mut self) {
+        if let Some(rpc) = self.rpc.take() {
+            self.pool.put_back(rpc);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framing::JsonFramer;
+    use crate::Request;
+    use serde_json::{json, Value};
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    /// Answer 'getinfo' requests until the client hangs up
+    fn answer_getinfo(mut stream: UnixStream) {
+        let info = json!({
+            "id": "02aa",
+            "alias": "pool",
+            "color": "02aa00",
+            "num_peers": 0,
+            "num_pending_channels": 0,
+            "num_active_channels": 0,
+            "num_inactive_channels": 0,
+            "address": [],
+            "binding": [],
+            "version": "v23.08",
+            "blockheight": 110,
+            "network": "regtest",
+            "fees_collected_msat": 0,
+            "lightning-dir": "/tmp/l1/regtest"
+        });
+        let mut framer = JsonFramer::new();
+        let mut buf = [0u8; 1024];
+        loop {
+            while let Some(frame) = framer.next_frame() {
+                let request: Request = serde_json::from_slice(&frame).unwrap();
+                let response =
+                    crate::error::result_to_response(Ok(info.clone()), request.id.clone());
+                stream
+                    .write_all(&serde_json::to_vec(&response).unwrap())
+                    .unwrap();
+            }
+            match stream.read(&mut buf) {
+                Ok(0) | Err(_) => return,
+                Ok(n) => framer.push(&buf[..n]),
+            }
+        }
+    }
+
+    #[test]
+    fn concurrent_getinfo() {
+        let path = std::env::temp_dir().join(format!("clightningrpc-pool-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let accepted = connections.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                thread::spawn(move || answer_getinfo(stream.unwrap()));
+            }
+        });
+
+        let pool = LightningRPCPool::new(&path, 2);
+        let threads: Vec<_> = (0..6)
+            .map(|_| {
+                let pool = pool.clone();
+                thread::spawn(move || {
+                    for _ in 0..5 {
+                        assert_eq!(pool.get().getinfo().unwrap().blockheight, 110);
+                        let info: Value = pool.call("getinfo", json!({})).unwrap();
+                        assert_eq!(info["alias"], "pool");
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert!(connections.load(Ordering::SeqCst) <= 2);
+        let state = pool.state();
+        assert_eq!(state.idle.len(), state.open);
+        drop(state);
+
+        // A connection that failed is not returned to the pool
+        fs::remove_file(&path).unwrap();
+        let dead = LightningRPCPool::new(&path, 1);
+        assert!(dead.get().getinfo().is_err());
+        assert_eq!(dead.state().open, 0);
+    }
+}
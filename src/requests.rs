@@ -15,28 +15,43 @@
 //! Structures representing requests to API calls
 #![allow(missing_docs)]
 
-use common;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::common;
 
 /// 'getinfo' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct GetInfo {}
 
+/// Unit style for 'feerates' command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum FeerateStyle {
+    /// Satoshi per 1000 virtual bytes
+    #[serde(rename = "perkb")]
+    PerKb,
+    /// Satoshi per 1000 weight units
+    #[serde(rename = "perkw")]
+    PerKw,
+}
+
 /// 'feerates' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct FeeRates {
-    pub style: String,
+    pub style: FeerateStyle,
 }
 
 /// 'listnodes' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct ListNodes {
-    pub id: Option<String>,
+pub struct ListNodes<'a> {
+    pub id: Option<&'a str>,
 }
 
 /// 'listchannels' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct ListChannels {
-    pub short_channel_id: Option<String>,
+pub struct ListChannels<'a> {
+    pub short_channel_id: Option<&'a str>,
+    pub source: Option<&'a str>,
+    pub destination: Option<&'a str>,
 }
 
 /// 'help' command
@@ -47,81 +62,137 @@ pub struct Help {
 
 /// 'getlog' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct GetLog {
-    pub level: Option<String>,
+pub struct GetLog<'a> {
+    pub level: Option<&'a str>,
 }
 
 /// 'listconfigs' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct ListConfigs {
-    pub config: Option<String>,
+pub struct ListConfigs<'a> {
+    pub config: Option<&'a str>,
 }
 
 /// 'listpeers' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct ListPeers {
-    pub id: Option<String>,
-    pub level: Option<String>,
+pub struct ListPeers<'a> {
+    pub id: Option<&'a str>,
+    pub level: Option<&'a str>,
+}
+
+/// 'listpeerchannels' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListPeerChannels<'a> {
+    pub id: Option<&'a str>,
+}
+
+/// 'listclosedchannels' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListClosedChannels<'a> {
+    pub id: Option<&'a str>,
 }
 
 /// 'listinvoices' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct ListInvoices {
-    pub label: Option<String>,
+pub struct ListInvoices<'a> {
+    pub label: Option<&'a str>,
+    pub invstring: Option<&'a str>,
+    pub payment_hash: Option<&'a str>,
+    pub offer_id: Option<&'a str>,
+    pub index: Option<&'a str>,
+    pub start: Option<u64>,
+    pub limit: Option<u64>,
 }
 
 /// 'invoice' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Invoice {
-    pub msatoshi: i64,
+    pub amount_msat: u64,
     pub label: String,
     pub description: String,
-    pub expiry: Option<i64>,
+    pub expiry: Option<u64>,
+    pub fallbacks: Option<Vec<String>>,
+    pub preimage: Option<String>,
+    pub exposeprivatechannels: Option<bool>,
+    pub cltv: Option<u32>,
+    pub deschashonly: Option<bool>,
+}
+
+/// 'createinvoice' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CreateInvoice<'a> {
+    pub invstring: &'a str,
+    pub label: &'a str,
+    pub preimage: &'a str,
+}
+
+/// 'signinvoice' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SignInvoice<'a> {
+    pub invstring: &'a str,
 }
 
 /// 'delinvoice' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct DelInvoice {
-    pub label: String,
-    pub status: String,
+pub struct DelInvoice<'a> {
+    pub label: &'a str,
+    pub status: &'a str,
 }
 
 /// 'delexpiredinvoice' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DelExpiredInvoice {
-    pub maxexpirytime: Option<i64>,
+    pub maxexpirytime: Option<u64>,
 }
 
 /// 'autocleaninvoice' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AutoCleanInvoice {
-    pub cycle_seconds: Option<i64>,
-    pub expired_by: Option<i64>,
+    pub cycle_seconds: Option<u64>,
+    pub expired_by: Option<u64>,
 }
 
 /// 'waitanyinvoice' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct WaitAnyInvoice {
-    pub lastpay_index: Option<i64>,
+    pub lastpay_index: Option<u64>,
 }
 
 /// 'waitinvoice' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct WaitInvoice {
-    pub label: String,
+pub struct WaitInvoice<'a> {
+    pub label: &'a str,
+}
+
+/// 'waitblockheight' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WaitBlockHeight {
+    pub blockheight: u32,
+    pub timeout: Option<u32>,
+}
+
+/// 'wait' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Wait {
+    pub subsystem: common::WaitSubsystem,
+    pub indexname: common::WaitIndex,
+    pub nextvalue: u64,
 }
 
 /// 'pay' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Pay {
     pub bolt11: String,
-    pub msatoshi: Option<i64>,
-    pub description: Option<String>,
+    pub amount_msat: Option<u64>,
+    pub label: Option<String>,
     pub riskfactor: Option<f64>,
     pub maxfeepercent: Option<f64>,
-    pub exemptfee: Option<i64>,
-    pub retry_for: Option<i64>,
-    pub maxdelay: Option<i64>,
+    pub retry_for: Option<u64>,
+    pub maxdelay: Option<u64>,
+    pub exemptfee: Option<u64>,
+    pub localinvreqid: Option<String>,
+    pub exclude: Option<Vec<String>>,
+    pub maxfee: Option<u64>,
+    pub description: Option<String>,
 }
 
 /// 'sendpay' command
@@ -135,9 +206,33 @@ pub struct SendPay {
 
 /// 'waitsendpay' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct WaitSendPay {
-    pub payment_hash: String,
-    pub timeout: i64,
+pub struct WaitSendPay<'a> {
+    pub payment_hash: &'a str,
+    pub timeout: Option<u64>,
+}
+
+/// 'listsendpays' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListSendPays<'a> {
+    pub bolt11: Option<&'a str>,
+    pub payment_hash: Option<&'a str>,
+}
+
+/// 'delpay' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DelPay<'a> {
+    pub payment_hash: &'a str,
+    pub status: &'a str,
+    pub partid: Option<u64>,
+    pub groupid: Option<u64>,
+}
+
+/// 'listpays' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListPays<'a> {
+    pub bolt11: Option<&'a str>,
+    pub payment_hash: Option<&'a str>,
+    pub status: Option<&'a str>,
 }
 
 /// 'listpayments' command
@@ -149,78 +244,571 @@ pub struct ListPayments {
 
 /// 'decodepay' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct DecodePay {
-    pub bolt11: String,
-    pub description: Option<String>,
+pub struct DecodePay<'a> {
+    pub bolt11: &'a str,
+    pub description: Option<&'a str>,
+}
+
+/// 'decode' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Decode<'a> {
+    pub string: &'a str,
 }
 
 /// 'getroute' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct GetRoute {
-    pub id: String,
-    pub msatoshi: i64,
+pub struct GetRoute<'a> {
+    pub id: &'a str,
+    pub amount_msat: u64,
     pub riskfactor: f64,
-    pub cltv: Option<i64>,
-    pub fromid: Option<String>,
+    pub cltv: Option<u64>,
+    pub fromid: Option<&'a str>,
     pub fuzzpercent: Option<f64>,
-    pub seed: Option<String>,
+    pub exclude: Option<Vec<String>>,
+    pub maxhops: Option<u64>,
 }
 
-/// 'connect' command
+/// A hop for 'createonion'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OnionHop {
+    /// Node id of the hop
+    pub pubkey: String,
+    /// Hex-encoded payload for the hop, including its length prefix
+    pub payload: String,
+}
+
+/// The first hop for 'sendonion', which the onion itself does not describe
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct Connect {
+pub struct FirstHop {
     pub id: String,
-    pub host: Option<String>,
+    pub amount_msat: u64,
+    pub delay: u64,
+}
+
+/// 'createonion' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CreateOnion<'a> {
+    pub hops: Vec<OnionHop>,
+    pub assocdata: &'a str,
+    pub session_key: Option<&'a str>,
+}
+
+/// 'sendonion' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SendOnion<'a> {
+    pub onion: &'a str,
+    pub first_hop: FirstHop,
+    pub payment_hash: &'a str,
+    pub label: Option<String>,
+    pub shared_secrets: Option<Vec<String>>,
+    pub partid: Option<u64>,
+    pub bolt11: Option<String>,
+    pub amount_msat: Option<u64>,
+    pub destination: Option<String>,
+}
+
+/// 'connect' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Connect<'a> {
+    pub id: &'a str,
+    pub host: Option<&'a str>,
+    pub port: Option<u16>,
 }
 
 /// 'disconnect' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct Disconnect {
-    pub id: String,
+pub struct Disconnect<'a> {
+    pub id: &'a str,
+    pub force: bool,
 }
 
 /// 'fundchannel' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct FundChannel {
+pub struct FundChannel<'a> {
+    pub id: &'a str,
+    pub amount: &'a str,
+    pub feerate: Option<&'a str>,
+    pub announce: Option<bool>,
+    pub minconf: Option<u32>,
+    pub utxos: Option<Vec<String>>,
+    pub push_msat: Option<u64>,
+    pub close_to: Option<&'a str>,
+}
+
+/// Sub-structure for destinations in 'multifundchannel'
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct MultiFundDest {
+    /// Peer id, optionally with "@host:port" to connect to it first
     pub id: String,
-    pub satoshi: i64,
-    pub feerate: Option<i64>,
+    /// Amount to fund the channel with, e.g. "100000sat" or "all"
+    pub amount: String,
+    // Unset fields are left out here, only top-level parameters are filtered by the client
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub announce: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub push_msat: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub close_to: Option<String>,
+}
+
+/// 'multifundchannel' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MultiFundChannel<'a> {
+    pub destinations: Vec<MultiFundDest>,
+    pub feerate: Option<&'a str>,
+    pub minconf: Option<u32>,
+    pub utxos: Option<Vec<String>>,
+    pub minchannels: Option<u32>,
+}
+
+/// 'fundchannel_start' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FundChannelStart<'a> {
+    pub id: &'a str,
+    pub amount: u64,
+    pub feerate: Option<&'a str>,
+    pub announce: Option<bool>,
+    pub close_to: Option<&'a str>,
+    pub push_msat: Option<u64>,
+}
+
+/// 'fundchannel_complete' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FundChannelComplete<'a> {
+    pub id: &'a str,
+    pub txid: &'a str,
+    pub txout: u32,
+}
+
+/// 'fundchannel_cancel' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FundChannelCancel<'a> {
+    pub id: &'a str,
+}
+
+/// 'splice_init' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SpliceInit<'a> {
+    pub channel_id: &'a str,
+    pub relative_amount: i64,
+    pub initialpsbt: Option<&'a str>,
+    pub feerate_per_kw: Option<u32>,
+}
+
+/// 'splice_update' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SpliceUpdate<'a> {
+    pub channel_id: &'a str,
+    pub psbt: &'a str,
+}
+
+/// 'splice_signed' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SpliceSigned<'a> {
+    pub channel_id: &'a str,
+    pub psbt: &'a str,
 }
 
 /// 'close' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct Close {
-    pub id: String,
-    pub force: Option<bool>,
-    pub timeout: Option<i64>,
+pub struct Close<'a> {
+    pub id: &'a str,
+    pub unilateraltimeout: Option<u32>,
+    pub destination: Option<&'a str>,
+    pub fee_negotiation_step: Option<&'a str>,
+}
+
+/// 'setchannel' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SetChannel<'a> {
+    pub id: &'a str,
+    pub feebase: Option<u64>,
+    pub feeppm: Option<u32>,
+    pub htlcmin: Option<u64>,
+    pub htlcmax: Option<u64>,
+    pub enforcedelay: Option<u32>,
 }
 
 /// 'ping' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct Ping {
-    pub peerid: String,
-    pub len: Option<i64>,
-    pub pongbytes: Option<i64>,
+pub struct Ping<'a> {
+    pub id: &'a str,
+    pub len: u16,
+    pub pongbytes: u16,
+}
+
+/// 'listforwards' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListForwards {}
+
+/// 'listhtlcs' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListHtlcs<'a> {
+    pub id: Option<&'a str>,
 }
 
 /// 'listfunds' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct ListFunds {}
+pub struct ListFunds {
+    pub spent: Option<bool>,
+}
+
+/// 'listtransactions' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListTransactions {}
 
 /// 'withdraw' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct Withdraw {
-    pub destination: String,
-    pub satoshi: i64,
-    pub feerate: Option<i64>,
+pub struct Withdraw<'a> {
+    pub destination: &'a str,
+    pub satoshi: &'a str,
+    pub feerate: Option<&'a str>,
+    pub minconf: Option<u32>,
+    pub utxos: Option<Vec<String>>,
+}
+
+/// 'txprepare' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TxPrepare<'a> {
+    /// List of single-entry `{address: amount}` objects
+    pub outputs: Vec<HashMap<String, String>>,
+    pub feerate: Option<&'a str>,
+    pub minconf: Option<u32>,
+    pub utxos: Option<Vec<String>>,
+}
+
+/// 'txsend' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TxSend<'a> {
+    pub txid: &'a str,
+}
+
+/// 'txdiscard' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TxDiscard<'a> {
+    pub txid: &'a str,
+}
+
+/// 'signpsbt' command
+#[derive(Debug, Clone, Serialize)]
+pub struct SignPsbt<'a> {
+    pub psbt: &'a str,
+    pub signonly: Option<&'a [u32]>,
+}
+
+/// 'sendpsbt' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SendPsbt<'a> {
+    pub psbt: &'a str,
+}
+
+/// 'setpsbtversion' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SetPsbtVersion<'a> {
+    pub psbt: &'a str,
+    pub version: u8,
+}
+
+/// 'reserveinputs' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReserveInputs<'a> {
+    pub psbt: &'a str,
+    pub exclusive: Option<bool>,
+    pub reserve: Option<u32>,
+}
+
+/// 'unreserveinputs' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UnreserveInputs<'a> {
+    pub psbt: &'a str,
+    pub reserve: Option<u32>,
+}
+
+/// 'fundpsbt' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FundPsbt<'a> {
+    pub satoshi: &'a str,
+    pub feerate: &'a str,
+    pub startweight: u32,
+    pub minconf: Option<u32>,
+    pub reserve: Option<u32>,
+    pub locktime: Option<u32>,
+    pub min_witness_weight: Option<u32>,
+    pub excess_as_change: Option<bool>,
+}
+
+/// 'utxopsbt' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UtxoPsbt<'a> {
+    pub satoshi: &'a str,
+    pub feerate: &'a str,
+    pub startweight: u32,
+    pub utxos: Vec<String>,
+    pub reserve: Option<u32>,
+    pub reservedok: Option<bool>,
+    pub locktime: Option<u32>,
+    pub min_witness_weight: Option<u32>,
+    pub excess_as_change: Option<bool>,
+}
+
+/// Address type for 'newaddr' command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum AddressType {
+    /// Native segwit address
+    #[serde(rename = "bech32")]
+    Bech32,
+    /// Segwit address wrapped in P2SH
+    #[serde(rename = "p2sh-segwit")]
+    P2shSegwit,
+    /// One address of each type, for the same key
+    #[serde(rename = "all")]
+    All,
 }
 
 /// 'newaddr' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct NewAddr {
-    pub addresstype: Option<String>,
+    pub addresstype: AddressType,
+}
+
+/// Subcommand for 'plugin' command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PluginSubcommand {
+    /// Start a plugin
+    #[serde(rename = "start")]
+    Start,
+    /// Stop a plugin
+    #[serde(rename = "stop")]
+    Stop,
+    /// Start all plugins in the plugin directories that are not running yet
+    #[serde(rename = "rescan")]
+    Rescan,
+    /// List the plugins
+    #[serde(rename = "list")]
+    List,
+}
+
+/// 'plugin' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Plugin<'a> {
+    pub subcommand: PluginSubcommand,
+    pub plugin: Option<&'a str>,
+}
+
+/// 'signmessage' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SignMessage<'a> {
+    pub message: &'a str,
+}
+
+/// 'checkmessage' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CheckMessage<'a> {
+    pub message: &'a str,
+    pub zbase: &'a str,
+    pub pubkey: Option<&'a str>,
+}
+
+/// 'getsharedsecret' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GetSharedSecret<'a> {
+    pub point: &'a str,
+}
+
+/// Sub-structure for the hops of route hints in 'keysend', leading to the destination
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HintHop {
+    pub id: String,
+    pub short_channel_id: common::ShortChannelId,
+    pub fee_base_msat: u64,
+    pub fee_proportional_millionths: u32,
+    pub cltv_expiry_delta: u16,
+}
+
+/// 'keysend' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct KeySend<'a> {
+    pub destination: &'a str,
+    pub amount_msat: u64,
+    pub label: Option<String>,
+    pub maxfeepercent: Option<f64>,
+    pub retry_for: Option<u64>,
+    pub maxdelay: Option<u64>,
+    pub exemptfee: Option<u64>,
+    pub routehints: Option<Vec<Vec<HintHop>>>,
+    /// Serialized as an object with the types as keys
+    pub extratlvs: Option<BTreeMap<u64, String>>,
+}
+
+/// 'renepay' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RenePay<'a> {
+    pub invstring: &'a str,
+    pub amount_msat: Option<u64>,
+    pub maxfee: Option<u64>,
+    pub maxdelay: Option<u64>,
+    pub retry_for: Option<u64>,
+    pub description: Option<String>,
+    pub label: Option<String>,
+}
+
+/// 'renepaystatus' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RenePayStatus<'a> {
+    pub invstring: Option<&'a str>,
+}
+
+/// 'offer' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Offer<'a> {
+    pub amount: &'a str,
+    pub description: &'a str,
+    pub issuer: Option<String>,
+    pub label: Option<String>,
+    pub quantity_max: Option<u64>,
+    pub absolute_expiry: Option<u64>,
+    pub recurrence: Option<String>,
+    pub recurrence_base: Option<u64>,
+    pub recurrence_paywindow: Option<String>,
+    pub recurrence_limit: Option<u64>,
+    pub single_use: Option<bool>,
+}
+
+/// 'fetchinvoice' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FetchInvoice<'a> {
+    pub offer: &'a str,
+    pub amount_msat: Option<u64>,
+    pub quantity: Option<u64>,
+    pub recurrence_counter: Option<u64>,
+    pub recurrence_start: Option<u64>,
+    pub recurrence_label: Option<String>,
+    pub timeout: Option<u64>,
+    pub payer_note: Option<String>,
+}
+
+/// How 'datastore' treats an existing entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum DataStoreMode {
+    /// Fail if the key exists, the default
+    #[serde(rename = "must-create")]
+    MustCreate,
+    /// Fail unless the key exists, and replace its value
+    #[serde(rename = "must-replace")]
+    MustReplace,
+    /// Create the key or replace its value
+    #[serde(rename = "create-or-replace")]
+    CreateOrReplace,
+    /// Fail unless the key exists, and append to its value
+    #[serde(rename = "must-append")]
+    MustAppend,
+    /// Create the key or append to its value
+    #[serde(rename = "create-or-append")]
+    CreateOrAppend,
+}
+
+/// 'datastore' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DataStore<'a> {
+    pub key: Vec<String>,
+    pub string: Option<&'a str>,
+    pub hex: Option<&'a str>,
+    pub mode: Option<DataStoreMode>,
+    pub generation: Option<u64>,
+}
+
+/// 'deldatastore' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DelDataStore {
+    pub key: Vec<String>,
+    pub generation: Option<u64>,
 }
 
+/// 'listdatastore' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListDataStore {
+    pub key: Option<Vec<String>>,
+}
+
+/// 'sql' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Sql<'a> {
+    pub query: &'a str,
+}
+
+/// 'listsqlschemas' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListSqlSchemas<'a> {
+    pub table: Option<&'a str>,
+}
+
+/// 'bkpr-listbalances' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BkprListBalances {}
+
+/// 'bkpr-listaccountevents' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BkprListAccountEvents<'a> {
+    pub account: Option<&'a str>,
+}
+
+/// 'bkpr-listincome' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BkprListIncome {
+    pub consolidate_fees: Option<bool>,
+    pub start_time: Option<u64>,
+    pub end_time: Option<u64>,
+}
+
+/// 'sendcustommsg' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SendCustomMsg<'a> {
+    pub node_id: &'a str,
+    pub msg: &'a str,
+}
+
+/// 'addgossip' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AddGossip<'a> {
+    pub message: &'a str,
+}
+
+/// 'staticbackup' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StaticBackup {}
+
+/// 'recoverchannel' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RecoverChannel {
+    pub scb: Vec<String>,
+}
+
+/// 'emergencyrecover' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EmergencyRecover {}
+
 /// 'stop' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Stop {}
+
+/// 'dev-rescan-outputs' command
+#[cfg(feature = "dev")]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DevRescanOutputs {}
+
+/// 'dev-forget-channel' command
+#[cfg(feature = "dev")]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DevForgetChannel<'a> {
+    pub id: &'a str,
+    pub short_channel_id: Option<&'a str>,
+    pub channel_id: Option<&'a str>,
+    pub force: Option<bool>,
+}
+
+/// 'dev-sign-last-tx' command
+#[cfg(feature = "dev")]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DevSignLastTx<'a> {
+    pub id: &'a str,
+}
@@ -14,19 +14,10 @@
 //
 #![allow(missing_docs)]
 //! Structures representing responses to API calls
+use serde_json::Value;
 use std::collections::HashMap;
-use strason::Json;
 
-use common;
-
-/// structure for network addresses
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct NetworkAddress {
-    #[serde(rename = "type")]
-    pub type_: String,
-    pub address: String,
-    pub port: String,
-}
+use crate::common;
 
 /// 'getinfo' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -34,27 +25,68 @@ pub struct GetInfo {
     pub id: String,
     pub alias: String,
     pub color: String,
-    pub address: Vec<NetworkAddress>,
-    pub binding: Vec<NetworkAddress>,
+    pub num_peers: u64,
+    pub num_pending_channels: u64,
+    pub num_active_channels: u64,
+    pub num_inactive_channels: u64,
+    /// Addresses announced to the network
+    pub address: Vec<common::NetAddress>,
+    /// Addresses listened on
+    pub binding: Vec<common::NetAddress>,
     pub version: String,
-    pub blockheight: i64,
-    pub network: String,
+    pub blockheight: u64,
+    pub network: common::Network,
+    pub fees_collected_msat: common::MSat,
+    #[serde(rename = "lightning-dir")]
+    pub lightning_dir: String,
+    /// Set while bitcoind is still syncing the blockchain
+    pub warning_bitcoind_sync: Option<String>,
+    /// Set while lightningd is still catching up with the blockchain
+    pub warning_lightningd_sync: Option<String>,
+    pub our_features: Option<OurFeatures>,
+}
+
+/// Sub-structure for 'getinfo' our_features, what we advertise in each context
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OurFeatures {
+    /// Sent to peers on connection
+    pub init: common::FeatureBits,
+    /// In our node announcement
+    pub node: Option<common::FeatureBits>,
+    /// In our channel announcements
+    pub channel: Option<common::FeatureBits>,
+    /// In our invoices
+    pub invoice: Option<common::FeatureBits>,
 }
 
+/// Sub-structure for 'feerates' perkb/perkw object
+///
+/// All fields are optional because lightningd leaves out estimates that bitcoind has not
+/// produced yet.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct FeeRatesInner {
-    pub urgent: i64,
-    pub normal: i64,
-    pub slow: i64,
-    pub min_acceptable: i64,
-    pub max_acceptable: i64,
+    pub opening: Option<u64>,
+    pub mutual_close: Option<u64>,
+    pub unilateral_close: Option<u64>,
+    pub delayed_to_us: Option<u64>,
+    pub htlc_resolution: Option<u64>,
+    pub penalty: Option<u64>,
+    pub min_acceptable: Option<u64>,
+    pub max_acceptable: Option<u64>,
+    // pre-0.7 daemons
+    pub urgent: Option<u64>,
+    pub normal: Option<u64>,
+    pub slow: Option<u64>,
 }
 
+/// Sub-structure for 'feerates' onchain_fee_estimates object
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct FeeRatesOnchain {
-    pub opening_channel_satoshis: i64,
-    pub mutual_close_satoshis: i64,
-    pub unilateral_close_satoshis: i64,
+    pub opening_channel_satoshis: Option<u64>,
+    pub mutual_close_satoshis: Option<u64>,
+    pub unilateral_close_satoshis: Option<u64>,
+    pub htlc_timeout_satoshis: Option<u64>,
+    pub htlc_success_satoshis: Option<u64>,
 }
 
 /// 'feerates' command
@@ -63,17 +95,21 @@ pub struct FeeRates {
     pub perkb: Option<FeeRatesInner>,
     pub perkw: Option<FeeRatesInner>,
     pub onchain_fee_estimates: Option<FeeRatesOnchain>,
+    pub warning_missing_feerates: Option<String>,
 }
 
 /// Sub-structure for 'listnodes' items
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ListNodesItem {
     pub nodeid: String,
+    /// The fields below are absent until a node announcement was received
     pub alias: Option<String>,
     pub color: Option<String>,
-    pub last_timestamp: Option<i64>,
-    pub global_features: Option<String>,
-    pub addresses: Option<Vec<NetworkAddress>>,
+    pub last_timestamp: Option<u64>,
+    /// Feature bits, as hex
+    #[serde(alias = "global_features")]
+    pub features: Option<common::FeatureBits>,
+    pub addresses: Option<Vec<common::NetAddress>>,
 }
 
 /// 'listnodes' command
@@ -82,20 +118,27 @@ pub struct ListNodes {
     pub nodes: Vec<ListNodesItem>,
 }
 
-/// Sub-structure for 'listchannels' item
+/// Sub-structure for 'listchannels' item, one direction of a channel
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ListChannelsItem {
     pub source: String,
     pub destination: String,
-    pub short_channel_id: String,
+    pub short_channel_id: common::ShortChannelId,
+    /// 0 if {source} is the lesser node id, 1 otherwise
+    pub direction: u8,
     pub public: bool,
-    pub satoshis: i64,
-    pub flags: i64,
+    pub amount_msat: common::MSat,
+    pub message_flags: u8,
+    pub channel_flags: u8,
     pub active: bool,
-    pub last_update: i64,
-    pub base_fee_millisatoshi: i64,
-    pub fee_per_millionth: i64,
-    pub delay: i64,
+    pub last_update: u64,
+    #[serde(rename = "base_fee_millisatoshi")]
+    pub fee_base_msat: common::MSat,
+    pub fee_per_millionth: u32,
+    pub delay: u32,
+    pub htlc_minimum_msat: common::MSat,
+    pub htlc_maximum_msat: Option<common::MSat>,
+    pub features: common::FeatureBits,
 }
 
 /// 'listchannels' command
@@ -118,15 +161,61 @@ pub struct Help {
     pub verbose: Option<String>,
 }
 
-/// Sub-structure for 'getlog' item
+/// Sub-structure for messages in 'getlog' entries
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct LogEntry {
-    #[serde(rename = "type")]
-    pub type_: String,
-    pub num_skipped: Option<i64>,
-    pub time: Option<String>,
-    pub source: Option<String>,
-    pub log: Option<String>,
+pub struct LogMessage {
+    /// Seconds since the log was created, as a decimal string
+    pub time: String,
+    /// Peer the message is about, if any
+    pub node_id: Option<String>,
+    /// Subsystem that logged the message
+    pub source: String,
+    pub log: String,
+}
+
+/// Sub-structure for IO messages in 'getlog' entries
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LogIo {
+    /// Seconds since the log was created, as a decimal string
+    pub time: String,
+    /// Peer the message is about, if any
+    pub node_id: Option<String>,
+    /// Subsystem that logged the message
+    pub source: String,
+    pub log: String,
+    /// The bytes that were sent or received, as hex
+    pub data: Option<String>,
+}
+
+/// Sub-structure for 'getlog' item, depending on its `type`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum LogEntry {
+    /// Entries below the requested level that were left out
+    #[serde(rename = "SKIPPED")]
+    Skipped {
+        /// Number of entries left out
+        num_skipped: u64,
+    },
+    #[serde(rename = "BROKEN")]
+    Broken(LogMessage),
+    #[serde(rename = "UNUSUAL")]
+    Unusual(LogMessage),
+    #[serde(rename = "INFO")]
+    Info(LogMessage),
+    #[serde(rename = "DEBUG")]
+    Debug(LogMessage),
+    #[serde(rename = "TRACE")]
+    Trace(LogMessage),
+    /// Data received from a peer or subdaemon
+    #[serde(rename = "IO_IN")]
+    IoIn(LogIo),
+    /// Data sent to a peer or subdaemon
+    #[serde(rename = "IO_OUT")]
+    IoOut(LogIo),
+    /// An entry type not known to this version of the crate
+    #[serde(other)]
+    Unknown,
 }
 
 /// 'getlog' command
@@ -138,39 +227,180 @@ pub struct GetLog {
     pub log: Vec<LogEntry>,
 }
 
-/// 'listconfigs' command
-pub type ListConfigs = HashMap<String, Json>;
+/// 'listconfigs' command, a map from option name to value
+///
+/// Values are kept as JSON as their types differ per option. Since v23.08 lightningd reports
+/// options in a `configs` object, with each value in an object that also tells where it was set;
+/// `get` and the typed accessors look in both places.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ListConfigs(pub HashMap<String, Value>);
+
+impl ListConfigs {
+    /// Value of option {key}, `None` if it is not set
+    ///
+    /// Options that can be given several times, such as `log-level` or `plugin`, are returned
+    /// as a list of all their values.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        let value = match self.0.get(key) {
+            Some(value) => value,
+            None => self.0.get("configs")?.get(key)?,
+        };
+        match value {
+            // {"value_int": 1000, "source": "cmdline"} or
+            // {"values_str": ["a", "b"], "sources": ["cmdline", "cmdline"]}
+            Value::Object(obj) => obj
+                .iter()
+                .find(|(k, _)| k.starts_with("value_") || k.starts_with("values_"))
+                .map(|(_, v)| v),
+            value => Some(value),
+        }
+    }
+
+    fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(Value::as_str)
+    }
+
+    /// The chain lightningd runs on
+    pub fn network(&self) -> Option<common::Network> {
+        self.get_str("network").map(common::Network::from)
+    }
+
+    /// The node's alias
+    pub fn alias(&self) -> Option<&str> {
+        self.get_str("alias")
+    }
+
+    /// The node's color, as 6 hex characters
+    pub fn rgb(&self) -> Option<&str> {
+        self.get_str("rgb")
+    }
+
+    /// The base fee charged for forwarding, in msat
+    pub fn fee_base(&self) -> Option<u64> {
+        self.get("fee-base").and_then(Value::as_u64)
+    }
+
+    /// The proportional fee charged for forwarding, in millionths
+    pub fn fee_per_satoshi(&self) -> Option<u64> {
+        self.get("fee-per-satoshi").and_then(Value::as_u64)
+    }
+}
+
+string_enum! {
+    /// Direction of an HTLC in 'listpeers' and 'listhtlcs'
+    pub enum HtlcDirection {
+        /// Offered by the peer
+        In => "in",
+        /// Offered by us
+        Out => "out",
+    }
+}
+
+string_enum! {
+    /// Progress of an HTLC through the commitment protocol in 'listpeers' and 'listhtlcs'
+    ///
+    /// The first ten states are for HTLCs we offered, the others for HTLCs offered by the peer.
+    /// An HTLC is settled or failed once it reaches `SentRemoveAckRevocation` (ours) or
+    /// `RcvdRemoveAckRevocation` (theirs).
+    pub enum HtlcState {
+        SentAddHtlc => "SENT_ADD_HTLC",
+        SentAddCommit => "SENT_ADD_COMMIT",
+        RcvdAddRevocation => "RCVD_ADD_REVOCATION",
+        RcvdAddAckCommit => "RCVD_ADD_ACK_COMMIT",
+        SentAddAckRevocation => "SENT_ADD_ACK_REVOCATION",
+        RcvdRemoveHtlc => "RCVD_REMOVE_HTLC",
+        RcvdRemoveCommit => "RCVD_REMOVE_COMMIT",
+        SentRemoveRevocation => "SENT_REMOVE_REVOCATION",
+        SentRemoveAckCommit => "SENT_REMOVE_ACK_COMMIT",
+        RcvdRemoveAckRevocation => "RCVD_REMOVE_ACK_REVOCATION",
+        RcvdAddHtlc => "RCVD_ADD_HTLC",
+        RcvdAddCommit => "RCVD_ADD_COMMIT",
+        SentAddRevocation => "SENT_ADD_REVOCATION",
+        SentAddAckCommit => "SENT_ADD_ACK_COMMIT",
+        RcvdAddAckRevocation => "RCVD_ADD_ACK_REVOCATION",
+        SentRemoveHtlc => "SENT_REMOVE_HTLC",
+        SentRemoveCommit => "SENT_REMOVE_COMMIT",
+        RcvdRemoveRevocation => "RCVD_REMOVE_REVOCATION",
+        RcvdRemoveAckCommit => "RCVD_REMOVE_ACK_COMMIT",
+        SentRemoveAckRevocation => "SENT_REMOVE_ACK_REVOCATION",
+    }
+}
+
+/// Sub-structure for HTLCs in flight in 'listpeers' channels
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Htlc {
+    pub direction: HtlcDirection,
+    pub id: u64,
+    pub amount_msat: common::MSat,
+    /// Block height at which the HTLC times out
+    pub expiry: u32,
+    pub payment_hash: String,
+    pub state: HtlcState,
+}
 
 /// Sub-structure for channel in 'listpeers'
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Channel {
-    pub state: String,
-    pub owner: Option<String>,
-    pub short_channel_id: String,
+    pub state: common::ChannelState,
+    /// Absent until the funding transaction is confirmed
+    pub short_channel_id: Option<common::ShortChannelId>,
+    pub channel_id: String,
+    pub funding_txid: String,
+    pub to_us_msat: common::MSat,
+    pub total_msat: common::MSat,
+    /// Reserve the peer has to keep in the channel
+    pub their_reserve_msat: common::MSat,
+    /// Reserve we have to keep in the channel
+    pub our_reserve_msat: common::MSat,
+    /// How much we can send through the channel right now
+    pub spendable_msat: common::MSat,
+    /// How much we can receive through the channel right now
+    pub receivable_msat: common::MSat,
+    /// Our base fee for forwarding, absent while the channel is being opened
+    pub fee_base_msat: Option<common::MSat>,
+    /// Our proportional fee for forwarding, absent while the channel is being opened
+    pub fee_proportional_millionths: Option<u32>,
+    #[serde(default)]
+    pub htlcs: Vec<Htlc>,
+}
+
+string_enum! {
+    /// Reason a channel was closed in 'listclosedchannels'
+    pub enum CloseCause {
+        /// lightningd did not record the cause
+        Unspecified => "unknown",
+        /// We closed the channel, e.g. because of an internal error
+        Local => "local",
+        /// The channel was closed with the `close` command
+        User => "user",
+        /// The peer closed the channel
+        Remote => "remote",
+        /// The peer violated the protocol
+        Protocol => "protocol",
+        /// A commitment transaction was seen onchain
+        Onchain => "onchain",
+    }
+}
+
+/// Sub-structure for 'listclosedchannels' entries
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ClosedChannel {
+    /// Absent for some channels closed by old versions of lightningd
+    pub peer_id: Option<String>,
     pub channel_id: String,
+    pub short_channel_id: Option<common::ShortChannelId>,
     pub funding_txid: String,
-    pub msatoshi_to_us: i64,
-    pub msatoshi_to_us_min: i64,
-    pub msatoshi_to_us_max: i64,
-    pub msatoshi_total: i64,
-    pub dust_limit_satoshis: i64,
-    pub max_htlc_value_in_flight_msat: u64, // this exceeds what fits into i64
-    pub their_channel_reserve_satoshis: i64,
-    pub our_channel_reserve_satoshis: i64,
-    pub spendable_msatoshi: i64,
-    pub htlc_minimum_msat: i64,
-    pub their_to_self_delay: i64,
-    pub our_to_self_delay: i64,
-    pub max_accepted_htlcs: i64,
-    pub status: Vec<String>,
-    pub in_payments_offered: i64,
-    pub in_msatoshi_offered: i64,
-    pub in_payments_fulfilled: i64,
-    pub in_msatoshi_fulfilled: i64,
-    pub out_payments_offered: i64,
-    pub out_msatoshi_offered: i64,
-    pub out_payments_fulfilled: i64,
-    pub out_msatoshi_fulfilled: i64,
+    pub total_msat: common::MSat,
+    pub final_to_us_msat: common::MSat,
+    /// "local" or "remote", if known
+    pub closer: Option<String>,
+    pub close_cause: CloseCause,
+}
+
+/// 'listclosedchannels' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListClosedChannels {
+    pub closedchannels: Vec<ClosedChannel>,
 }
 
 /// Sub-structure for log entry in 'listpeers'
@@ -188,9 +418,12 @@ pub struct Log {
 pub struct Peer {
     pub id: String,
     pub connected: bool,
-    pub netaddr: Option<Vec<String>>,
-    pub local_features: Option<String>,
-    pub global_features: Option<String>,
+    /// Addresses we are connected on, only set if connected
+    pub netaddr: Option<Vec<common::NetAddress>>,
+    /// Features the peer supports, only set if connected
+    pub features: Option<common::FeatureBits>,
+    /// Not reported any more by recent versions of lightningd, which provide 'listpeerchannels'
+    #[serde(default)]
     pub channels: Vec<Channel>,
     pub log: Option<Vec<Log>>,
 }
@@ -201,17 +434,38 @@ pub struct ListPeers {
     pub peers: Vec<Peer>,
 }
 
+/// Sub-structure for 'listpeerchannels' entries, a channel as in 'listpeers' with its peer
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PeerChannel {
+    pub peer_id: String,
+    pub peer_connected: bool,
+    #[serde(flatten)]
+    pub channel: Channel,
+}
+
+/// 'listpeerchannels' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListPeerChannels {
+    pub channels: Vec<PeerChannel>,
+}
+
 /// Sub-structure for invoices in 'listinvoices'
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ListInvoice {
     pub label: String,
     pub bolt11: String,
     pub payment_hash: String,
-    pub msatoshi: i64,
+    /// Amount of the invoice, `None` for invoices that accept any amount
+    #[serde(alias = "msatoshi")]
+    pub amount_msat: Option<common::MSat>,
     pub status: String,
     pub expires_at: i64,
     pub pay_index: Option<i64>,
     pub paid_at: Option<i64>,
+    /// Position in the order of creation, used for pagination
+    pub created_index: Option<u64>,
+    /// Position in the order of the last update, such as being paid
+    pub updated_index: Option<u64>,
 }
 
 /// 'listinvoices' command
@@ -226,10 +480,54 @@ pub struct Invoice {
     pub payment_hash: String,
     pub expires_at: i64,
     pub bolt11: String,
+    pub payment_secret: Option<String>,
+    /// Set if the channels cannot receive the amount, even together
+    pub warning_capacity: Option<String>,
+    /// Set if enough capacity is only available with peers that are offline
+    pub warning_offline: Option<String>,
+    /// Set if enough capacity is only available with peers that are dead ends
+    pub warning_deadends: Option<String>,
+    /// Set if enough capacity is only available in private channels not exposed in the invoice
+    pub warning_private_unused: Option<String>,
+    /// Set if the amount can only be received in multiple parts
+    pub warning_mpp: Option<String>,
+}
+
+/// 'createinvoice' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CreateInvoice {
+    pub label: String,
+    pub bolt11: String,
+    pub payment_hash: String,
+    pub amount_msat: Option<common::MSat>,
+    pub status: String,
+    pub description: Option<String>,
+    pub expires_at: u64,
+    pub pay_index: Option<u64>,
+    pub amount_received_msat: Option<common::MSat>,
+    pub paid_at: Option<u64>,
+    pub payment_preimage: Option<String>,
+}
+
+/// 'signinvoice' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SignInvoice {
+    pub bolt11: String,
 }
 
 /// 'delinvoice' command
-pub type DelInvoice = ListInvoice;
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DelInvoice {
+    pub label: String,
+    pub bolt11: Option<String>,
+    pub payment_hash: String,
+    pub amount_msat: Option<common::MSat>,
+    pub status: String,
+    pub description: Option<String>,
+    pub expires_at: u64,
+    pub pay_index: Option<u64>,
+    pub paid_at: Option<u64>,
+}
 
 /// 'delexpiredinvoice' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -237,44 +535,92 @@ pub struct DelExpiredInvoice {}
 
 /// 'autocleaninvoice' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct AutoCleanInvoice {}
+pub struct AutoCleanInvoice {
+    pub enabled: Option<bool>,
+    pub cycle_seconds: Option<u64>,
+    pub expired_by: Option<u64>,
+}
+
+/// 'waitinvoice' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WaitInvoice {
+    pub label: String,
+    pub bolt11: String,
+    pub payment_hash: String,
+    pub status: String,
+    pub expires_at: u64,
+    pub pay_index: u64,
+    pub amount_received_msat: common::MSat,
+    pub paid_at: u64,
+    pub payment_preimage: String,
+}
 
 /// 'waitanyinvoice' command
-pub type WaitAnyInvoice = ListInvoice;
+pub type WaitAnyInvoice = WaitInvoice;
 
-/// 'waitinvoice' command
-pub type WaitInvoice = ListInvoice;
+/// 'waitblockheight' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WaitBlockHeight {
+    pub blockheight: u32,
+}
 
-/// Sub-structure for failure in 'pay'
+/// 'wait' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct FailureItem {
-    pub message: String,
-    #[serde(rename = "type")]
-    pub type_: String,
-    pub erring_index: i64,
-    pub failcode: i64,
-    pub erring_node: String,
-    pub erring_channel: String,
-    pub channel_update: Option<String>,
-    pub route: Vec<common::RouteItem>,
+pub struct Wait {
+    pub subsystem: common::WaitSubsystem,
+    /// The current values of the indexes, only the one waited for is always set
+    pub created: Option<u64>,
+    pub updated: Option<u64>,
+    pub deleted: Option<u64>,
+    /// What changed, such as the label and status of an invoice; lightningd reports it under
+    /// the name of the subsystem
+    #[serde(alias = "invoices", alias = "forwards", alias = "sendpays")]
+    pub details: Option<Value>,
 }
 
 /// 'pay' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Pay {
-    pub id: i64,
-    pub payment_hash: String,
-    pub destination: String,
-    pub msatoshi: i64,
-    pub msatoshi_sent: i64,
-    pub created_at: i64,
-    pub status: String,
     pub payment_preimage: String,
-    pub description: String,
-    pub getroute_tries: i64,
-    pub sendpay_tries: i64,
-    pub route: Vec<common::RouteItem>,
-    pub failures: Vec<FailureItem>,
+    pub payment_hash: String,
+    pub destination: Option<String>,
+    /// UNIX time the payment was started, with fractional seconds
+    pub created_at: f64,
+    /// Number of parts the payment was split into
+    pub parts: u32,
+    /// Amount the recipient received
+    pub amount_msat: common::MSat,
+    /// Amount sent, including fees
+    pub amount_sent_msat: common::MSat,
+    pub status: PayStatus,
+    /// Set if only some parts of the payment succeeded
+    pub warning_partial_completion: Option<String>,
+}
+
+/// Sub-structure for payments in 'renepaystatus'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RenePayEntry {
+    pub bolt11: String,
+    pub payment_hash: String,
+    pub payment_preimage: Option<String>,
+    pub destination: Option<String>,
+    /// UNIX time the payment was started
+    pub created_at: f64,
+    pub groupid: u64,
+    pub parts: Option<u32>,
+    pub amount_msat: common::MSat,
+    /// Amount sent, including fees, only set once the payment succeeded
+    pub amount_sent_msat: Option<common::MSat>,
+    pub status: PayStatus,
+    /// What renepay did and why, one line per step
+    #[serde(default)]
+    pub notes: Vec<String>,
+}
+
+/// 'renepaystatus' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RenePayStatus {
+    pub paystatus: Vec<RenePayEntry>,
 }
 
 /// 'sendpay' command
@@ -285,30 +631,97 @@ pub struct SendPay {
     pub id: i64,
     pub payment_hash: String,
     pub destination: String,
-    pub msatoshi: i64,
-    pub msatoshi_sent: i64,
+    pub msatoshi: common::MSat,
+    pub msatoshi_sent: common::MSat,
     pub created_at: i64,
     pub status: String,
     pub payment_preimage: Option<String>,
     pub description: Option<String>,
 }
 
-/// Sub-structure for payments in 'listpayments' and 'waitsendpay'
+/// Sub-structure for payments in 'listpayments'
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ListPaymentsItem {
     pub id: i64,
     pub payment_hash: String,
     pub destination: String,
-    pub msatoshi: i64,
-    pub msatoshi_sent: i64,
+    pub msatoshi: common::MSat,
+    pub msatoshi_sent: common::MSat,
     pub created_at: i64,
     pub status: String,
     pub payment_preimage: Option<String>,
     pub description: Option<String>,
 }
 
+/// Sub-structure for payment attempts in 'listsendpays' and 'waitsendpay'
+///
+/// The failure fields are only present when this is the error data of a failed attempt, as
+/// returned by `RpcError::sendpay_failure`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SendPayItem {
+    pub id: u64,
+    pub groupid: Option<u64>,
+    pub partid: Option<u64>,
+    pub payment_hash: String,
+    pub destination: Option<String>,
+    pub amount_msat: Option<common::MSat>,
+    pub amount_sent_msat: common::MSat,
+    pub created_at: u64,
+    pub status: String,
+    pub payment_preimage: Option<String>,
+    pub bolt11: Option<String>,
+    pub label: Option<String>,
+    pub erring_index: Option<u64>,
+    pub failcode: Option<u32>,
+    pub failcodename: Option<String>,
+    pub erring_node: Option<String>,
+    pub erring_channel: Option<String>,
+    pub erring_direction: Option<u8>,
+}
+
 /// 'waitsendpay' command
-pub type WaitSendPay = ListPaymentsItem;
+pub type WaitSendPay = SendPayItem;
+
+/// 'listsendpays' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListSendPays {
+    pub payments: Vec<SendPayItem>,
+}
+
+/// 'delpay' command, the deleted payment attempts
+pub type DelPay = ListSendPays;
+
+string_enum! {
+    /// State of a payment in 'listpays'
+    pub enum PayStatus {
+        Pending => "pending",
+        Failed => "failed",
+        Complete => "complete",
+    }
+}
+
+/// Sub-structure for 'listpays' entries
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListPaysItem {
+    pub bolt11: Option<String>,
+    pub destination: String,
+    pub payment_hash: String,
+    pub status: PayStatus,
+    pub created_at: u64,
+    pub completed_at: Option<u64>,
+    pub label: Option<String>,
+    pub description: Option<String>,
+    pub amount_msat: Option<common::MSat>,
+    /// Absent for payments that failed before any part was sent
+    pub amount_sent_msat: Option<common::MSat>,
+    pub preimage: Option<String>,
+}
+
+/// 'listpays' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListPays {
+    pub pays: Vec<ListPaysItem>,
+}
 
 /// 'listpayments' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -316,30 +729,156 @@ pub struct ListPayments {
     pub payments: Vec<ListPaymentsItem>,
 }
 
+/// Sub-structure for route hints in 'decodepay'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RouteHop {
+    pub pubkey: String,
+    pub short_channel_id: common::ShortChannelId,
+    pub fee_base_msat: common::MSat,
+    pub fee_proportional_millionths: u64,
+    pub cltv_expiry_delta: u64,
+}
+
 /// 'decodepay' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DecodePay {
     pub currency: String,
-    pub created_at: i64,
-    pub expiry: i64,
+    pub created_at: u64,
+    pub expiry: u64,
     pub payee: String,
-    pub msatoshi: i64,
-    pub description: String,
-    pub min_final_cltv_expiry: i64,
+    pub amount_msat: Option<common::MSat>,
+    pub description: Option<String>,
+    pub description_hash: Option<String>,
+    pub min_final_cltv_expiry: u64,
     pub payment_hash: String,
+    pub payment_secret: Option<String>,
+    pub features: Option<common::FeatureBits>,
+    /// Route hints, each a list of hops leading to the payee
+    #[serde(default)]
+    pub routes: Vec<Vec<RouteHop>>,
     pub signature: String,
 }
 
+string_enum! {
+    /// Kind of string recognized by 'decode'
+    pub enum DecodeType {
+        Bolt11Invoice => "bolt11 invoice",
+        Bolt12Offer => "bolt12 offer",
+        Bolt12InvoiceRequest => "bolt12 invoice_request",
+        Bolt12Invoice => "bolt12 invoice",
+        Rune => "rune",
+    }
+}
+
+/// 'decode' command
+///
+/// Only the fields shared by most string types are modelled; which ones are present depends on
+/// `type_`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Decode {
+    #[serde(rename = "type")]
+    pub type_: DecodeType,
+    pub valid: bool,
+    pub currency: Option<String>,
+    pub created_at: Option<u64>,
+    pub expiry: Option<u64>,
+    pub payee: Option<String>,
+    pub amount_msat: Option<common::MSat>,
+    pub description: Option<String>,
+    pub description_hash: Option<String>,
+    pub min_final_cltv_expiry: Option<u64>,
+    pub payment_hash: Option<String>,
+    pub payment_secret: Option<String>,
+    pub features: Option<common::FeatureBits>,
+    pub routes: Option<Vec<Vec<RouteHop>>>,
+    pub signature: Option<String>,
+    pub offer_id: Option<String>,
+    pub offer_description: Option<String>,
+    pub offer_node_id: Option<String>,
+    pub offer_amount_msat: Option<common::MSat>,
+    pub invoice_amount_msat: Option<common::MSat>,
+    pub invoice_payment_hash: Option<String>,
+    pub unique_id: Option<String>,
+    pub string: Option<String>,
+    pub warning_missing_offer_node_id: Option<String>,
+}
+
+/// Sub-structure for route in 'getroute'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GetRouteHop {
+    pub id: String,
+    pub channel: common::ShortChannelId,
+    pub direction: u8,
+    pub amount_msat: common::MSat,
+    pub delay: u64,
+    pub style: String,
+}
+
+impl From<GetRouteHop> for common::RouteItem {
+    /// Convert a computed hop into the form taken by `sendpay`
+    fn from(hop: GetRouteHop) -> common::RouteItem {
+        common::RouteItem {
+            id: hop.id,
+            channel: hop.channel,
+            msatoshi: hop.amount_msat,
+            delay: hop.delay as i64,
+        }
+    }
+}
+
 /// 'getroute' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct GetRoute {
-    pub route: Vec<common::RouteItem>,
+    pub route: Vec<GetRouteHop>,
+}
+
+/// 'createonion' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CreateOnion {
+    pub onion: String,
+    pub shared_secrets: Vec<String>,
+}
+
+/// 'sendonion' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SendOnion {
+    pub message: Option<String>,
+    pub id: u64,
+    pub created_index: Option<u64>,
+    pub payment_hash: String,
+    /// "pending", or "complete" if the payment was made before
+    pub status: String,
+    /// Set if {amount_msat} was given to `sendonion`
+    pub amount_msat: Option<common::MSat>,
+    pub amount_sent_msat: common::MSat,
+    /// Set if {destination} was given to `sendonion`
+    pub destination: Option<String>,
+    pub created_at: u64,
+    pub partid: Option<u64>,
+    pub label: Option<String>,
+    pub bolt11: Option<String>,
+    pub payment_preimage: Option<String>,
+}
+
+string_enum! {
+    /// Which side initiated a connection in 'connect'
+    pub enum ConnectDirection {
+        /// The peer connected to us
+        In => "in",
+        /// We connected to the peer
+        Out => "out",
+    }
 }
 
 /// 'connect' command
+///
+/// Only `id` is reported by older versions of lightningd.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Connect {
     pub id: String,
+    pub features: Option<common::FeatureBits>,
+    pub direction: Option<ConnectDirection>,
+    pub address: Option<common::NetAddress>,
 }
 
 /// 'disconnect' command
@@ -352,41 +891,224 @@ pub struct FundChannel {
     pub tx: String,
     pub txid: String,
     pub channel_id: String,
+    /// Output of the funding transaction that funds the channel
+    pub outnum: u32,
+    /// Script our funds go to on a mutual close, if {close_to} was given and the peer supports it
+    pub close_to: Option<String>,
 }
 
-/// 'close' command
+/// Sub-structure for opened channels in 'multifundchannel'
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct Close {
+pub struct ChannelIdEntry {
+    pub id: String,
+    pub channel_id: String,
+    /// Output of the funding transaction that funds the channel
+    pub outnum: u32,
+    /// Script our funds go to on a mutual close, if {close_to} was given and the peer supports it
+    pub close_to: Option<String>,
+}
+
+/// Sub-structure for destinations that failed in 'multifundchannel'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FailedDest {
+    pub id: String,
+    /// Step that failed, e.g. "connect" or "fundchannel_start"
+    pub method: String,
+    pub error: crate::error::RpcError,
+}
+
+/// 'multifundchannel' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MultiFundChannel {
     pub tx: String,
     pub txid: String,
+    pub channel_ids: Vec<ChannelIdEntry>,
+    /// Destinations that were left out, only reported if {minchannels} allowed it
+    #[serde(default)]
+    pub failed: Vec<FailedDest>,
+}
+
+/// 'fundchannel_start' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FundChannelStart {
+    pub funding_address: String,
+    pub scriptpubkey: String,
+    pub close_to: Option<String>,
+}
+
+/// 'fundchannel_complete' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FundChannelComplete {
+    pub channel_id: Option<String>,
+    pub commitments_secured: bool,
+}
+
+/// 'fundchannel_cancel' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FundChannelCancel {
+    pub cancelled: String,
+}
+
+/// 'splice_init' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SpliceInit {
+    pub psbt: String,
+}
+
+/// 'splice_update' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SpliceUpdate {
+    pub psbt: String,
+    /// Whether the new commitment transactions are signed, and the splice can be signed
+    pub commitments_secured: bool,
+}
+
+/// 'splice_signed' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SpliceSigned {
+    pub tx: String,
+    pub txid: String,
+}
+
+string_enum! {
+    /// How a channel was closed by 'close'
+    pub enum CloseType {
+        /// Both sides agreed on the closing transaction
+        Mutual => "mutual",
+        /// Our commitment transaction was broadcast
+        Unilateral => "unilateral",
+        /// The channel was never opened, so there was nothing to close onchain
+        Unopened => "unopened",
+    }
+}
+
+/// 'close' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Close {
     #[serde(rename = "type")]
-    pub type_: String,
+    pub type_: CloseType,
+    /// Absent if there was nothing to broadcast, e.g. for `CloseType::Unopened`
+    pub tx: Option<String>,
+    pub txid: Option<String>,
+}
+
+/// Sub-structure for channels in 'setchannel'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SetChannelResult {
+    pub peer_id: String,
+    pub channel_id: String,
+    pub short_channel_id: Option<common::ShortChannelId>,
+    pub fee_base_msat: common::MSat,
+    pub fee_proportional_millionths: u32,
+    pub minimum_htlc_out_msat: common::MSat,
+    pub maximum_htlc_out_msat: common::MSat,
+    pub warning_htlcmin_too_low: Option<String>,
+    pub warning_htlcmax_too_high: Option<String>,
+}
+
+/// 'setchannel' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SetChannel {
+    pub channels: Vec<SetChannelResult>,
 }
 
 /// 'ping' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Ping {
-    pub totlen: i64,
+    pub totlen: u16,
+}
+
+string_enum! {
+    /// State of a forwarded HTLC in 'listforwards'
+    pub enum ForwardStatus {
+        Offered => "offered",
+        Settled => "settled",
+        Failed => "failed",
+        LocalFailed => "local_failed",
+    }
+}
+
+/// Sub-structure for forwards in 'listforwards'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Forward {
+    pub payment_hash: Option<String>,
+    pub in_channel: common::ShortChannelId,
+    pub out_channel: Option<common::ShortChannelId>,
+    pub in_msat: common::MSat,
+    pub out_msat: Option<common::MSat>,
+    pub fee_msat: Option<common::MSat>,
+    pub status: ForwardStatus,
+    pub received_time: f64,
+    pub resolved_time: Option<f64>,
+}
+
+/// Sub-structure for HTLCs in 'listhtlcs'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HtlcEntry {
+    pub short_channel_id: common::ShortChannelId,
+    /// Index of the HTLC within the channel, per direction
+    pub id: u64,
+    /// Block height at which the HTLC times out
+    pub expiry: u32,
+    pub direction: HtlcDirection,
+    pub amount_msat: common::MSat,
+    pub payment_hash: String,
+    pub state: HtlcState,
+}
+
+/// 'listhtlcs' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListHtlcs {
+    pub htlcs: Vec<HtlcEntry>,
+}
+
+/// 'listforwards' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListForwards {
+    pub forwards: Vec<Forward>,
+}
+
+string_enum! {
+    /// State of an output in 'listfunds'
+    pub enum OutputStatus {
+        /// Not confirmed yet
+        Unconfirmed => "unconfirmed",
+        /// Confirmed and spendable
+        Confirmed => "confirmed",
+        /// Already spent, only listed with {spent}
+        Spent => "spent",
+        /// A coinbase output that cannot be spent yet
+        Immature => "immature",
+    }
 }
 
 /// Sub-structure for 'listfunds' output
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ListFundsOutput {
     pub txid: String,
-    pub output: i64,
-    pub value: i64,
-    pub address: String,
-    pub status: String,
+    pub output: u32,
+    pub amount_msat: common::MSat,
+    pub scriptpubkey: String,
+    pub address: Option<String>,
+    pub status: OutputStatus,
+    /// Whether the output is reserved for a transaction being built, e.g. by `txprepare`
+    pub reserved: bool,
+    /// Absent for unconfirmed outputs
+    pub blockheight: Option<u32>,
 }
 
 /// Sub-structure for 'listfunds' channel
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ListFundsChannel {
     pub peer_id: String,
-    pub short_channel_id: String,
-    pub channel_sat: i64,
-    pub channel_total_sat: i64,
+    pub our_amount_msat: common::MSat,
+    pub amount_msat: common::MSat,
     pub funding_txid: String,
+    pub funding_output: u32,
+    pub connected: bool,
+    pub state: common::ChannelState,
+    /// Absent until the funding transaction is confirmed
+    pub short_channel_id: Option<common::ShortChannelId>,
 }
 
 /// 'listfunds' command
@@ -396,18 +1118,2378 @@ pub struct ListFunds {
     pub channels: Vec<ListFundsChannel>,
 }
 
+impl ListFunds {
+    /// Sum up the funds of the wallet and the channels, see `Balance`
+    pub fn balance(&self) -> Balance {
+        let mut balance = Balance::default();
+        for output in &self.outputs {
+            match output.status {
+                OutputStatus::Confirmed => balance.onchain_confirmed_msat.0 += output.amount_msat.0,
+                OutputStatus::Unconfirmed | OutputStatus::Immature => {
+                    balance.onchain_unconfirmed_msat.0 += output.amount_msat.0
+                }
+                _ => {}
+            }
+        }
+        for channel in &self.channels {
+            balance.channel_total_msat.0 += channel.our_amount_msat.0;
+            if channel.connected && channel.state.is_active() {
+                balance.channel_spendable_msat.0 += channel.our_amount_msat.0;
+            }
+        }
+        balance
+    }
+}
+
+/// Funds of the node, summed up from 'listfunds' by `ListFunds::balance`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub struct Balance {
+    /// Confirmed wallet outputs, including reserved ones
+    pub onchain_confirmed_msat: common::MSat,
+    /// Wallet outputs that are not confirmed yet, or immature coinbase outputs
+    pub onchain_unconfirmed_msat: common::MSat,
+    /// Our side of the channels that can be used for payments right now: connected and in
+    /// `ChannelState::is_active` state. The channel reserve is not subtracted, so a bit less can
+    /// actually be sent.
+    pub channel_spendable_msat: common::MSat,
+    /// Our side of all channels, including those being opened or closed
+    pub channel_total_msat: common::MSat,
+}
+
+/// Sub-structure for inputs in 'listtransactions'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TransactionInput {
+    pub txid: String,
+    pub index: u32,
+    pub sequence: u32,
+    #[serde(rename = "type")]
+    pub type_: Option<Vec<String>>,
+    pub channel: Option<common::ShortChannelId>,
+}
+
+/// Sub-structure for outputs in 'listtransactions'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TransactionOutput {
+    pub index: u32,
+    pub amount_msat: common::MSat,
+    #[serde(rename = "scriptPubKey")]
+    pub script_pub_key: String,
+    /// What the output is for, e.g. `channel_funding` or `withdrawal`, if known
+    #[serde(rename = "type")]
+    pub type_: Option<Vec<String>>,
+    pub channel: Option<common::ShortChannelId>,
+}
+
+/// Sub-structure for 'listtransactions' entries
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Transaction {
+    pub hash: String,
+    pub rawtx: String,
+    pub blockheight: u32,
+    pub txindex: u32,
+    pub locktime: u32,
+    pub version: u32,
+    pub inputs: Vec<TransactionInput>,
+    pub outputs: Vec<TransactionOutput>,
+}
+
+/// 'listtransactions' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListTransactions {
+    pub transactions: Vec<Transaction>,
+}
+
 /// 'withdraw' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Withdraw {
     pub tx: String,
     pub txid: String,
+    /// Not reported by older versions of lightningd
+    pub psbt: Option<String>,
+}
+
+/// 'txprepare' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TxPrepare {
+    pub psbt: String,
+    pub unsigned_tx: String,
+    pub txid: String,
+}
+
+/// 'txsend' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TxSend {
+    pub psbt: String,
+    pub tx: String,
+    pub txid: String,
+}
+
+/// 'txdiscard' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TxDiscard {
+    pub unsigned_tx: String,
+    pub txid: String,
+}
+
+/// 'signpsbt' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SignPsbt {
+    pub signed_psbt: String,
+}
+
+/// 'sendpsbt' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SendPsbt {
+    /// The raw transaction, as hex
+    pub tx: String,
+    pub txid: String,
+}
+
+/// Reservation state of one input, as returned by 'reserveinputs' and 'unreserveinputs'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Reservation {
+    pub txid: String,
+    pub vout: u32,
+    /// Whether the output was already reserved before this call
+    pub was_reserved: bool,
+    /// Whether the output is reserved after this call
+    pub reserved: bool,
+    /// Block height at which the reservation expires, absent once unreserved
+    pub reserved_to_block: Option<u32>,
+}
+
+/// 'setpsbtversion' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SetPsbtVersion {
+    pub psbt: String,
+}
+
+/// 'reserveinputs' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReserveInputs {
+    pub reservations: Vec<Reservation>,
+}
+
+/// 'unreserveinputs' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UnreserveInputs {
+    pub reservations: Vec<Reservation>,
+}
+
+/// 'fundpsbt' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FundPsbt {
+    /// Unsigned PSBT spending the selected inputs, base64 encoded
+    pub psbt: String,
+    pub feerate_per_kw: u32,
+    /// Weight of the transaction once all inputs are signed, including {startweight}
+    pub estimated_final_weight: u32,
+    /// Input amount left over after the requested amount and fees
+    pub excess_msat: common::MSat,
+    /// Index of the change output, set if {excess_as_change} added one
+    pub change_outnum: Option<u32>,
+    /// Set if the inputs were reserved
+    pub reservations: Option<Vec<Reservation>>,
+}
+
+/// 'utxopsbt' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UtxoPsbt {
+    /// Unsigned PSBT spending {utxos}, base64 encoded
+    pub psbt: String,
+    pub feerate_per_kw: u32,
+    /// Weight of the transaction once all inputs are signed, including {startweight}
+    pub estimated_final_weight: u32,
+    /// Input amount left over after the requested amount and fees
+    pub excess_msat: common::MSat,
+    /// Index of the change output, set if {excess_as_change} added one
+    pub change_outnum: Option<u32>,
+    /// Set if the inputs were reserved
+    pub reservations: Option<Vec<Reservation>>,
 }
 
 /// 'newaddr' command
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct NewAddr {
-    pub address: String,
+    /// Set if {addresstype} was bech32 or all
+    pub bech32: Option<String>,
+    /// Set if {addresstype} was p2sh-segwit or all
+    #[serde(rename = "p2sh-segwit")]
+    pub p2sh_segwit: Option<String>,
+}
+
+/// Sub-structure for plugins in 'plugin'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PluginInfo {
+    /// Path of the plugin
+    pub name: String,
+    pub active: bool,
+    /// Whether the plugin can be stopped and started while lightningd runs
+    pub dynamic: Option<bool>,
+}
+
+/// 'plugin' command with the start, rescan and list subcommands
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Plugins {
+    pub command: String,
+    pub plugins: Vec<PluginInfo>,
+}
+
+/// 'plugin' command with the stop subcommand
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PluginStop {
+    pub command: String,
+    /// A message saying which plugin was stopped
+    pub result: String,
+}
+
+/// 'check' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Check {
+    pub command_to_check: String,
+}
+
+/// 'signmessage' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SignMessage {
+    pub signature: String,
+    pub recid: String,
+    pub zbase: String,
+}
+
+/// 'checkmessage' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CheckMessage {
+    pub verified: bool,
+    pub pubkey: String,
+}
+
+/// 'getsharedsecret' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GetSharedSecret {
+    pub shared_secret: String,
+}
+
+/// 'keysend' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct KeySend {
+    pub destination: Option<String>,
+    pub payment_preimage: String,
+    pub payment_hash: String,
+    pub created_at: f64,
+    pub parts: u64,
+    pub amount_msat: common::MSat,
+    pub amount_sent_msat: common::MSat,
+    pub status: String,
+    pub warning_partial_completion: Option<String>,
+}
+
+/// 'offer' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Offer {
+    pub offer_id: String,
+    pub active: bool,
+    pub single_use: bool,
+    pub bolt12: String,
+    pub used: bool,
+    /// False if an identical offer already existed and was returned instead
+    pub created: bool,
+    pub label: Option<String>,
+}
+
+/// Sub-structure for the differences between the invoice and the offer in 'fetchinvoice'
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct FetchInvoiceChanges {
+    /// Text added to the end of the offer's description
+    pub description_appended: Option<String>,
+    /// The new description, if it was otherwise changed
+    pub description: Option<String>,
+    /// The offer's issuer, if the invoice leaves it out
+    pub issuer_removed: Option<String>,
+    /// The new issuer, if it was changed
+    pub issuer: Option<String>,
+    /// The amount, if the offer did not specify one in msat
+    pub amount_msat: Option<common::MSat>,
+}
+
+/// Sub-structure for the next period of a recurring offer in 'fetchinvoice'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NextPeriod {
+    pub counter: u64,
+    pub starttime: u64,
+    pub endtime: u64,
+    pub paywindow_start: u64,
+    pub paywindow_end: u64,
+}
+
+/// 'fetchinvoice' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FetchInvoice {
+    pub invoice: String,
+    pub changes: FetchInvoiceChanges,
+    pub next_period: Option<NextPeriod>,
+}
+
+/// 'datastore' command, and the entries of 'listdatastore'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DataStore {
+    /// Path of the entry, from the top level down
+    pub key: Vec<String>,
+    /// Incremented on every change, absent for keys that only have children
+    pub generation: Option<u64>,
+    /// The value, absent for keys that only have children
+    pub hex: Option<String>,
+    /// The value, if it is valid UTF-8
+    pub string: Option<String>,
+}
+
+/// 'deldatastore' command, the entry that was deleted
+pub type DelDataStore = DataStore;
+
+/// 'listdatastore' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListDataStore {
+    pub datastore: Vec<DataStore>,
+}
+
+/// 'sql' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Sql {
+    /// One entry per row, with the columns in the order of the query
+    pub rows: Vec<Vec<Value>>,
+}
+
+/// Sub-structure for columns in 'listsqlschemas'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SqlColumn {
+    pub name: String,
+    /// SQL type of the column, such as "INTEGER", "TEXT" or "BLOB"
+    #[serde(rename = "type")]
+    pub type_: String,
+}
+
+/// Sub-structure for tables in 'listsqlschemas'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SqlSchema {
+    pub tablename: String,
+    pub columns: Vec<SqlColumn>,
+    /// Columns of each index on the table
+    #[serde(default)]
+    pub indices: Vec<Vec<String>>,
 }
 
-/// 'stop' command
-pub type Stop = String;
+/// 'listsqlschemas' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListSqlSchemas {
+    pub schemas: Vec<SqlSchema>,
+}
+
+/// Sub-structure for balances in 'bkpr-listbalances' accounts
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BkprBalance {
+    pub balance_msat: common::MSat,
+    pub coin_type: String,
+}
+
+/// Sub-structure for accounts in 'bkpr-listbalances'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BkprAccount {
+    /// "wallet", "external" or the channel id
+    pub account: String,
+    /// Set for channel accounts
+    pub peer_id: Option<String>,
+    pub we_opened: Option<bool>,
+    pub account_closed: Option<bool>,
+    /// Whether all outputs of the closed channel are resolved onchain
+    pub account_resolved: Option<bool>,
+    pub resolved_at_block: Option<u32>,
+    pub balances: Vec<BkprBalance>,
+}
+
+/// 'bkpr-listbalances' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BkprListBalances {
+    pub accounts: Vec<BkprAccount>,
+}
+
+string_enum! {
+    /// Kind of event in 'bkpr-listaccountevents'
+    pub enum AccountEventType {
+        /// Fees paid for an onchain transaction
+        OnchainFee => "onchain_fee",
+        /// Movement of an onchain output
+        Chain => "chain",
+        /// Payment or forward over a channel
+        Channel => "channel",
+    }
+}
+
+/// Sub-structure for events in 'bkpr-listaccountevents'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AccountEvent {
+    pub account: String,
+    #[serde(rename = "type")]
+    pub type_: AccountEventType,
+    /// What the event is for, e.g. "deposit", "invoice" or "routed"
+    pub tag: String,
+    pub credit_msat: common::MSat,
+    pub debit_msat: common::MSat,
+    pub currency: String,
+    pub timestamp: u64,
+    /// Output as "txid:vout", set for chain events
+    pub outpoint: Option<String>,
+    pub blockheight: Option<u32>,
+    /// Account the funds came from, for chain events
+    pub origin: Option<String>,
+    /// Payment hash, set for channel events
+    pub payment_id: Option<String>,
+    /// Spending transaction, set for onchain fee and chain events
+    pub txid: Option<String>,
+    pub description: Option<String>,
+    pub fees_msat: Option<common::MSat>,
+    pub is_rebalance: Option<bool>,
+    pub part_id: Option<u64>,
+}
+
+/// 'bkpr-listaccountevents' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BkprListAccountEvents {
+    pub events: Vec<AccountEvent>,
+}
+
+/// Sub-structure for income events in 'bkpr-listincome'
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IncomeEvent {
+    pub account: String,
+    /// What the income or expense is for, e.g. "invoice", "routed" or "onchain_fee"
+    pub tag: String,
+    pub credit_msat: common::MSat,
+    pub debit_msat: common::MSat,
+    pub currency: String,
+    pub timestamp: u64,
+    pub description: Option<String>,
+    /// Output as "txid:vout", set for onchain events
+    pub outpoint: Option<String>,
+    /// Set for onchain fee events
+    pub txid: Option<String>,
+    /// Payment hash, set for channel events
+    pub payment_id: Option<String>,
+}
+
+/// 'bkpr-listincome' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListIncome {
+    pub income_events: Vec<IncomeEvent>,
+}
+
+/// 'sendcustommsg' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SendCustomMsg {
+    /// Which subdaemon the message was handed to, for information only
+    pub status: String,
+}
+
+/// 'addgossip' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AddGossip {}
+
+/// 'staticbackup' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StaticBackup {
+    /// One static channel backup per channel, in hex
+    pub scb: Vec<String>,
+}
+
+/// 'recoverchannel' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RecoverChannel {
+    /// Channel ids of the stub channels that were created
+    pub stubs: Vec<String>,
+}
+
+/// 'emergencyrecover' command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EmergencyRecover {
+    /// Channel ids of the stub channels that were created
+    pub stubs: Vec<String>,
+}
+
+/// 'stop' command, the message lightningd sends before shutting down
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Stop(pub String);
+
+/// Sub-structure for 'dev-rescan-outputs' outputs
+#[cfg(feature = "dev")]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DevRescannedOutput {
+    pub txid: String,
+    pub output: u32,
+    /// Wallet state of the output before the rescan: 0 available, 1 reserved, 2 spent
+    pub oldstate: u32,
+    /// Wallet state of the output after the rescan
+    pub newstate: u32,
+}
+
+/// 'dev-rescan-outputs' command
+#[cfg(feature = "dev")]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DevRescanOutputs {
+    pub outputs: Vec<DevRescannedOutput>,
+}
+
+/// 'dev-forget-channel' command
+#[cfg(feature = "dev")]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DevForgetChannel {
+    /// Whether {force} was needed because the funding output is unspent
+    pub forced: bool,
+    pub funding_unspent: bool,
+    pub funding_txid: String,
+}
+
+/// 'dev-sign-last-tx' command
+#[cfg(feature = "dev")]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DevSignLastTx {
+    /// The signed commitment transaction, as hex
+    pub tx: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feerates_missing_estimates() {
+        let json = r#"{
+            "perkw": {
+                "opening": 7500,
+                "mutual_close": 3750,
+                "min_acceptable": 1875,
+                "max_acceptable": 150000
+            },
+            "onchain_fee_estimates": {
+                "opening_channel_satoshis": 5265
+            },
+            "warning_missing_feerates": "Some fee estimates unavailable: bitcoind startup?"
+        }"#;
+        let feerates: FeeRates = serde_json::from_str(json).unwrap();
+        let perkw = feerates.perkw.unwrap();
+        assert_eq!(perkw.opening, Some(7500));
+        assert_eq!(perkw.unilateral_close, None);
+        assert!(feerates.perkb.is_none());
+        assert_eq!(
+            feerates
+                .onchain_fee_estimates
+                .unwrap()
+                .opening_channel_satoshis,
+            Some(5265)
+        );
+        assert!(feerates.warning_missing_feerates.is_some());
+    }
+
+    #[test]
+    fn waitanyinvoice() {
+        let json = r#"{
+            "label": "lbl-1",
+            "bolt11": "lnbcrt10n1p0v2ruapp5lhv0ndz0aqtrjkkhfzwluq5lgzpcxcm0kx0fkzghunzehxhdyxsdqqcqzpgsp5uz93m4pwqss6qt5auzpjkqghhfmec29pcfg5ad0r7pvmsdgqt44s9qy9qsq0vde6nt4uhxv4cty2m0xcp4kxjjlatlkt9ntu9j7wdvmmgq73ngp0mwhpf0d2uk7e0ws2xg0kcj55jfvc9xd2hlqfx0272enqlkwrcpqc0tz7",
+            "payment_hash": "fdd8f9b44fe81639dad7489dfe029f408383636fb19e9b0917e4c59b9aed21a1",
+            "msatoshi": 1000,
+            "amount_msat": "1000msat",
+            "status": "paid",
+            "pay_index": 1,
+            "msatoshi_received": 1000,
+            "amount_received_msat": "1000msat",
+            "paid_at": 1595415930,
+            "payment_preimage": "cc7ad0e9f7809ba00a7c1e6e4a5a8ee0a1bc28e88b8a80b1be0b5bb3321e2ea2",
+            "description": "test",
+            "expires_at": 1596020720
+        }"#;
+        let inv: WaitAnyInvoice = serde_json::from_str(json).unwrap();
+        assert_eq!(inv.label, "lbl-1");
+        assert_eq!(inv.status, "paid");
+        assert_eq!(inv.pay_index, 1);
+        assert_eq!(inv.amount_received_msat, common::MSat(1000));
+        assert_eq!(inv.paid_at, 1595415930);
+
+        let ser = serde_json::to_value(&inv).unwrap();
+        let des: WaitInvoice = serde_json::from_value(ser).unwrap();
+        assert_eq!(des.payment_preimage, inv.payment_preimage);
+        assert_eq!(des.bolt11, inv.bolt11);
+    }
+
+    #[test]
+    fn listforwards() {
+        let json = r#"{
+            "forwards": [
+                {
+                    "payment_hash": "f0b5a6c9b2736ba3bcc0e9a0b5cce5d043a0cd8e0f2a3bf3a88c9c8a27f81f7e",
+                    "in_channel": "103x1x0",
+                    "out_channel": "110x1x1",
+                    "in_msat": "100001001msat",
+                    "out_msat": "100000000msat",
+                    "fee_msat": "1001msat",
+                    "status": "settled",
+                    "received_time": 1595415931.026,
+                    "resolved_time": 1595415931.754
+                },
+                {
+                    "in_channel": "103x1x0",
+                    "out_channel": "110x1x1",
+                    "in_msat": 5000002,
+                    "out_msat": 5000000,
+                    "fee_msat": 2,
+                    "status": "failed",
+                    "received_time": 1595415950.5,
+                    "resolved_time": 1595415951.1
+                },
+                {
+                    "in_channel": "103x1x0",
+                    "in_msat": "2000msat",
+                    "status": "local_failed",
+                    "received_time": 1595415960.0
+                },
+                {
+                    "in_channel": "103x1x0",
+                    "in_msat": "2000msat",
+                    "status": "some_future_status",
+                    "received_time": 1595415970.0
+                }
+            ]
+        }"#;
+        let res: ListForwards = serde_json::from_str(json).unwrap();
+        assert_eq!(res.forwards.len(), 4);
+        assert_eq!(res.forwards[0].status, ForwardStatus::Settled);
+        assert_eq!(res.forwards[0].fee_msat, Some(common::MSat(1001)));
+        assert_eq!(res.forwards[1].status, ForwardStatus::Failed);
+        assert_eq!(res.forwards[1].in_msat, common::MSat(5000002));
+        assert_eq!(res.forwards[2].status, ForwardStatus::LocalFailed);
+        assert!(res.forwards[2].out_channel.is_none());
+        assert!(res.forwards[2].resolved_time.is_none());
+        assert_eq!(
+            res.forwards[3].status,
+            ForwardStatus::Unknown("some_future_status".to_string())
+        );
+        assert_eq!(res.forwards[3].status.as_str(), "some_future_status");
+    }
+
+    #[test]
+    fn listsendpays() {
+        let json = r#"{
+            "payments": [
+                {
+                    "id": 1,
+                    "payment_hash": "684b5bbb5b84c4f1c9959cb1f5c0b5fb1bdc6df2bd2b0cf1ae9d6468f0cfbc04",
+                    "destination": "035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d",
+                    "msatoshi": 1000000,
+                    "amount_msat": "1000000msat",
+                    "msatoshi_sent": 1000000,
+                    "amount_sent_msat": "1000000msat",
+                    "created_at": 1595415931,
+                    "status": "complete",
+                    "payment_preimage": "e1a6e1d326ee3b6dd7de90c9f5fd8c3ff84ab72eaf28bf1ff3f2dbb3058ff8c5",
+                    "bolt11": "lnbcrt10u1p0v2ruapp5dp94hw6mscj0rjv4njcltsx9lvdacm0jh54se7dwn4jxcm8hhszq"
+                },
+                {
+                    "id": 2,
+                    "partid": 1,
+                    "payment_hash": "a1d1b1b5e0c0b3b9f3bd4dbde3a7a0b6b8f46bb8a3e0c0a1913e3d01e1d0c1f0",
+                    "destination": "035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d",
+                    "amount_sent_msat": 2000,
+                    "created_at": 1595415940,
+                    "status": "failed"
+                }
+            ]
+        }"#;
+        let res: ListSendPays = serde_json::from_str(json).unwrap();
+        assert_eq!(res.payments.len(), 2);
+        assert_eq!(res.payments[0].status, "complete");
+        assert_eq!(res.payments[0].amount_msat, Some(common::MSat(1000000)));
+        assert!(res.payments[0].payment_preimage.is_some());
+        assert_eq!(res.payments[1].partid, Some(1));
+        assert_eq!(res.payments[1].amount_sent_msat, common::MSat(2000));
+        assert!(res.payments[1].amount_msat.is_none());
+        assert!(res.payments[1].payment_preimage.is_none());
+    }
+
+    #[test]
+    fn signmessage_checkmessage() {
+        let json = r#"{
+            "signature": "9ff26aa6b1348a911d7d5b1c4d9ab2bf1e2f8cc6a7766ca64e08e1db32ae33124a06a6d85e4bd9f74080e7ce14d98402aa62673a334fa3da575ae4012c4fda36",
+            "recid": "00",
+            "zbase": "d6tqaeuonjhi98mmont9m4wag7gg4krg1f4txonug3h31e9h6p6k6nbwjondnj46dkyausobstnk7fhyy998bhgc1yr98dfmhb4k54d7"
+        }"#;
+        let signed: SignMessage = serde_json::from_str(json).unwrap();
+        assert_eq!(signed.recid, "00");
+        assert_eq!(signed.signature.len(), 128);
+        assert!(signed.zbase.starts_with("d6tqaeuonjhi"));
+
+        let json = r#"{
+            "pubkey": "0266e4598d1d3c415f572a8488830b60f7e744ed9235eb0b1ba93283b315c03518",
+            "verified": true
+        }"#;
+        let checked: CheckMessage = serde_json::from_str(json).unwrap();
+        assert!(checked.verified);
+        assert_eq!(
+            checked.pubkey,
+            "0266e4598d1d3c415f572a8488830b60f7e744ed9235eb0b1ba93283b315c03518"
+        );
+    }
+
+    #[test]
+    fn keysend() {
+        let json = r#"{
+            "destination": "035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d",
+            "payment_hash": "b8a7b5fb0b5ad19e6c6b1c9ae3d0cc7a1b0c93cf6d14ad2c3d8b7e7f7a6c2bd1",
+            "created_at": 1605183928.0624,
+            "parts": 1,
+            "msatoshi": 10000,
+            "amount_msat": "10000msat",
+            "msatoshi_sent": 10000,
+            "amount_sent_msat": "10000msat",
+            "payment_preimage": "8d2b1d7b6c6b9a1f2e3f1c0d1d3c1a9c6d1e7f7e4c4d3a2b1f0e9d8c7b6a5f4e",
+            "status": "complete"
+        }"#;
+        let res: KeySend = serde_json::from_str(json).unwrap();
+        assert_eq!(res.status, "complete");
+        assert_eq!(res.parts, 1);
+        assert_eq!(res.amount_msat, common::MSat(10000));
+        assert!(res.warning_partial_completion.is_none());
+    }
+
+    #[test]
+    fn fundchannel_start_complete_cancel() {
+        let json = r#"{
+            "funding_address": "bcrt1qc2ylm4jxwm6w8yvyqhchfd3fbdr05yamxgzda6zqclh35rq6k4js2f0r8k",
+            "scriptpubkey": "0020c289fdd64676f4e3918405f174b629b46fa13bb3204dee8840c7ef1a0c1ab565"
+        }"#;
+        let start: FundChannelStart = serde_json::from_str(json).unwrap();
+        assert!(start.funding_address.starts_with("bcrt1q"));
+        assert!(start.close_to.is_none());
+        let ser = serde_json::to_value(&start).unwrap();
+        let des: FundChannelStart = serde_json::from_value(ser).unwrap();
+        assert_eq!(des.scriptpubkey, start.scriptpubkey);
+
+        let json = r#"{
+            "channel_id": "a1f6e8ee0c3d5f8b9db5c13cd4d2a0e05c6b4b0b1c8f3c2f6d1e8a7b9c0d1e2f",
+            "commitments_secured": true
+        }"#;
+        let complete: FundChannelComplete = serde_json::from_str(json).unwrap();
+        assert!(complete.commitments_secured);
+        let ser = serde_json::to_value(&complete).unwrap();
+        let des: FundChannelComplete = serde_json::from_value(ser).unwrap();
+        assert_eq!(des.channel_id, complete.channel_id);
+
+        let json = r#"{"cancelled": "Channel open canceled by RPC"}"#;
+        let cancel: FundChannelCancel = serde_json::from_str(json).unwrap();
+        let ser = serde_json::to_value(&cancel).unwrap();
+        let des: FundChannelCancel = serde_json::from_value(ser).unwrap();
+        assert_eq!(des.cancelled, "Channel open canceled by RPC");
+    }
+
+    #[test]
+    fn decodepay_route_hints() {
+        let json = r#"{
+            "currency": "bcrt",
+            "created_at": 1624548916,
+            "expiry": 604800,
+            "payee": "02a1633cafcc01ebfb6d78e39f687a1f0995c62fc95f51ead10a02ee0be551b5dc",
+            "amount_msat": "10000msat",
+            "description": "coffee",
+            "min_final_cltv_expiry": 10,
+            "payment_secret": "5c7e1a5bb3b9d5a0e7b40e0614c0c39cd0848e8f0b9fc7a4f1a0d1b6e0cde5f4",
+            "features": "02024100",
+            "routes": [[{
+                "pubkey": "029e03ff8d1dfb9a4361e8fd95c9f1fd58d4fd5ad2508e989f8e8aabe0d5a03c6b",
+                "short_channel_id": "103x1x0",
+                "fee_base_msat": 1000,
+                "fee_proportional_millionths": 10,
+                "cltv_expiry_delta": 6
+            }]],
+            "payment_hash": "a3f6ee3b8eae9d8f1de8fbd1a8b2e0c3f3c4d5e6f708192a3b4c5d6e7f801234",
+            "signature": "3045022100b6fe"
+        }"#;
+        let pay: DecodePay = serde_json::from_str(json).unwrap();
+        assert_eq!(pay.amount_msat, Some(common::MSat(10000)));
+        assert!(pay.description_hash.is_none());
+        assert_eq!(pay.routes.len(), 1);
+        assert_eq!(pay.routes[0][0].short_channel_id.to_string(), "103x1x0");
+        assert_eq!(pay.routes[0][0].fee_base_msat, common::MSat(1000));
+
+        let offer = r#"{
+            "type": "bolt12 offer",
+            "valid": true,
+            "offer_id": "9c3d4e",
+            "offer_description": "coffee",
+            "offer_node_id": "02a1633cafcc01ebfb6d78e39f687a1f0995c62fc95f51ead10a02ee0be551b5dc"
+        }"#;
+        let decoded: Decode = serde_json::from_str(offer).unwrap();
+        assert_eq!(decoded.type_, DecodeType::Bolt12Offer);
+        assert!(decoded.valid);
+        assert!(decoded.routes.is_none());
+    }
+
+    #[test]
+    fn createinvoice_roundtrip() {
+        // sha256 of 32 zero bytes
+        let preimage = "0000000000000000000000000000000000000000000000000000000000000000";
+        let hash = "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925";
+        let request = serde_json::to_value(crate::requests::CreateInvoice {
+            invstring: "lnbcrt1",
+            label: "hodl-1",
+            preimage,
+        })
+        .unwrap();
+        assert_eq!(request.get("invstring").unwrap().as_str(), Some("lnbcrt1"));
+        assert_eq!(request.get("preimage").unwrap().as_str(), Some(preimage));
+
+        let json = format!(
+            r#"{{
+                "label": "hodl-1",
+                "bolt11": "lnbcrt1",
+                "payment_hash": "{}",
+                "amount_msat": "5000msat",
+                "status": "unpaid",
+                "description": "hodl",
+                "expires_at": 1625153716
+            }}"#,
+            hash
+        );
+        let invoice: CreateInvoice = serde_json::from_str(&json).unwrap();
+        assert_eq!(invoice.payment_hash, hash);
+        assert_eq!(invoice.status, "unpaid");
+        assert!(invoice.pay_index.is_none());
+    }
+
+    #[test]
+    fn listpays_mixed_status() {
+        let json = r#"{"pays": [
+            {
+                "bolt11": "lnbcrt1",
+                "destination": "02a1633cafcc01ebfb6d78e39f687a1f0995c62fc95f51ead10a02ee0be551b5dc",
+                "payment_hash": "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925",
+                "status": "complete",
+                "created_at": 1624548916,
+                "completed_at": 1624548918,
+                "amount_msat": "10000msat",
+                "amount_sent_msat": "10001msat",
+                "preimage": "0000000000000000000000000000000000000000000000000000000000000000"
+            },
+            {
+                "destination": "02a1633cafcc01ebfb6d78e39f687a1f0995c62fc95f51ead10a02ee0be551b5dc",
+                "payment_hash": "ab",
+                "status": "failed",
+                "created_at": 1624548920,
+                "label": "retry"
+            },
+            {
+                "bolt11": "lnbcrt2",
+                "destination": "02a1633cafcc01ebfb6d78e39f687a1f0995c62fc95f51ead10a02ee0be551b5dc",
+                "payment_hash": "cd",
+                "status": "pending",
+                "created_at": 1624548930,
+                "amount_sent_msat": "5000msat"
+            }
+        ]}"#;
+        let pays: ListPays = serde_json::from_str(json).unwrap();
+        let statuses: Vec<_> = pays.pays.iter().map(|p| p.status.clone()).collect();
+        assert_eq!(
+            statuses,
+            vec![PayStatus::Complete, PayStatus::Failed, PayStatus::Pending]
+        );
+        assert_eq!(pays.pays[0].amount_sent_msat, Some(common::MSat(10001)));
+        assert!(pays.pays[1].amount_sent_msat.is_none());
+        assert!(pays.pays[1].bolt11.is_none());
+        assert!(pays.pays[2].preimage.is_none());
+    }
+
+    #[test]
+    fn getroute_multi_hop() {
+        let json = r#"{"route": [
+            {
+                "id": "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59",
+                "channel": "103x1x0",
+                "direction": 1,
+                "amount_msat": "1002msat",
+                "delay": 15,
+                "style": "tlv"
+            },
+            {
+                "id": "035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d",
+                "channel": "105x1x0",
+                "direction": 0,
+                "amount_msat": 1000,
+                "delay": 9,
+                "style": "tlv"
+            }
+        ]}"#;
+        let route: GetRoute = serde_json::from_str(json).unwrap();
+        assert_eq!(route.route.len(), 2);
+        assert_eq!(route.route[0].direction, 1);
+        assert_eq!(route.route[0].amount_msat, common::MSat(1002));
+        assert_eq!(route.route[1].delay, 9);
+        let item: common::RouteItem = route.route[1].clone().into();
+        assert_eq!(item.msatoshi, common::MSat(1000));
+        assert_eq!(item.channel.to_string(), "105x1x0");
+    }
+
+    #[test]
+    fn onion_roundtrip() {
+        let json = r#"{
+            "onion": "0003f3f80d2142b953319336d2fe4097",
+            "shared_secrets": [
+                "88ce98c73e4d2a4c1f3f4de1d3b0b1d8ee4c3b2a1f0e9d8c7b6a5f4e3d2c1b0a",
+                "1f0e9d8c7b6a5f4e3d2c1b0a88ce98c73e4d2a4c1f3f4de1d3b0b1d8ee4c3b2a"
+            ]
+        }"#;
+        let onion: CreateOnion = serde_json::from_str(json).unwrap();
+        assert_eq!(onion.shared_secrets.len(), 2);
+        let back: CreateOnion =
+            serde_json::from_value(serde_json::to_value(&onion).unwrap()).unwrap();
+        assert_eq!(back.onion, onion.onion);
+        assert_eq!(back.shared_secrets, onion.shared_secrets);
+
+        let json = r#"{
+            "message": "Monitor status with listpays or waitsendpay",
+            "id": 12,
+            "payment_hash": "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925",
+            "created_index": 12,
+            "destination": "035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d",
+            "amount_msat": 1000,
+            "amount_sent_msat": 1002,
+            "created_at": 1624548916,
+            "status": "pending"
+        }"#;
+        let sent: SendOnion = serde_json::from_str(json).unwrap();
+        assert_eq!(sent.amount_sent_msat, common::MSat(1002));
+        let back: SendOnion = serde_json::from_value(serde_json::to_value(&sent).unwrap()).unwrap();
+        assert_eq!(back.id, 12);
+        assert_eq!(back.status, "pending");
+        assert_eq!(back.payment_hash, sent.payment_hash);
+    }
+
+    #[test]
+    fn getinfo_numeric_port() {
+        let json = r#"{
+            "id": "02a1633cafcc01ebfb6d78e39f687a1f0995c62fc95f51ead10a02ee0be551b5dc",
+            "alias": "SILENTARTIST",
+            "color": "02a163",
+            "num_peers": 0,
+            "num_pending_channels": 0,
+            "num_active_channels": 0,
+            "num_inactive_channels": 0,
+            "address": [{"type": "ipv4", "address": "203.0.113.7", "port": 9735}],
+            "binding": [{"type": "ipv6", "address": "::", "port": 9735}],
+            "version": "v0.10.2",
+            "blockheight": 690000,
+            "network": "bitcoin",
+            "fees_collected_msat": "0msat",
+            "lightning-dir": "/home/ln/.lightning/bitcoin"
+        }"#;
+        let info: GetInfo = serde_json::from_str(json).unwrap();
+        match info.address[0] {
+            common::NetAddress::Ipv4 { port, .. } => assert_eq!(port, 9735),
+            ref other => panic!("expected ipv4 address, got {:?}", other),
+        }
+        match info.binding[0] {
+            common::NetAddress::Ipv6 { ref address, .. } => assert_eq!(address, "::"),
+            ref other => panic!("expected ipv6 address, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn listtransactions_with_tags() {
+        let json = r#"{"transactions": [{
+            "hash": "05985ec1d1a2ad4ad7a6b8a3b1ec5f3d0e2c9f6a7b8c9d0e1f2a3b4c5d6e7f80",
+            "rawtx": "02000000000101a1b2",
+            "blockheight": 682149,
+            "txindex": 1423,
+            "locktime": 682148,
+            "version": 2,
+            "inputs": [{
+                "txid": "9c5a0e1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7",
+                "index": 1,
+                "sequence": 4294967293
+            }],
+            "outputs": [
+                {
+                    "index": 0,
+                    "amount_msat": "1000000000msat",
+                    "scriptPubKey": "0020a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f9",
+                    "type": ["channel_funding"],
+                    "channel": "682149x1423x0"
+                },
+                {
+                    "index": 1,
+                    "amount_msat": "48990000msat",
+                    "scriptPubKey": "0014d1e2f3a4b5c6d7e8f90a1b2c3d4e5f60718293a4"
+                }
+            ]
+        }]}"#;
+        let txs: ListTransactions = serde_json::from_str(json).unwrap();
+        let tx = &txs.transactions[0];
+        assert_eq!(tx.blockheight, 682149);
+        assert_eq!(tx.inputs[0].sequence, 4294967293);
+        assert_eq!(
+            tx.outputs[0].type_,
+            Some(vec!["channel_funding".to_owned()])
+        );
+        assert_eq!(
+            tx.outputs[0].amount_msat,
+            common::MSat::from_sat(1_000_000).unwrap()
+        );
+        assert!(tx.outputs[1].type_.is_none());
+        assert!(tx.outputs[1].script_pub_key.starts_with("0014"));
+    }
+
+    #[test]
+    fn tx_staging_roundtrip() {
+        let json = r#"{"psbt": "cHNidP8BAF4CAAAA", "unsigned_tx": "0200000001", "txid": "ab12"}"#;
+        let prepared: TxPrepare = serde_json::from_str(json).unwrap();
+        let back: TxPrepare =
+            serde_json::from_value(serde_json::to_value(&prepared).unwrap()).unwrap();
+        assert_eq!(back.txid, "ab12");
+        assert_eq!(back.unsigned_tx, prepared.unsigned_tx);
+
+        let json = r#"{"psbt": "cHNidP8BAF4CAAAA", "tx": "020000000001", "txid": "ab12"}"#;
+        let sent: TxSend = serde_json::from_str(json).unwrap();
+        let back: TxSend = serde_json::from_value(serde_json::to_value(&sent).unwrap()).unwrap();
+        assert_eq!(back.tx, "020000000001");
+
+        let json = r#"{"unsigned_tx": "0200000001", "txid": "ab12"}"#;
+        let discarded: TxDiscard = serde_json::from_str(json).unwrap();
+        let back: TxDiscard =
+            serde_json::from_value(serde_json::to_value(&discarded).unwrap()).unwrap();
+        assert_eq!(back.txid, discarded.txid);
+    }
+
+    #[test]
+    fn ping_roundtrip() {
+        let ping: Ping = serde_json::from_str(r#"{"totlen": 132}"#).unwrap();
+        let back: Ping = serde_json::from_value(serde_json::to_value(&ping).unwrap()).unwrap();
+        assert_eq!(back.totlen, 132);
+    }
+
+    #[test]
+    fn setchannel_single_and_all() {
+        let single = r#"{"channels": [{
+            "peer_id": "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59",
+            "channel_id": "9d8c6b0a5f4e3d2c1b0a88ce98c73e4d2a4c1f3f4de1d3b0b1d8ee4c3b2a1f0e",
+            "short_channel_id": "103x1x0",
+            "fee_base_msat": 1000,
+            "fee_proportional_millionths": 10,
+            "minimum_htlc_out_msat": 0,
+            "maximum_htlc_out_msat": 990000000
+        }]}"#;
+        let set: SetChannel = serde_json::from_str(single).unwrap();
+        assert_eq!(set.channels.len(), 1);
+        assert_eq!(set.channels[0].fee_base_msat, common::MSat(1000));
+        assert_eq!(set.channels[0].fee_proportional_millionths, 10);
+
+        let all = r#"{"channels": [
+            {
+                "peer_id": "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59",
+                "channel_id": "9d8c6b0a5f4e3d2c1b0a88ce98c73e4d2a4c1f3f4de1d3b0b1d8ee4c3b2a1f0e",
+                "short_channel_id": "103x1x0",
+                "fee_base_msat": "0msat",
+                "fee_proportional_millionths": 500,
+                "minimum_htlc_out_msat": "1msat",
+                "maximum_htlc_out_msat": "990000000msat"
+            },
+            {
+                "peer_id": "035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d",
+                "channel_id": "1f0e9d8c7b6a5f4e3d2c1b0a88ce98c73e4d2a4c1f3f4de1d3b0b1d8ee4c3b2a",
+                "fee_base_msat": "0msat",
+                "fee_proportional_millionths": 500,
+                "minimum_htlc_out_msat": "1msat",
+                "maximum_htlc_out_msat": "5000000msat",
+                "warning_htlcmax_too_high": "Set maximum_htlc_out_msat to maximum possible in channel"
+            }
+        ]}"#;
+        let set: SetChannel = serde_json::from_str(all).unwrap();
+        assert_eq!(set.channels.len(), 2);
+        assert!(set.channels[1].short_channel_id.is_none());
+        assert!(set.channels[1].warning_htlcmax_too_high.is_some());
+        assert_eq!(
+            set.channels[1].maximum_htlc_out_msat,
+            common::MSat(5_000_000)
+        );
+    }
+
+    #[test]
+    fn delinvoice_round_trip() {
+        let json = r#"{
+            "label": "order-42",
+            "bolt11": "lnbcrt10n1pw",
+            "payment_hash": "5a5c9b8f3e6e4d1b2d4a3b0c1e2f3a4b5c6d7e8f9a0b1c2d3e4f5a6b7c8d9e0f",
+            "amount_msat": "1000msat",
+            "status": "expired",
+            "description": "coffee",
+            "expires_at": 1600000000
+        }"#;
+        let deleted: DelInvoice = serde_json::from_str(json).unwrap();
+        assert_eq!(deleted.label, "order-42");
+        assert_eq!(deleted.status, "expired");
+        assert_eq!(deleted.amount_msat, Some(common::MSat(1000)));
+        assert_eq!(deleted.pay_index, None);
+
+        let ser = serde_json::to_value(&deleted).unwrap();
+        assert_eq!(ser["amount_msat"], Value::from(1000));
+        let des: DelInvoice = serde_json::from_value(ser).unwrap();
+        assert_eq!(des.payment_hash, deleted.payment_hash);
+        assert_eq!(des.amount_msat, deleted.amount_msat);
+        assert_eq!(des.expires_at, 1600000000);
+
+        // "any" amount invoices have no amount_msat
+        let json = r#"{
+            "label": "donation",
+            "payment_hash": "00",
+            "status": "paid",
+            "expires_at": 1600000000,
+            "pay_index": 3,
+            "paid_at": 1599999000
+        }"#;
+        let deleted: DelInvoice = serde_json::from_str(json).unwrap();
+        assert!(deleted.amount_msat.is_none());
+        assert_eq!(deleted.pay_index, Some(3));
+    }
+
+    #[test]
+    fn connect_ipv4_round_trip() {
+        let json = r#"{
+            "id": "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59",
+            "features": "08a0000a8a5961",
+            "direction": "out",
+            "address": {"type": "ipv4", "address": "127.0.0.1", "port": 9735}
+        }"#;
+        let connect: Connect = serde_json::from_str(json).unwrap();
+        assert_eq!(connect.direction, Some(ConnectDirection::Out));
+        assert_eq!(
+            connect.address,
+            Some(common::NetAddress::Ipv4 {
+                address: "127.0.0.1".to_owned(),
+                port: 9735
+            })
+        );
+
+        let ser = serde_json::to_value(&connect).unwrap();
+        assert_eq!(ser["address"]["type"], Value::from("ipv4"));
+        let des: Connect = serde_json::from_value(ser).unwrap();
+        assert_eq!(des.address, connect.address);
+        assert_eq!(des.features, connect.features);
+    }
+
+    #[test]
+    fn connect_torv3_round_trip() {
+        let json = r#"{
+            "id": "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59",
+            "features": "08a0000a8a5961",
+            "direction": "in",
+            "address": {
+                "type": "torv3",
+                "address": "vww6ybal4bd7szmgncyruucpgfkqahzddi37ktceo3ah7ngmcopnpyyd.onion",
+                "port": 9735
+            }
+        }"#;
+        let connect: Connect = serde_json::from_str(json).unwrap();
+        assert_eq!(connect.direction, Some(ConnectDirection::In));
+        match connect.address {
+            Some(common::NetAddress::Torv3 { ref address, port }) => {
+                assert!(address.ends_with(".onion"));
+                assert_eq!(port, 9735);
+            }
+            ref other => panic!("expected torv3 address, got {:?}", other),
+        }
+
+        let ser = serde_json::to_value(&connect).unwrap();
+        assert_eq!(ser["address"]["type"], Value::from("torv3"));
+        let des: Connect = serde_json::from_value(ser).unwrap();
+        assert_eq!(des.address, connect.address);
+
+        let unknown: common::NetAddress =
+            serde_json::from_str(r#"{"type": "i2p", "address": "x", "port": 1}"#).unwrap();
+        assert_eq!(unknown, common::NetAddress::Unknown);
+    }
+
+    #[test]
+    fn listclosedchannels_parse() {
+        let json = r#"{
+            "closedchannels": [
+                {
+                    "peer_id": "035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d",
+                    "channel_id": "3e2d4d8a5d0e3b1c5a1c0d1f2e3a4b5c6d7e8f9a0b1c2d3e4f5a6b7c8d9e0f1a",
+                    "short_channel_id": "103x1x0",
+                    "funding_txid": "1a0f9e8d7c6b5a4f3e2d1c0b9a8f7e6d5c4b3a2e1f0d1c5a1c3b0e5d8a4d2e3e",
+                    "total_msat": 1000000000,
+                    "final_to_us_msat": "499990000msat",
+                    "closer": "local",
+                    "close_cause": "user"
+                },
+                {
+                    "channel_id": "00",
+                    "funding_txid": "00",
+                    "total_msat": 20000,
+                    "final_to_us_msat": 0,
+                    "close_cause": "unknown"
+                }
+            ]
+        }"#;
+        let closed: ListClosedChannels = serde_json::from_str(json).unwrap();
+        assert_eq!(closed.closedchannels.len(), 2);
+        let first = &closed.closedchannels[0];
+        assert_eq!(first.short_channel_id.unwrap().to_string(), "103x1x0");
+        assert_eq!(first.final_to_us_msat, common::MSat(499990000));
+        assert_eq!(first.closer.as_deref(), Some("local"));
+        assert_eq!(first.close_cause, CloseCause::User);
+        let second = &closed.closedchannels[1];
+        assert!(second.peer_id.is_none() && second.short_channel_id.is_none());
+        assert_eq!(second.close_cause, CloseCause::Unspecified);
+    }
+
+    #[test]
+    fn newaddr_round_trip() {
+        use crate::requests::AddressType;
+
+        let cases = [
+            (AddressType::Bech32, "bech32", true, false),
+            (AddressType::P2shSegwit, "p2sh-segwit", false, true),
+            (AddressType::All, "all", true, true),
+        ];
+        for &(addresstype, name, bech32, p2sh_segwit) in cases.iter() {
+            let request = crate::requests::NewAddr { addresstype };
+            let ser = serde_json::to_value(&request).unwrap();
+            assert_eq!(ser["addresstype"], Value::from(name));
+            let des: crate::requests::NewAddr = serde_json::from_value(ser).unwrap();
+            assert_eq!(des.addresstype, addresstype);
+
+            let mut json = serde_json::Map::new();
+            if bech32 {
+                json.insert(
+                    "bech32".to_owned(),
+                    Value::from("bcrt1qq8adjz4u6enf0cjey9j8yt0y490tact9fahkwf"),
+                );
+            }
+            if p2sh_segwit {
+                json.insert(
+                    "p2sh-segwit".to_owned(),
+                    Value::from("2N4Qro3fDfUKD5mT1Ki3gPwVJ1qgFpJhYcj"),
+                );
+            }
+            let newaddr: NewAddr = serde_json::from_value(Value::from(json.clone())).unwrap();
+            assert_eq!(newaddr.bech32.is_some(), bech32);
+            assert_eq!(newaddr.p2sh_segwit.is_some(), p2sh_segwit);
+            let ser = serde_json::to_value(&newaddr).unwrap();
+            let des: NewAddr = serde_json::from_value(ser).unwrap();
+            assert_eq!(des.bech32, newaddr.bech32);
+            assert_eq!(des.p2sh_segwit, newaddr.p2sh_segwit);
+        }
+    }
+
+    #[test]
+    fn withdraw_round_trip() {
+        let json = r#"{
+            "tx": "02000000000101",
+            "txid": "f5dfb2ff1fd1a1a8c2e1fc3a8a5e3b1de7df6389a359312a1777cd4b5d5b6b07",
+            "psbt": "cHNidP8BAF4CAAAAAQ"
+        }"#;
+        let withdraw: Withdraw = serde_json::from_str(json).unwrap();
+        assert_eq!(withdraw.psbt.as_deref(), Some("cHNidP8BAF4CAAAAAQ"));
+        let ser = serde_json::to_value(&withdraw).unwrap();
+        let des: Withdraw = serde_json::from_value(ser).unwrap();
+        assert_eq!(des.tx, withdraw.tx);
+        assert_eq!(des.txid, withdraw.txid);
+        assert_eq!(des.psbt, withdraw.psbt);
+    }
+
+    #[test]
+    fn getinfo_synced() {
+        let json = r#"{
+            "id": "02e25b0d1ea2b95ea2e2cd4bfe16a5e81ed9e6ad1e8b4f3d0c1a5b9b5f1e0d0c9a",
+            "alias": "SLICKERGOPHER",
+            "color": "02e25b",
+            "num_peers": 3,
+            "num_pending_channels": 0,
+            "num_active_channels": 2,
+            "num_inactive_channels": 1,
+            "address": [
+                {"type": "ipv4", "address": "203.0.113.5", "port": 9735},
+                {"type": "torv3", "address": "vww6ybal4bd7szmgncyruucpgfkqahzddi37ktceo3ah7ngmcopnpyyd.onion", "port": 9735}
+            ],
+            "binding": [
+                {"type": "ipv6", "address": "::", "port": 9735},
+                {"type": "local socket", "socket": "/tmp/l1/lightning-rpc"}
+            ],
+            "version": "v23.08.1",
+            "blockheight": 807124,
+            "network": "bitcoin",
+            "fees_collected_msat": 12345,
+            "lightning-dir": "/home/ln/.lightning/bitcoin",
+            "our_features": {"init": "08a0000a8a5961"}
+        }"#;
+        let info: GetInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(info.num_active_channels, 2);
+        assert_eq!(info.fees_collected_msat, common::MSat(12345));
+        assert_eq!(info.lightning_dir, "/home/ln/.lightning/bitcoin");
+        assert!(info.our_features.as_ref().unwrap().init.anchors());
+        assert_eq!(
+            info.binding[1],
+            common::NetAddress::LocalSocket {
+                socket: "/tmp/l1/lightning-rpc".to_owned()
+            }
+        );
+        assert!(info.warning_bitcoind_sync.is_none());
+        assert!(info.warning_lightningd_sync.is_none());
+
+        let ser = serde_json::to_value(&info).unwrap();
+        assert_eq!(
+            ser["lightning-dir"],
+            Value::from("/home/ln/.lightning/bitcoin")
+        );
+        let des: GetInfo = serde_json::from_value(ser).unwrap();
+        assert_eq!(des.address, info.address);
+        assert_eq!(des.binding, info.binding);
+        assert_eq!(des.blockheight, info.blockheight);
+    }
+
+    #[test]
+    fn getinfo_syncing() {
+        let json = r#"{
+            "id": "02e25b0d1ea2b95ea2e2cd4bfe16a5e81ed9e6ad1e8b4f3d0c1a5b9b5f1e0d0c9a",
+            "alias": "SLICKERGOPHER",
+            "color": "02e25b",
+            "num_peers": 0,
+            "num_pending_channels": 0,
+            "num_active_channels": 0,
+            "num_inactive_channels": 0,
+            "address": [],
+            "binding": [{"type": "ipv4", "address": "127.0.0.1", "port": 19846}],
+            "version": "v23.08.1",
+            "blockheight": 1200,
+            "network": "regtest",
+            "fees_collected_msat": "0msat",
+            "lightning-dir": "/tmp/l1/regtest",
+            "warning_bitcoind_sync": "Bitcoind is not up-to-date with network.",
+            "warning_lightningd_sync": "Still loading latest blocks from bitcoind."
+        }"#;
+        let info: GetInfo = serde_json::from_str(json).unwrap();
+        assert!(info.address.is_empty());
+        assert_eq!(info.fees_collected_msat, common::MSat(0));
+        assert!(info.warning_bitcoind_sync.is_some());
+        assert!(info.warning_lightningd_sync.is_some());
+
+        let ser = serde_json::to_value(&info).unwrap();
+        let des: GetInfo = serde_json::from_value(ser).unwrap();
+        assert_eq!(des.warning_bitcoind_sync, info.warning_bitcoind_sync);
+        assert_eq!(des.warning_lightningd_sync, info.warning_lightningd_sync);
+    }
+
+    #[test]
+    fn listfunds_reserved_and_spent() {
+        let json = r#"{
+            "outputs": [
+                {
+                    "txid": "8fd9b3a3d8c2e7b0e5f3a1c2b4d6e8f0a1b3c5d7e9f1a3b5c7d9e1f3a5b7c9d1",
+                    "output": 1,
+                    "amount_msat": "200000000msat",
+                    "scriptpubkey": "00141c8be5c9e5d0cb3b3a1b5b8b2b0a8c1e1c3e5f7a",
+                    "address": "bcrt1qrj97tj096r9nkwsmtw9jkz5vrcwrcl0629d4rx",
+                    "status": "confirmed",
+                    "reserved": true,
+                    "reserved_to_block": 1345,
+                    "blockheight": 1201
+                },
+                {
+                    "txid": "d1c9b7a5f3e1d9c7b5a3f1e9d7c5b3a1f0e8d6b4c2a1f3e5b0e7c2d8a3b3d9f8",
+                    "output": 0,
+                    "amount_msat": 50000000,
+                    "scriptpubkey": "5120f1e2d3c4b5a6978877665544332211aabbccddeeff00112233445566778899",
+                    "status": "spent",
+                    "reserved": false,
+                    "blockheight": 1100
+                },
+                {
+                    "txid": "00",
+                    "output": 0,
+                    "amount_msat": 1000,
+                    "scriptpubkey": "00",
+                    "status": "unconfirmed",
+                    "reserved": false
+                }
+            ],
+            "channels": [
+                {
+                    "peer_id": "035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d",
+                    "connected": true,
+                    "state": "CHANNELD_NORMAL",
+                    "channel_id": "a1b3c5d7e9f1a3b5c7d9e1f3a5b7c9d1e3f5a7b9c1d3e5f7a9b1c3d5e7f9a1b3",
+                    "short_channel_id": "103x1x0",
+                    "our_amount_msat": "499990000msat",
+                    "amount_msat": "1000000000msat",
+                    "funding_txid": "b3a1f9e7d5c3b1a9f7e5d3c1b9a7f5e3d1c9b7a5f3e1d9c7b5a3f1e9d7c5b3a1",
+                    "funding_output": 0
+                },
+                {
+                    "peer_id": "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59",
+                    "connected": false,
+                    "state": "CHANNELD_AWAITING_LOCKIN",
+                    "our_amount_msat": 0,
+                    "amount_msat": 20000000,
+                    "funding_txid": "00",
+                    "funding_output": 1
+                }
+            ]
+        }"#;
+        let funds: ListFunds = serde_json::from_str(json).unwrap();
+        let outputs = &funds.outputs;
+        assert!(outputs[0].reserved);
+        assert_eq!(outputs[0].status, OutputStatus::Confirmed);
+        assert_eq!(outputs[0].amount_msat, common::MSat(200000000));
+        assert_eq!(outputs[1].status, OutputStatus::Spent);
+        assert!(outputs[1].address.is_none());
+        assert_eq!(outputs[2].blockheight, None);
+
+        let channels = &funds.channels;
+        assert!(channels[0].state.is_active());
+        assert_eq!(channels[0].short_channel_id.unwrap().to_string(), "103x1x0");
+        assert_eq!(channels[0].our_amount_msat, common::MSat(499990000));
+        assert!(!channels[1].connected);
+        assert!(channels[1].short_channel_id.is_none());
+
+        let ser = serde_json::to_value(&funds).unwrap();
+        let des: ListFunds = serde_json::from_value(ser).unwrap();
+        assert_eq!(des.outputs.len(), 3);
+        assert_eq!(des.outputs[1].status, OutputStatus::Spent);
+        assert_eq!(des.channels[1].state, channels[1].state);
+    }
+
+    #[test]
+    fn close_round_trip() {
+        let json = r#"{
+            "tx": "0200000001a1b3c5",
+            "txid": "e7c68a5e1f3a9f2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f70819203",
+            "type": "mutual"
+        }"#;
+        let close: Close = serde_json::from_str(json).unwrap();
+        assert_eq!(close.type_, CloseType::Mutual);
+        let des: Close = serde_json::from_value(serde_json::to_value(&close).unwrap()).unwrap();
+        assert_eq!(des.type_, CloseType::Mutual);
+        assert_eq!(des.tx, close.tx);
+        assert_eq!(des.txid, close.txid);
+
+        let json = r#"{
+            "tx": "0200000001d5c3b1",
+            "txid": "3092817f6e5d4c3b2a1908f7e6d5c4b3a2918070f6e5d4c3b2a9f3f1e5a86c7e",
+            "type": "unilateral"
+        }"#;
+        let close: Close = serde_json::from_str(json).unwrap();
+        assert_eq!(close.type_, CloseType::Unilateral);
+        let ser = serde_json::to_value(&close).unwrap();
+        assert_eq!(ser["type"], Value::from("unilateral"));
+        let des: Close = serde_json::from_value(ser).unwrap();
+        assert_eq!(des.type_, CloseType::Unilateral);
+        assert_eq!(des.txid, close.txid);
+
+        let close: Close = serde_json::from_str(r#"{"type": "unopened"}"#).unwrap();
+        assert_eq!(close.type_, CloseType::Unopened);
+        assert!(close.tx.is_none() && close.txid.is_none());
+    }
+
+    #[test]
+    fn fundchannel_round_trip() {
+        let json = r#"{
+            "tx": "020000000001019a",
+            "txid": "5b0e8f7d3c2a1b9f0e8d7c6b5a4f3e2d1c0b9a8f7e6d5c4b3a2f1e0d9c8b7a6f",
+            "channel_id": "6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b1c2d3e4f5a6b7c8d9e0f1b2a3c7d8f0e5b",
+            "outnum": 1,
+            "close_to": "0014a8e5a0d6e7f2c1b3e1d2c4b5a697887766554433"
+        }"#;
+        let fund: FundChannel = serde_json::from_str(json).unwrap();
+        assert_eq!(fund.outnum, 1);
+        let des: FundChannel =
+            serde_json::from_value(serde_json::to_value(&fund).unwrap()).unwrap();
+        assert_eq!(des.txid, fund.txid);
+        assert_eq!(des.channel_id, fund.channel_id);
+        assert_eq!(des.outnum, fund.outnum);
+        assert_eq!(des.close_to, fund.close_to);
+
+        let json = r#"{"tx": "00", "txid": "00", "channel_id": "00", "outnum": 0}"#;
+        let fund: FundChannel = serde_json::from_str(json).unwrap();
+        assert!(fund.close_to.is_none());
+    }
+
+    #[test]
+    fn pay_multi_part() {
+        let json = r#"{
+            "destination": "035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d",
+            "payment_hash": "8c4c2c1e8cd4bd2e7c6f1bf8e1e0b8b4b5f3b7c6d2a1e0f9e8d7c6b5a4f3e2d1",
+            "created_at": 1661371840.476,
+            "parts": 3,
+            "amount_msat": "100000000msat",
+            "amount_sent_msat": 100011502,
+            "payment_preimage": "0b7a1c2d3e4f5a6b7c8d9e0f1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b",
+            "status": "complete"
+        }"#;
+        let pay: Pay = serde_json::from_str(json).unwrap();
+        assert_eq!(pay.parts, 3);
+        assert_eq!(pay.status, PayStatus::Complete);
+        assert_eq!(pay.amount_msat, common::MSat(100000000));
+        assert_eq!(pay.amount_sent_msat, common::MSat(100011502));
+        assert!(pay.amount_sent_msat > pay.amount_msat);
+        assert!(pay.warning_partial_completion.is_none());
+
+        let des: Pay = serde_json::from_value(serde_json::to_value(&pay).unwrap()).unwrap();
+        assert_eq!(des.parts, pay.parts);
+        assert_eq!(des.created_at, pay.created_at);
+        assert_eq!(des.payment_preimage, pay.payment_preimage);
+        assert_eq!(des.destination, pay.destination);
+    }
+
+    #[test]
+    fn listpeers_htlc_in_flight() {
+        let json = r#"{
+            "peers": [{
+                "id": "035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d",
+                "connected": true,
+                "netaddr": ["127.0.0.1:39455"],
+                "features": "08a0000a0a69a2",
+                "channels": [{
+                    "state": "CHANNELD_NORMAL",
+                    "short_channel_id": "103x1x0",
+                    "channel_id": "a1b3c5d7e9f1a3b5c7d9e1f3a5b7c9d1e3f5a7b9c1d3e5f7a9b1c3d5e7f9a1b3",
+                    "funding_txid": "b3a1f9e7d5c3b1a9f7e5d3c1b9a7f5e3d1c9b7a5f3e1d9c7b5a3f1e9d7c5b3a1",
+                    "to_us_msat": "899990000msat",
+                    "total_msat": "1000000000msat",
+                    "their_reserve_msat": "10000000msat",
+                    "our_reserve_msat": "10000000msat",
+                    "spendable_msat": "872450000msat",
+                    "receivable_msat": "89990000msat",
+                    "fee_base_msat": "1000msat",
+                    "fee_proportional_millionths": 10,
+                    "htlcs": [{
+                        "direction": "out",
+                        "id": 0,
+                        "amount_msat": "100000000msat",
+                        "expiry": 125,
+                        "payment_hash": "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925",
+                        "state": "SENT_ADD_ACK_REVOCATION"
+                    }]
+                }]
+            }, {
+                "id": "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59",
+                "connected": false,
+                "channels": []
+            }]
+        }"#;
+        let peers: ListPeers = serde_json::from_str(json).unwrap();
+        let channel = &peers.peers[0].channels[0];
+        assert!(channel.state.is_active());
+        assert_eq!(channel.fee_base_msat, Some(common::MSat(1000)));
+        assert_eq!(channel.htlcs.len(), 1);
+        let htlc = &channel.htlcs[0];
+        assert_eq!(htlc.direction, HtlcDirection::Out);
+        assert_eq!(htlc.amount_msat, common::MSat(100000000));
+        assert_eq!(htlc.expiry, 125);
+        assert!(peers.peers[1].features.is_none());
+
+        let des: ListPeers = serde_json::from_value(serde_json::to_value(&peers).unwrap()).unwrap();
+        let back = &des.peers[0].channels[0];
+        assert_eq!(back.to_us_msat, channel.to_us_msat);
+        assert_eq!(back.receivable_msat, channel.receivable_msat);
+        assert_eq!(back.htlcs[0].payment_hash, htlc.payment_hash);
+        assert_eq!(back.htlcs[0].state, htlc.state);
+        assert_eq!(
+            des.peers[0].netaddr.as_ref().unwrap()[0],
+            common::NetAddress::Ipv4 {
+                address: "127.0.0.1".to_owned(),
+                port: 39455
+            }
+        );
+        assert_eq!(des.peers[1].netaddr, None);
+    }
+
+    #[test]
+    fn offer_round_trip() {
+        let json = r#"{
+            "offer_id": "dca9774ba2925b48c42eb12e599c09389d9d80d44445c4d0c944556c7f7fd508",
+            "active": true,
+            "single_use": false,
+            "bolt12": "lno1qgsqvgnwgcg35z6ee2h3yczraddm72xrfua9uve2rlrm9deu7xyfzrcgqgn3qzsyvfkx26qkyypvr5hfx60h9w9k934lt8s2n6zc0wwtgqlulw7dythr83dqx8tzumg",
+            "used": false,
+            "created": true,
+            "label": "coffee"
+        }"#;
+        let offer: Offer = serde_json::from_str(json).unwrap();
+        assert!(offer.active && offer.created && !offer.used);
+        assert_eq!(offer.label.as_deref(), Some("coffee"));
+        let des: Offer = serde_json::from_value(serde_json::to_value(&offer).unwrap()).unwrap();
+        assert_eq!(des.bolt12, offer.bolt12);
+    }
+
+    #[test]
+    fn fetchinvoice_round_trip() {
+        let json = r#"{
+            "invoice": "lni1qqg0qe01",
+            "changes": {"description_appended": ", extra shot", "amount_msat": 2000000},
+            "next_period": {
+                "counter": 1,
+                "starttime": 1700003600,
+                "endtime": 1700007199,
+                "paywindow_start": 1700003600,
+                "paywindow_end": 1700007199
+            }
+        }"#;
+        let fetched: FetchInvoice = serde_json::from_str(json).unwrap();
+        assert_eq!(fetched.changes.amount_msat, Some(common::MSat(2_000_000)));
+        assert!(fetched.changes.description.is_none());
+        assert_eq!(fetched.next_period.as_ref().unwrap().counter, 1);
+        let des: FetchInvoice =
+            serde_json::from_value(serde_json::to_value(&fetched).unwrap()).unwrap();
+        assert_eq!(des.invoice, "lni1qqg0qe01");
+
+        let single: FetchInvoice =
+            serde_json::from_str(r#"{"invoice": "lni1qqg0qe02", "changes": {}}"#).unwrap();
+        assert!(single.next_period.is_none());
+    }
+
+    #[test]
+    fn listconfigs_accessors() {
+        let flat = r##"{
+            "# version": "v0.12.1",
+            "lightning-dir": "/home/user/.lightning/regtest",
+            "network": "regtest",
+            "allow-deprecated-apis": true,
+            "rpc-file": "lightning-rpc",
+            "disable-plugin": [],
+            "always-use-proxy": false,
+            "daemon": "false",
+            "alias": "SILENTARTIST",
+            "rgb": "022d22",
+            "fee-base": 1000,
+            "fee-per-satoshi": 10,
+            "min-capacity-sat": 10000,
+            "addr": "127.0.0.1:9735",
+            "max-locktime-blocks": 2016,
+            "plugins": [{"path": "/usr/libexec/c-lightning/plugins/pay", "name": "pay"}]
+        }"##;
+        let configs: ListConfigs = serde_json::from_str(flat).unwrap();
+        assert_eq!(configs.network(), Some(common::Network::Regtest));
+        assert_eq!(configs.alias(), Some("SILENTARTIST"));
+        assert_eq!(configs.rgb(), Some("022d22"));
+        assert_eq!(configs.fee_base(), Some(1000));
+        assert_eq!(configs.fee_per_satoshi(), Some(10));
+        assert_eq!(configs.get("min-capacity-sat"), Some(&Value::from(10000)));
+        assert!(configs.get("plugins").unwrap().is_array());
+        assert!(configs.get("proxy").is_none());
+
+        let nested = r#"{
+            "configs": {
+                "network": {"value_str": "testnet", "source": "/home/user/.lightning/config:1"},
+                "alias": {"value_str": "node", "source": "cmdline"},
+                "fee-base": {"value_int": 1, "source": "default"},
+                "fee-per-satoshi": {"value_int": 100, "source": "default"},
+                "log-level": {"values_str": ["debug"], "sources": ["cmdline"]},
+                "disable-plugin": {"values_str": ["offers", "bookkeeper"], "sources": ["cmdline", "cmdline"]}
+            }
+        }"#;
+        let configs: ListConfigs = serde_json::from_str(nested).unwrap();
+        assert_eq!(configs.network(), Some(common::Network::Testnet));
+        assert_eq!(configs.fee_base(), Some(1));
+        assert_eq!(configs.fee_per_satoshi(), Some(100));
+        assert!(configs.rgb().is_none());
+        assert_eq!(
+            configs.get("log-level"),
+            Some(&serde_json::json!(["debug"]))
+        );
+        assert_eq!(
+            configs.get("disable-plugin"),
+            Some(&serde_json::json!(["offers", "bookkeeper"]))
+        );
+    }
+
+    #[test]
+    fn plugin_round_trip() {
+        let json = r#"{
+            "command": "list",
+            "plugins": [
+                {"name": "/usr/libexec/c-lightning/plugins/pay", "active": true, "dynamic": false},
+                {"name": "/home/user/plugins/summary.py", "active": true, "dynamic": true}
+            ]
+        }"#;
+        let list: Plugins = serde_json::from_str(json).unwrap();
+        assert_eq!(list.plugins.len(), 2);
+        assert_eq!(list.plugins[1].dynamic, Some(true));
+
+        let json = r#"{
+            "command": "start",
+            "plugins": [{"name": "/home/user/plugins/helloworld.py", "active": false}]
+        }"#;
+        let started: Plugins = serde_json::from_str(json).unwrap();
+        let des: Plugins = serde_json::from_value(serde_json::to_value(&started).unwrap()).unwrap();
+        assert_eq!(des.command, "start");
+        assert!(!des.plugins[0].active);
+        assert!(des.plugins[0].dynamic.is_none());
+    }
+
+    #[test]
+    fn invoice_warnings() {
+        let json = r#"{
+            "payment_hash": "f2a3dc6566c1ca2c8ac8d3efba1cbc2f7b4d8b34b53f87c6db1312d0dcf3cd1c",
+            "expires_at": 1700604800,
+            "bolt11": "lnbcrt10m1pjq8xyzpp5",
+            "payment_secret": "2b3a2f7b2a0e1fa0c3b7d2f4a3c5e8d1b4a6c9e2f5a8b1c4d7e0f3a6b9c2d5e8",
+            "warning_capacity": "Insufficient incoming channel capacity to pay invoice"
+        }"#;
+        let invoice: Invoice = serde_json::from_str(json).unwrap();
+        let des: Invoice = serde_json::from_value(serde_json::to_value(&invoice).unwrap()).unwrap();
+        assert_eq!(
+            des.warning_capacity.as_deref(),
+            Some("Insufficient incoming channel capacity to pay invoice")
+        );
+        assert!(des.warning_offline.is_none());
+        assert!(des.warning_mpp.is_none());
+    }
+
+    #[test]
+    fn listnodes_listchannels() {
+        let json = r#"{
+            "nodes": [{
+                "nodeid": "02312627fdf07fbdd7e5ddb136611bdde9b00d26821d14d94891395452f67af248",
+                "alias": "VIOLENTSPAWN",
+                "color": "023127",
+                "last_timestamp": 1700000000,
+                "features": "88a0000a8a5961",
+                "addresses": [{"type": "ipv4", "address": "203.0.113.7", "port": 9735}]
+            }, {
+                "nodeid": "035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d"
+            }]
+        }"#;
+        let nodes: ListNodes = serde_json::from_str(json).unwrap();
+        assert_eq!(nodes.nodes[0].alias.as_deref(), Some("VIOLENTSPAWN"));
+        assert_eq!(nodes.nodes[0].addresses.as_ref().unwrap().len(), 1);
+        assert!(nodes.nodes[1].last_timestamp.is_none());
+        let old: ListNodesItem =
+            serde_json::from_str(r#"{"nodeid": "02aa", "global_features": "8a"}"#).unwrap();
+        assert_eq!(old.features.unwrap().to_string(), "8a");
+
+        let json = r#"{
+            "channels": [{
+                "source": "02312627fdf07fbdd7e5ddb136611bdde9b00d26821d14d94891395452f67af248",
+                "destination": "035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d",
+                "short_channel_id": "103x1x0",
+                "direction": 0,
+                "public": true,
+                "amount_msat": 1000000000,
+                "message_flags": 1,
+                "channel_flags": 0,
+                "active": true,
+                "last_update": 1700000000,
+                "base_fee_millisatoshi": 1000,
+                "fee_per_millionth": 10,
+                "delay": 6,
+                "htlc_minimum_msat": 0,
+                "htlc_maximum_msat": 990000000,
+                "features": ""
+            }]
+        }"#;
+        let channels: ListChannels = serde_json::from_str(json).unwrap();
+        let channel = &channels.channels[0];
+        assert_eq!(channel.direction, 0);
+        assert_eq!(channel.amount_msat, common::MSat(1_000_000_000));
+        assert_eq!(channel.fee_base_msat, common::MSat(1000));
+        assert_eq!(channel.htlc_maximum_msat, Some(common::MSat(990_000_000)));
+        let ser = serde_json::to_value(channel).unwrap();
+        assert_eq!(ser["base_fee_millisatoshi"], 1000);
+    }
+
+    #[test]
+    fn psbt_round_trip() {
+        let json = r#"{"signed_psbt": "cHNidP8BAFICAAAAAZ38ZijCbFiZ/hvT3DOGZb/VXXraEPYiCXPfLTht7BJ2AQAAAAD9////AQAAAAAAAAAAFgAUdp/OPbKk7mp4h2ifRe8hezdAAA=="}"#;
+        let signed: SignPsbt = serde_json::from_str(json).unwrap();
+        let back: SignPsbt =
+            serde_json::from_value(serde_json::to_value(&signed).unwrap()).unwrap();
+        assert_eq!(back.signed_psbt, signed.signed_psbt);
+
+        let json = r#"{
+            "tx": "02000000000101fc6628c26c5899fe1bd3dc338665bfd55d7ada10f6220973df2d386dec127601000000",
+            "txid": "05985cb4ec99fd3a8a5e46e35eb6cede8d13fd5c9b2206350cedf7f0b6ac92e1"
+        }"#;
+        let sent: SendPsbt = serde_json::from_str(json).unwrap();
+        let back: SendPsbt = serde_json::from_value(serde_json::to_value(&sent).unwrap()).unwrap();
+        assert_eq!(back.txid, sent.txid);
+        assert!(back.tx.starts_with("0200"));
+    }
+
+    #[test]
+    fn reservations_round_trip() {
+        let json = r#"{
+            "reservations": [
+                {
+                    "txid": "05985cb4ec99fd3a8a5e46e35eb6cede8d13fd5c9b2206350cedf7f0b6ac92e1",
+                    "vout": 0,
+                    "was_reserved": false,
+                    "reserved": true,
+                    "reserved_to_block": 817
+                },
+                {
+                    "txid": "4f5bcd1e0b3cb7a8a8d0d2c7e4c0b6f53d0e4b8e3c35a4e1d37bc6f9c7e0a112",
+                    "vout": 1,
+                    "was_reserved": true,
+                    "reserved": true,
+                    "reserved_to_block": 889
+                }
+            ]
+        }"#;
+        let reserved: ReserveInputs = serde_json::from_str(json).unwrap();
+        assert_eq!(reserved.reservations.len(), 2);
+        assert!(!reserved.reservations[0].was_reserved);
+        assert_eq!(reserved.reservations[1].vout, 1);
+        assert_eq!(reserved.reservations[1].reserved_to_block, Some(889));
+        let back: ReserveInputs =
+            serde_json::from_value(serde_json::to_value(&reserved).unwrap()).unwrap();
+        assert_eq!(back.reservations[0].txid, reserved.reservations[0].txid);
+
+        let json = r#"{
+            "reservations": [
+                {
+                    "txid": "05985cb4ec99fd3a8a5e46e35eb6cede8d13fd5c9b2206350cedf7f0b6ac92e1",
+                    "vout": 0,
+                    "was_reserved": true,
+                    "reserved": false
+                }
+            ]
+        }"#;
+        let released: UnreserveInputs = serde_json::from_str(json).unwrap();
+        assert!(!released.reservations[0].reserved);
+        assert_eq!(released.reservations[0].reserved_to_block, None);
+    }
+
+    #[test]
+    fn funded_psbt_round_trip() {
+        let json = r#"{
+            "psbt": "cHNidP8BADMCAAAAAQ",
+            "feerate_per_kw": 253,
+            "estimated_final_weight": 664,
+            "excess_msat": "2000000msat",
+            "reservations": [
+                {
+                    "txid": "05985cb4ec99fd3a8a5e46e35eb6cede8d13fd5c9b2206350cedf7f0b6ac92e1",
+                    "vout": 1,
+                    "was_reserved": false,
+                    "reserved": true,
+                    "reserved_to_block": 175
+                }
+            ]
+        }"#;
+        let funded: FundPsbt = serde_json::from_str(json).unwrap();
+        assert_eq!(funded.feerate_per_kw, 253);
+        assert_eq!(funded.excess_msat.0, 2_000_000);
+        assert!(funded.change_outnum.is_none());
+        let back: FundPsbt =
+            serde_json::from_value(serde_json::to_value(&funded).unwrap()).unwrap();
+        assert_eq!(back.psbt, funded.psbt);
+        assert_eq!(back.reservations.unwrap()[0].reserved_to_block, Some(175));
+
+        let json = r#"{
+            "psbt": "cHNidP8BAFICAAAAAQ",
+            "feerate_per_kw": 7500,
+            "estimated_final_weight": 712,
+            "excess_msat": 0,
+            "change_outnum": 0
+        }"#;
+        let funded: UtxoPsbt = serde_json::from_str(json).unwrap();
+        let back: UtxoPsbt =
+            serde_json::from_value(serde_json::to_value(&funded).unwrap()).unwrap();
+        assert_eq!(back.change_outnum, Some(0));
+        assert_eq!(back.estimated_final_weight, 712);
+        assert!(back.reservations.is_none());
+    }
+
+    #[test]
+    fn recover_round_trip() {
+        let json = r#"{
+            "stubs": [
+                "5ab31c3f74c0a4b6f8bd1b1e3c35a4e1d37bc6f9c7e0a1124f5bcd1e0b3cb7a8",
+                "e6c693c5cdb8bd0e7a4d9af3bd8d4fcd1e5c1a2f2e8b48a0c9a1b8f0db3f6fa1"
+            ]
+        }"#;
+        let recovered: RecoverChannel = serde_json::from_str(json).unwrap();
+        assert_eq!(recovered.stubs.len(), 2);
+        let back: RecoverChannel =
+            serde_json::from_value(serde_json::to_value(&recovered).unwrap()).unwrap();
+        assert_eq!(back.stubs, recovered.stubs);
+
+        let recovered: EmergencyRecover = serde_json::from_str(r#"{"stubs": []}"#).unwrap();
+        assert!(recovered.stubs.is_empty());
+        let back: EmergencyRecover =
+            serde_json::from_value(serde_json::to_value(&recovered).unwrap()).unwrap();
+        assert!(back.stubs.is_empty());
+    }
+
+    #[test]
+    fn staticbackup_round_trip() {
+        let json = r#"{
+            "scb": [
+                "0000000000000001c4b45bd5ee777ba0b2d1783d793755678b97fc9cde3d3f9d3f94a0aead6a8f4c00017f000001a30c",
+                "0000000000000002e5bbfa00fa7d60205b075b6893e0d1f211add4d9cb276c6dc0bd4be01639a401000170000001a30c"
+            ]
+        }"#;
+        let backup: StaticBackup = serde_json::from_str(json).unwrap();
+        assert_eq!(backup.scb.len(), 2);
+        assert!(backup.scb[1].starts_with("0000000000000002"));
+        let back: StaticBackup =
+            serde_json::from_value(serde_json::to_value(&backup).unwrap()).unwrap();
+        assert_eq!(back.scb, backup.scb);
+    }
+
+    #[test]
+    fn listfunds_balance() {
+        let output = |amount: u64, status: &str| {
+            serde_json::json!({
+                "txid": "05985cb4ec99fd3a8a5e46e35eb6cede8d13fd5c9b2206350cedf7f0b6ac92e1",
+                "output": 0,
+                "amount_msat": format!("{}msat", amount),
+                "scriptpubkey": "0014a2e8b48a0c9a1b8f0db3f6fa1e6c693c5cdb8bd0",
+                "status": status,
+                "reserved": false
+            })
+        };
+        let channel = |our: u64, connected: bool, state: &str| {
+            serde_json::json!({
+                "peer_id": "02aa",
+                "our_amount_msat": our,
+                "amount_msat": 10_000_000_000u64,
+                "funding_txid": "05985cb4ec99fd3a8a5e46e35eb6cede8d13fd5c9b2206350cedf7f0b6ac92e1",
+                "funding_output": 1,
+                "connected": connected,
+                "state": state
+            })
+        };
+        let funds: ListFunds = serde_json::from_value(serde_json::json!({
+            "outputs": [
+                output(100_000_000, "confirmed"),
+                output(250_000_000, "confirmed"),
+                output(7_000, "unconfirmed"),
+                output(5_000_000, "immature"),
+                output(999_999, "spent"),
+            ],
+            "channels": [
+                channel(4_000_000_000, true, "CHANNELD_NORMAL"),
+                channel(1_000_000_000, false, "CHANNELD_NORMAL"),
+                channel(500_000_000, true, "CHANNELD_AWAITING_LOCKIN"),
+            ]
+        }))
+        .unwrap();
+        assert_eq!(
+            funds.balance(),
+            Balance {
+                onchain_confirmed_msat: common::MSat(350_000_000),
+                onchain_unconfirmed_msat: common::MSat(5_007_000),
+                channel_spendable_msat: common::MSat(4_000_000_000),
+                channel_total_msat: common::MSat(5_500_000_000),
+            }
+        );
+        assert_eq!(
+            ListFunds {
+                outputs: vec![],
+                channels: vec![]
+            }
+            .balance(),
+            Balance::default()
+        );
+    }
+
+    #[test]
+    fn datastore_round_trip() {
+        let json = r#"{
+            "key": ["myplugin", "greeting"],
+            "generation": 0,
+            "hex": "68656c6c6f",
+            "string": "hello"
+        }"#;
+        let stored: DataStore = serde_json::from_str(json).unwrap();
+        assert_eq!(stored.key, vec!["myplugin", "greeting"]);
+        assert_eq!(stored.string.as_deref(), Some("hello"));
+        let back: DataStore =
+            serde_json::from_value(serde_json::to_value(&stored).unwrap()).unwrap();
+        assert_eq!(back.hex, stored.hex);
+        assert_eq!(back.generation, Some(0));
+
+        let json = r#"{
+            "datastore": [
+                {"key": ["myplugin"]},
+                {"key": ["blob"], "generation": 3, "hex": "00ff"}
+            ]
+        }"#;
+        let list: ListDataStore = serde_json::from_str(json).unwrap();
+        assert!(list.datastore[0].hex.is_none() && list.datastore[0].generation.is_none());
+        let blob = &list.datastore[1];
+        assert_eq!(
+            (blob.hex.as_deref(), blob.string.as_deref()),
+            (Some("00ff"), None)
+        );
+        let back: ListDataStore =
+            serde_json::from_value(serde_json::to_value(&list).unwrap()).unwrap();
+        assert_eq!(back.datastore[1].generation, Some(3));
+    }
+
+    #[test]
+    fn income_events_round_trip() {
+        let json = r#"{
+            "income_events": [
+                {
+                    "account": "wallet",
+                    "tag": "deposit",
+                    "credit_msat": 200000000000,
+                    "debit_msat": 0,
+                    "currency": "bcrt",
+                    "timestamp": 1686954564,
+                    "description": "onchain deposit",
+                    "outpoint": "05985cb4ec99fd3a8a5e46e35eb6cede8d13fd5c9b2206350cedf7f0b6ac92e1:1"
+                },
+                {
+                    "account": "wallet",
+                    "tag": "onchain_fee",
+                    "credit_msat": "0msat",
+                    "debit_msat": "1860000msat",
+                    "currency": "bcrt",
+                    "timestamp": 1686954600,
+                    "txid": "4f5bcd1e0b3cb7a8a8d0d2c7e4c0b6f53d0e4b8e3c35a4e1d37bc6f9c7e0a112"
+                },
+                {
+                    "account": "a397dd9b3e44afcb67f3f3ce1d649b74a8ade63e35505985e4cc1828634f69a2",
+                    "tag": "invoice",
+                    "credit_msat": 500000,
+                    "debit_msat": 0,
+                    "currency": "bcrt",
+                    "timestamp": 1686954700,
+                    "payment_id": "f56e0ba0e495ae7b4d6fa4c0cc9b2ffe3e1220a18a6bd4eaee2a2c1355bfb220"
+                }
+            ]
+        }"#;
+        let income: ListIncome = serde_json::from_str(json).unwrap();
+        assert_eq!(income.income_events.len(), 3);
+        let fee = &income.income_events[1];
+        assert_eq!(fee.debit_msat, common::MSat(1_860_000));
+        assert!(fee.outpoint.is_none() && fee.txid.is_some());
+        let back: ListIncome =
+            serde_json::from_value(serde_json::to_value(&income).unwrap()).unwrap();
+        assert_eq!(back.income_events[0].credit_msat.to_sat(), 200_000_000);
+        assert_eq!(
+            back.income_events[0].outpoint,
+            income.income_events[0].outpoint
+        );
+        assert_eq!(
+            back.income_events[2].payment_id,
+            income.income_events[2].payment_id
+        );
+        assert_eq!(back.income_events[2].timestamp, 1686954700);
+    }
+
+    #[test]
+    fn getlog_entries() {
+        let json = r#"{
+            "created_at": "1686954000.123456789",
+            "bytes_used": 1966727,
+            "bytes_max": 10485760,
+            "log": [
+                {"type": "SKIPPED", "num_skipped": 143},
+                {
+                    "type": "INFO",
+                    "time": "0.479951",
+                    "source": "lightningd",
+                    "log": "Server started with public key 02aa"
+                },
+                {
+                    "type": "IO_OUT",
+                    "time": "12.000381",
+                    "node_id": "02bb",
+                    "source": "connectd",
+                    "log": "peer_out WIRE_PING",
+                    "data": "0012000100000002"
+                },
+                {"type": "NOTICE_FROM_THE_FUTURE", "time": "13.0"}
+            ]
+        }"#;
+        let log: GetLog = serde_json::from_str(json).unwrap();
+        assert_eq!(log.bytes_used, 1966727);
+        match log.log[0] {
+            LogEntry::Skipped { num_skipped } => assert_eq!(num_skipped, 143),
+            ref other => panic!("expected skipped entry, got {:?}", other),
+        }
+        match log.log[1] {
+            LogEntry::Info(ref message) => {
+                assert_eq!(message.source, "lightningd");
+                assert!(message.node_id.is_none());
+            }
+            ref other => panic!("expected info entry, got {:?}", other),
+        }
+        match log.log[2] {
+            LogEntry::IoOut(ref io) => assert_eq!(io.data.as_deref(), Some("0012000100000002")),
+            ref other => panic!("expected io entry, got {:?}", other),
+        }
+        assert!(matches!(log.log[3], LogEntry::Unknown));
+
+        let ser = serde_json::to_value(&log.log[..3]).unwrap();
+        assert_eq!(
+            ser[0],
+            serde_json::json!({"type": "SKIPPED", "num_skipped": 143})
+        );
+        assert_eq!(ser[2]["type"], "IO_OUT");
+        let back: Vec<LogEntry> = serde_json::from_value(ser).unwrap();
+        match back[2] {
+            LogEntry::IoOut(ref io) => assert_eq!(io.node_id.as_deref(), Some("02bb")),
+            ref other => panic!("expected io entry, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn sendcustommsg_round_trip() {
+        let json = r#"{"status": "Message sent to connectd for delivery"}"#;
+        let sent: SendCustomMsg = serde_json::from_str(json).unwrap();
+        let back: SendCustomMsg =
+            serde_json::from_value(serde_json::to_value(&sent).unwrap()).unwrap();
+        assert_eq!(back.status, "Message sent to connectd for delivery");
+    }
+
+    #[test]
+    fn multifundchannel_round_trip() {
+        let json = r#"{
+            "tx": "02000000000101fc6628c26c5899fe",
+            "txid": "05985cb4ec99fd3a8a5e46e35eb6cede8d13fd5c9b2206350cedf7f0b6ac92e1",
+            "channel_ids": [
+                {
+                    "id": "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59",
+                    "channel_id": "e1c92acb0f7fedc0350622b9e5fd13d8dece6be5ae6e858a3afd99ecb45c9805",
+                    "outnum": 1
+                }
+            ],
+            "failed": [
+                {
+                    "id": "035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d",
+                    "method": "connect",
+                    "error": {"code": 401, "message": "All addresses failed"}
+                }
+            ]
+        }"#;
+        let funded: MultiFundChannel = serde_json::from_str(json).unwrap();
+        assert_eq!(funded.channel_ids[0].outnum, 1);
+        assert_eq!(funded.failed[0].method, "connect");
+        assert_eq!(
+            funded.failed[0].error.code_kind(),
+            crate::error::RpcErrorKind::AllAddressesFailed
+        );
+        let back: MultiFundChannel =
+            serde_json::from_value(serde_json::to_value(&funded).unwrap()).unwrap();
+        assert_eq!(
+            back.channel_ids[0].channel_id,
+            funded.channel_ids[0].channel_id
+        );
+        assert_eq!(back.failed[0].error.message, "All addresses failed");
+
+        let funded: MultiFundChannel =
+            serde_json::from_str(r#"{"tx": "0200", "txid": "05985cb4", "channel_ids": []}"#)
+                .unwrap();
+        assert!(funded.failed.is_empty());
+    }
+
+    #[test]
+    fn splice_init_round_trip() {
+        let json = r#"{"psbt": "cHNidP8BAgQCAAAAAQMEbwAAAAEEAQABBQEBAQYBAwH7BAIAAAAA"}"#;
+        let init: SpliceInit = serde_json::from_str(json).unwrap();
+        let back: SpliceInit =
+            serde_json::from_value(serde_json::to_value(&init).unwrap()).unwrap();
+        assert_eq!(back.psbt, init.psbt);
+    }
+
+    #[test]
+    fn splice_update_round_trip() {
+        let json = r#"{"psbt": "cHNidP8BAgQCAAAAAQMEbwAAAAEEAQEBBQECAQYBAwH7BAIAAAAA", "commitments_secured": true}"#;
+        let update: SpliceUpdate = serde_json::from_str(json).unwrap();
+        assert!(update.commitments_secured);
+        let back: SpliceUpdate =
+            serde_json::from_value(serde_json::to_value(&update).unwrap()).unwrap();
+        assert_eq!(back.psbt, update.psbt);
+        assert!(back.commitments_secured);
+    }
+
+    #[test]
+    fn splice_signed_round_trip() {
+        let json = r#"{
+            "tx": "02000000000101",
+            "txid": "f00d5a4e5e5d8bb0c8e3c8a3fb2f4aec5a5d0e10e8c2ad6b04380d3ebaf9cb46"
+        }"#;
+        let signed: SpliceSigned = serde_json::from_str(json).unwrap();
+        let back: SpliceSigned =
+            serde_json::from_value(serde_json::to_value(&signed).unwrap()).unwrap();
+        assert_eq!(back.tx, signed.tx);
+        assert_eq!(back.txid, signed.txid);
+    }
+
+    #[test]
+    fn renepaystatus_round_trip() {
+        let json = r#"{
+            "paystatus": [
+                {
+                    "bolt11": "lnbcrt100n1pjq7cu0sp5",
+                    "payment_hash": "f56e0ba0e495ae7b4d6fa4c0cc9b2ffe3e1220a18a6bd4eaee2a2c1355bfb220",
+                    "payment_preimage": "7b0ef6f6b2d3ea5ff1ea2bd3d5bd04e5a4eedb4b3b5bb0dd1b43e3f1d0c3b7c2",
+                    "destination": "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59",
+                    "created_at": 1690000000,
+                    "groupid": 1,
+                    "parts": 2,
+                    "amount_msat": 10000000,
+                    "amount_sent_msat": 10000202,
+                    "status": "complete",
+                    "notes": ["Success", "Got the preimage"]
+                },
+                {
+                    "bolt11": "lnbcrt200n1pjq7cu0sp5",
+                    "payment_hash": "0254a0c9a6c9faf2b2c1b31a4231e3cfbe73e68bbf1e5c13e14e4e5e4c1e37c9",
+                    "created_at": 1690000100.5,
+                    "groupid": 3,
+                    "amount_msat": 20000000,
+                    "status": "pending"
+                }
+            ]
+        }"#;
+        let status: RenePayStatus = serde_json::from_str(json).unwrap();
+        assert_eq!(status.paystatus[0].parts, Some(2));
+        assert_eq!(
+            status.paystatus[0].amount_sent_msat,
+            Some(common::MSat(10_000_202))
+        );
+        assert_eq!(status.paystatus[1].status, PayStatus::Pending);
+        assert!(status.paystatus[1].notes.is_empty());
+        let back: RenePayStatus =
+            serde_json::from_value(serde_json::to_value(&status).unwrap()).unwrap();
+        assert_eq!(back.paystatus[0].notes, status.paystatus[0].notes);
+        assert_eq!(back.paystatus[1].groupid, 3);
+    }
+
+    #[test]
+    fn sql_round_trip() {
+        let json = r#"{
+            "rows": [
+                ["022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59", 1],
+                ["035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d", 0]
+            ]
+        }"#;
+        let result: Sql = serde_json::from_str(json).unwrap();
+        assert_eq!(result.rows.len(), 2);
+        assert_eq!(result.rows[0][1], serde_json::json!(1));
+        assert_eq!(
+            result.rows[1][0].as_str(),
+            Some("035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d")
+        );
+        let back: Sql = serde_json::from_value(serde_json::to_value(&result).unwrap()).unwrap();
+        assert_eq!(back.rows, result.rows);
+
+        let json = r#"{
+            "schemas": [
+                {
+                    "tablename": "peers",
+                    "columns": [
+                        {"name": "id", "type": "BLOB"},
+                        {"name": "connected", "type": "INTEGER"}
+                    ],
+                    "indices": [["id"]]
+                }
+            ]
+        }"#;
+        let schemas: ListSqlSchemas = serde_json::from_str(json).unwrap();
+        let columns = &schemas.schemas[0].columns;
+        assert_eq!(
+            (&columns[1].name[..], &columns[1].type_[..]),
+            ("connected", "INTEGER")
+        );
+        assert_eq!(schemas.schemas[0].indices, vec![vec!["id".to_owned()]]);
+    }
+
+    #[test]
+    fn listhtlcs_both_directions() {
+        let json = r#"{
+            "htlcs": [
+                {
+                    "short_channel_id": "103x1x0",
+                    "id": 0,
+                    "expiry": 125,
+                    "direction": "out",
+                    "amount_msat": 100000000,
+                    "payment_hash": "f56e0ba0e495ae7b4d6fa4c0cc9b2ffe3e1220a18a6bd4eaee2a2c1355bfb220",
+                    "state": "SENT_REMOVE_ACK_REVOCATION"
+                },
+                {
+                    "short_channel_id": "108x1x1",
+                    "id": 3,
+                    "expiry": 140,
+                    "direction": "in",
+                    "amount_msat": 5000,
+                    "payment_hash": "0254a0c9a6c9faf2b2c1b31a4231e3cfbe73e68bbf1e5c13e14e4e5e4c1e37c9",
+                    "state": "RCVD_ADD_ACK_REVOCATION"
+                },
+                {
+                    "short_channel_id": "108x1x1",
+                    "id": 4,
+                    "expiry": 141,
+                    "direction": "in",
+                    "amount_msat": 6000,
+                    "payment_hash": "0254a0c9a6c9faf2b2c1b31a4231e3cfbe73e68bbf1e5c13e14e4e5e4c1e37c9",
+                    "state": "SOME_FUTURE_STATE"
+                }
+            ]
+        }"#;
+        let list: ListHtlcs = serde_json::from_str(json).unwrap();
+        let htlcs = &list.htlcs;
+        assert_eq!(htlcs[0].direction, HtlcDirection::Out);
+        assert_eq!(htlcs[0].state, HtlcState::SentRemoveAckRevocation);
+        assert_eq!(htlcs[1].direction, HtlcDirection::In);
+        assert_eq!(htlcs[1].state, HtlcState::RcvdAddAckRevocation);
+        assert_eq!(htlcs[1].short_channel_id.outnum(), 1);
+        assert_eq!(
+            htlcs[2].state,
+            HtlcState::Unknown("SOME_FUTURE_STATE".to_owned())
+        );
+        let back: ListHtlcs = serde_json::from_value(serde_json::to_value(&list).unwrap()).unwrap();
+        assert_eq!(back.htlcs[2].state.as_str(), "SOME_FUTURE_STATE");
+        assert_eq!(back.htlcs[1].amount_msat, common::MSat(5000));
+    }
+
+    #[test]
+    fn wait_round_trip() {
+        let json = r#"{
+            "subsystem": "invoices",
+            "updated": 3,
+            "invoices": {"label": "coffee", "status": "paid"}
+        }"#;
+        let wait: Wait = serde_json::from_str(json).unwrap();
+        assert_eq!(wait.subsystem, common::WaitSubsystem::Invoices);
+        assert_eq!((wait.created, wait.updated), (None, Some(3)));
+        assert_eq!(wait.details.as_ref().unwrap()["status"], "paid");
+        let back: Wait = serde_json::from_value(serde_json::to_value(&wait).unwrap()).unwrap();
+        assert_eq!(back.updated, Some(3));
+        assert_eq!(back.details, wait.details);
+
+        let wait: Wait = serde_json::from_str(r#"{"subsystem": "htlcs", "created": 9}"#).unwrap();
+        assert_eq!(wait.subsystem.as_str(), "htlcs");
+        assert!(wait.details.is_none());
+    }
+
+    #[test]
+    fn setpsbtversion_round_trip() {
+        let json = r#"{"psbt": "cHNidP8BAFICAAAAAZ38ZijCbFiZ/hvT3DOGZb/VXXraEPYiCXPfLTht7BJ2AQAAAAD9////AQAAAAAAAAAAFgAUjMP+GSZFbx6TO+8ApuOmaz0AAAAA"}"#;
+        let converted: SetPsbtVersion = serde_json::from_str(json).unwrap();
+        let back: SetPsbtVersion =
+            serde_json::from_value(serde_json::to_value(&converted).unwrap()).unwrap();
+        assert_eq!(back.psbt, converted.psbt);
+    }
+
+    #[test]
+    fn signinvoice_round_trip() {
+        let json = r#"{"bolt11": "lnbcrt100n1pjq7cu0sp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygs"}"#;
+        let signed: SignInvoice = serde_json::from_str(json).unwrap();
+        let back: SignInvoice =
+            serde_json::from_value(serde_json::to_value(&signed).unwrap()).unwrap();
+        assert_eq!(back.bolt11, signed.bolt11);
+        assert!(serde_json::from_str::<SignInvoice>(r#"{"invoice": "lnbcrt1"}"#).is_err());
+    }
+}
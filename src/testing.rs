@@ -0,0 +1,266 @@
+// Rust JSON-RPC Library
+// Written by
+//     Wladimir J. van der Laan <laanwj@gmail.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+//! Support for testing code that uses this crate without a running lightningd
+//!
+//! `MockTransport` answers requests with responses queued per method, and records the requests
+//! it received. Only available with the `testing` feature.
+//!
+//! ```
+//! use clightningrpc::testing::MockTransport;
+//! use clightningrpc::LightningRPC;
+//! use serde_json::json;
+//!
+//! // Code under test
+//! fn can_pay(rpc: &mut LightningRPC) -> bool {
+//!     match rpc.listfunds(None) {
+//!         Ok(funds) => !funds.channels.is_empty(),
+//!         Err(_) => false,
+//!     }
+//! }
+//!
+//! let mock = MockTransport::new();
+//! mock.push_result("listfunds", json!({"outputs": [], "channels": []})).unwrap();
+//! let mut rpc = LightningRPC::with_transport(mock.clone());
+//! assert!(!can_pay(&mut rpc));
+//! // Nothing queued any more, so the call fails with "method not found"
+//! assert!(!can_pay(&mut rpc));
+//! assert_eq!(mock.requests().len(), 2);
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::{result_to_response, standard_error, Error, RpcError, StandardError};
+use crate::framing::JsonFramer;
+use crate::transport::RpcTransport;
+use crate::{Request, Response};
+
+#[derive(Debug, Default)]
+struct State {
+    responses: HashMap<String, VecDeque<Response>>,
+    requests: Vec<Request>,
+    framer: JsonFramer,
+    replies: Vec<u8>,
+    connected: bool,
+}
+
+impl State {
+    /// Answer a request with the next response queued for its method
+    fn answer(&mut self, request: Request) -> Response {
+        let queued = self
+            .responses
+            .get_mut(&request.method)
+            .and_then(VecDeque::pop_front);
+        let mut response = queued.unwrap_or_else(|| {
+            let data = Value::from(format!("no response queued for {}", request.method));
+            result_to_response(
+                Err(standard_error(StandardError::MethodNotFound, Some(data))),
+                Value::Null,
+            )
+        });
+        response.id = request.id.clone();
+        self.requests.push(request);
+        response
+    }
+
+    /// Answer a request or batch of requests
+    fn answer_frame(&mut self, frame: &[u8]) -> io::Result<Value> {
+        let invalid = |e| io::Error::new(io::ErrorKind::InvalidData, e);
+        let json: Value = serde_json::from_slice(frame).map_err(invalid)?;
+        let reply = if json.is_array() {
+            let requests: Vec<Request> = serde_json::from_value(json).map_err(invalid)?;
+            let responses: Vec<Response> = requests.into_iter().map(|r| self.answer(r)).collect();
+            serde_json::to_value(responses)
+        } else {
+            let request = serde_json::from_value(json).map_err(invalid)?;
+            serde_json::to_value(self.answer(request))
+        };
+        reply.map_err(invalid)
+    }
+}
+
+/// A transport that answers with canned responses instead of talking to lightningd
+///
+/// Responses are queued per method and used up in order; a request for a method without queued
+/// responses gets a "method not found" error, like lightningd gives for unknown commands. Clones
+/// share the same queues, so one can be handed to `LightningRPC::with_transport` while the test
+/// keeps another to queue more responses and inspect the requests.
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<State>>,
+}
+
+impl MockTransport {
+    /// Create a transport with nothing queued
+    pub fn new() -> MockTransport {
+        MockTransport::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    /// Queue a response for the next call of {method}, its id is replaced by that of the request
+    pub fn push_response(&self, method: &str, response: Response) {
+        self.state()
+            .responses
+            .entry(method.to_owned())
+            .or_default()
+            .push_back(response);
+    }
+
+    /// Queue a successful result for the next call of {method}
+    pub fn push_result<T: Serialize>(&self, method: &str, result: T) -> Result<(), Error> {
+        let result = serde_json::to_value(result)?;
+        self.push_response(method, result_to_response(Ok(result), Value::Null));
+        Ok(())
+    }
+
+    /// Queue an error for the next call of {method}
+    pub fn push_error(&self, method: &str, error: RpcError) {
+        self.push_response(method, result_to_response(Err(error), Value::Null));
+    }
+
+    /// The requests received so far, oldest first
+    pub fn requests(&self) -> Vec<Request> {
+        self.state().requests.clone()
+    }
+}
+
+impl Read for MockTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut state = self.state();
+        if !state.connected {
+            return Err(io::ErrorKind::NotConnected.into());
+        }
+        let n = buf.len().min(state.replies.len());
+        buf[..n].copy_from_slice(&state.replies[..n]);
+        state.replies.drain(..n);
+        Ok(n)
+    }
+}
+
+impl Write for MockTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.state();
+        if !state.connected {
+            return Err(io::ErrorKind::NotConnected.into());
+        }
+        state.framer.push(buf);
+        while let Some(frame) = state.framer.next_frame() {
+            let reply = state.answer_frame(&frame)?;
+            state.replies.extend(reply.to_string().into_bytes());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl RpcTransport for MockTransport {
+    fn connect(&mut self) -> io::Result<()> {
+        let mut state = self.state();
+        state.connected = true;
+        state.framer = JsonFramer::new();
+        state.replies.clear();
+        Ok(())
+    }
+
+    fn disconnect(&mut self) {
+        self.state().connected = false;
+    }
+
+    fn is_connected(&self) -> bool {
+        self.state().connected
+    }
+
+    fn set_timeout(&mut self, _timeout: Option<Duration>) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requests;
+    use crate::LightningRPC;
+    use serde_json::json;
+
+    #[test]
+    fn mock_transport() {
+        let mock = MockTransport::new();
+        mock.push_result(
+            "signmessage",
+            json!({"signature": "aa", "recid": "00", "zbase": "a"}),
+        )
+        .unwrap();
+        mock.push_result(
+            "signmessage",
+            json!({"signature": "bb", "recid": "01", "zbase": "b"}),
+        )
+        .unwrap();
+        mock.push_error(
+            "stop",
+            RpcError {
+                code: -1,
+                message: "not today".to_owned(),
+                data: None,
+            },
+        );
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+
+        assert_eq!(rpc.signmessage("one").unwrap().signature, "aa");
+        match rpc.stop() {
+            Err(Error::Rpc(e)) => assert_eq!(e.message, "not today"),
+            other => panic!("expected rpc error, got {:?}", other),
+        }
+        assert_eq!(rpc.signmessage("two").unwrap().signature, "bb");
+        match rpc.signmessage("three") {
+            Err(Error::Rpc(e)) => assert!(e.is_method_not_found()),
+            other => panic!("expected rpc error, got {:?}", other),
+        }
+
+        let requests = mock.requests();
+        let methods: Vec<&str> = requests.iter().map(|r| &r.method[..]).collect();
+        assert_eq!(
+            methods,
+            ["signmessage", "stop", "signmessage", "signmessage"]
+        );
+        assert_eq!(requests[2].params, json!({"message": "two"}));
+    }
+
+    #[test]
+    fn mock_transport_batch() {
+        let mock = MockTransport::new();
+        mock.push_result("getinfo", true).unwrap();
+        let mut rpc = LightningRPC::with_transport(mock.clone());
+        let mut batch = rpc.batch();
+        batch
+            .call("listfunds", requests::ListFunds { spent: None })
+            .unwrap()
+            .call("getinfo", requests::GetInfo {})
+            .unwrap();
+        let responses = batch.send().unwrap();
+        assert!(responses[0].error.is_some());
+        assert_eq!(responses[1].result, Some(Value::from(true)));
+        assert_eq!(mock.requests().len(), 2);
+    }
+}
@@ -0,0 +1,185 @@
+// Rust JSON-RPC Library
+// Written by
+//     Wladimir J. van der Laan <laanwj@gmail.com>
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+//! Byte-stream transports for the synchronous client
+//!
+//! `client::Client` speaks JSON-RPC over anything implementing `RpcTransport`. The UNIX socket
+//! that lightningd creates is supported through `UnixTransport`, and `TcpTransport` can be used
+//! to reach a socket that was forwarded to a TCP port. Other transports, such as SSH tunnels, can
+//! be added by implementing the trait.
+
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A reconnectable byte stream to lightningd
+///
+/// Reading or writing while not connected fails with `io::ErrorKind::NotConnected`.
+pub trait RpcTransport: Read + Write + Send {
+    /// Open a new connection, closing the current one if any
+    fn connect(&mut self) -> io::Result<()>;
+
+    /// Close the current connection, if any
+    fn disconnect(&mut self);
+
+    /// Whether a connection is open
+    fn is_connected(&self) -> bool;
+
+    /// Set the timeout for individual reads and writes, for the current connection and the
+    /// ones opened later. `None` means block forever.
+    fn set_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()>;
+
+    /// Path of the socket, for transports that connect to one
+    fn socket_path(&self) -> Option<&Path> {
+        None
+    }
+}
+
+fn not_connected() -> io::Error {
+    io::ErrorKind::NotConnected.into()
+}
+
+/// Implement `Read` and `Write` by forwarding to an optional stream
+macro_rules! forward_io {
+    ($transport:ty) => {
+        impl Read for $transport {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                self.stream.as_mut().ok_or_else(not_connected)?.read(buf)
+            }
+        }
+
+        impl Write for $transport {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.stream.as_mut().ok_or_else(not_connected)?.write(buf)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                self.stream.as_mut().ok_or_else(not_connected)?.flush()
+            }
+        }
+    };
+}
+
+/// Transport over the UNIX socket created by lightningd
+#[derive(Debug)]
+pub struct UnixTransport {
+    path: PathBuf,
+    stream: Option<UnixStream>,
+    timeout: Option<Duration>,
+}
+
+impl UnixTransport {
+    /// Create a transport for the socket at `path`, this does not connect yet
+    pub fn new<P: AsRef<Path>>(path: P) -> UnixTransport {
+        UnixTransport {
+            path: path.as_ref().to_path_buf(),
+            stream: None,
+            timeout: None,
+        }
+    }
+}
+
+forward_io!(UnixTransport);
+
+impl RpcTransport for UnixTransport {
+    fn connect(&mut self) -> io::Result<()> {
+        self.stream = None;
+        let stream = UnixStream::connect(&self.path)?;
+        stream.set_read_timeout(self.timeout)?;
+        stream.set_write_timeout(self.timeout)?;
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    fn disconnect(&mut self) {
+        if let Some(stream) = self.stream.take() {
+            // Tell the daemon we are done rather than only closing our end
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    fn set_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        self.timeout = timeout;
+        if let Some(ref stream) = self.stream {
+            stream.set_read_timeout(timeout)?;
+            stream.set_write_timeout(timeout)?;
+        }
+        Ok(())
+    }
+
+    fn socket_path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+}
+
+/// Transport over TCP, for sockets forwarded to a port with e.g. `socat`
+///
+/// The connection is not encrypted or authenticated, anyone who can reach the port has full
+/// control over the node.
+#[derive(Debug)]
+pub struct TcpTransport {
+    addr: String,
+    stream: Option<TcpStream>,
+    timeout: Option<Duration>,
+}
+
+impl TcpTransport {
+    /// Create a transport for `addr`, given as "host:port", this does not connect yet
+    pub fn new<A: Into<String>>(addr: A) -> TcpTransport {
+        TcpTransport {
+            addr: addr.into(),
+            stream: None,
+            timeout: None,
+        }
+    }
+}
+
+forward_io!(TcpTransport);
+
+impl RpcTransport for TcpTransport {
+    fn connect(&mut self) -> io::Result<()> {
+        self.stream = None;
+        let stream = TcpStream::connect(&self.addr[..])?;
+        stream.set_read_timeout(self.timeout)?;
+        stream.set_write_timeout(self.timeout)?;
+        stream.set_nodelay(true)?;
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    fn disconnect(&mut self) {
+        if let Some(stream) = self.stream.take() {
+            // Tell the daemon we are done rather than only closing our end
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    fn set_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        self.timeout = timeout;
+        if let Some(ref stream) = self.stream {
+            stream.set_read_timeout(timeout)?;
+            stream.set_write_timeout(timeout)?;
+        }
+        Ok(())
+    }
+}
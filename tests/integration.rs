@@ -0,0 +1,27 @@
+//! Tests against a running lightningd, enabled with the `integration` feature.
+//!
+//! The socket of the node is taken from the `LIGHTNING_RPC` environment variable, the
+//! keysend recipient (which needs a channel path from the node) from `KEYSEND_DESTINATION`.
+#![cfg(feature = "integration")]
+
+extern crate clightningrpc;
+
+use std::env;
+
+use clightningrpc::lightningrpc::KeySendOptions;
+use clightningrpc::LightningRPC;
+
+fn rpc() -> LightningRPC {
+    LightningRPC::new(env::var("LIGHTNING_RPC").expect("LIGHTNING_RPC not set"))
+}
+
+#[test]
+fn keysend() {
+    let destination = env::var("KEYSEND_DESTINATION").expect("KEYSEND_DESTINATION not set");
+    let res = rpc()
+        .keysend(&destination, 1000, KeySendOptions::default())
+        .unwrap();
+    assert_eq!(res.status, "complete");
+    assert_eq!(res.amount_msat.to_msat(), 1000);
+    assert!(res.amount_sent_msat.to_msat() >= 1000);
+}